@@ -0,0 +1,20 @@
+-- Create refresh_tokens table
+-- Only a SHA-256 hash of each opaque token is stored. Tokens issued from the
+-- same login share a family_id so a replayed token can revoke the whole chain.
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL,
+    token_hash VARCHAR UNIQUE NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ NULL,
+    revoked_at TIMESTAMPTZ NULL,
+    replaced_by UUID NULL REFERENCES refresh_tokens(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
+
+COMMENT ON TABLE refresh_tokens IS 'Hashed, rotating refresh tokens';
+COMMENT ON COLUMN refresh_tokens.used_at IS 'Set when the token was exchanged; a second use revokes the family';
@@ -0,0 +1,18 @@
+-- Access tokens revoked before their expiry ("log out this session")
+CREATE TABLE revoked_tokens (
+    jti VARCHAR PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
+
+-- Per-user cutoff: every token issued at or before revoked_before is rejected ("log out everywhere")
+CREATE TABLE user_token_revocations (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    revoked_before TIMESTAMPTZ NOT NULL
+);
+
+COMMENT ON TABLE revoked_tokens IS 'Access token denylist keyed by jti; rows can be pruned after expires_at';
+COMMENT ON TABLE user_token_revocations IS 'Access tokens issued before this instant are rejected';
@@ -0,0 +1,5 @@
+DROP TRIGGER IF EXISTS role_permissions_bump_permission_version ON role_permissions;
+DROP TRIGGER IF EXISTS user_roles_bump_permission_version ON user_roles;
+DROP FUNCTION IF EXISTS bump_role_permission_version();
+DROP FUNCTION IF EXISTS bump_user_permission_version();
+ALTER TABLE users DROP COLUMN IF EXISTS permission_version;
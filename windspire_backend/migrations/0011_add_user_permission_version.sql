@@ -0,0 +1,41 @@
+-- Per-user permission version, bumped whenever the user's effective roles or permissions change.
+-- Access tokens carry the version they were issued at; older tokens have their permissions re-resolved.
+ALTER TABLE users ADD COLUMN permission_version BIGINT NOT NULL DEFAULT 0;
+
+-- A role was assigned to or removed from a user
+CREATE FUNCTION bump_user_permission_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE users SET permission_version = permission_version + 1 WHERE id = OLD.user_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        UPDATE users SET permission_version = permission_version + 1 WHERE id = NEW.user_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- A permission was granted to or removed from a role: bump every holder of the role
+CREATE FUNCTION bump_role_permission_version() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        UPDATE users SET permission_version = permission_version + 1
+        WHERE id IN (SELECT user_id FROM user_roles WHERE role_id = OLD.role_id);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        UPDATE users SET permission_version = permission_version + 1
+        WHERE id IN (SELECT user_id FROM user_roles WHERE role_id = NEW.role_id);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER user_roles_bump_permission_version
+AFTER INSERT OR UPDATE OR DELETE ON user_roles
+FOR EACH ROW EXECUTE FUNCTION bump_user_permission_version();
+
+CREATE TRIGGER role_permissions_bump_permission_version
+AFTER INSERT OR UPDATE OR DELETE ON role_permissions
+FOR EACH ROW EXECUTE FUNCTION bump_role_permission_version();
+
+COMMENT ON COLUMN users.permission_version IS 'Incremented by triggers whenever user_roles or role_permissions change for this user';
@@ -0,0 +1,33 @@
+-- Service accounts are users that never log in interactively; they authenticate with API keys only
+ALTER TABLE users ADD COLUMN is_service_account BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Create api_keys table
+-- Only a SHA-256 hash of each key is stored. The prefix is the non-secret start
+-- of the key, shown in listings so a leaked key can be identified and revoked.
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    prefix VARCHAR UNIQUE NOT NULL,
+    key_hash VARCHAR UNIQUE NOT NULL,
+    created_by UUID NULL REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NULL,
+    last_used_at TIMESTAMPTZ NULL,
+    revoked_at TIMESTAMPTZ NULL
+);
+
+-- Permissions a key is scoped to
+CREATE TABLE api_key_permissions (
+    api_key_id UUID NOT NULL REFERENCES api_keys(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    PRIMARY KEY (api_key_id, permission_id)
+);
+
+CREATE INDEX idx_api_keys_user_id ON api_keys(user_id);
+CREATE INDEX idx_api_key_permissions_permission_id ON api_key_permissions(permission_id);
+
+COMMENT ON TABLE api_keys IS 'Hashed personal and service account API keys';
+COMMENT ON TABLE api_key_permissions IS 'Permissions an API key is limited to';
+COMMENT ON COLUMN api_keys.expires_at IS 'NULL for keys that never expire';
+COMMENT ON COLUMN users.is_service_account IS 'Non-interactive account whose permissions come from its API keys';
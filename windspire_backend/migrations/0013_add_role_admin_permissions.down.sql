@@ -0,0 +1,4 @@
+DELETE FROM permissions WHERE id IN (
+    '01964081-4000-0000-0000-000000000001',
+    '01964081-4000-0000-0000-000000000002'
+);
@@ -0,0 +1,9 @@
+-- Permissions for the role administration API (/api/admin)
+INSERT INTO permissions (id, name, description, resource, action) VALUES
+('01964081-4000-0000-0000-000000000001', 'roles:read', 'Read roles, permissions and role assignments', 'roles', 'read'),
+('01964081-4000-0000-0000-000000000002', 'roles:write', 'Manage roles, permissions and role assignments', 'roles', 'write');
+
+-- Admin role gets all permissions
+INSERT INTO role_permissions (role_id, permission_id) VALUES
+('01964081-0000-0000-0000-000000000001', '01964081-4000-0000-0000-000000000001'),
+('01964081-0000-0000-0000-000000000001', '01964081-4000-0000-0000-000000000002');
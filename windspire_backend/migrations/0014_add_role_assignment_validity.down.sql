@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS role_assignment_sweeps;
+
+ALTER TABLE user_roles
+    DROP CONSTRAINT IF EXISTS user_roles_validity_check,
+    DROP COLUMN IF EXISTS assigned_by,
+    DROP COLUMN IF EXISTS valid_until,
+    DROP COLUMN IF EXISTS valid_from;
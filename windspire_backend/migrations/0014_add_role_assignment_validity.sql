@@ -0,0 +1,21 @@
+-- Role assignments can be limited in time, e.g. race officer rights for one weekend.
+-- Assignments outside their window are ignored when resolving a user's roles.
+ALTER TABLE user_roles
+    ADD COLUMN valid_from TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN valid_until TIMESTAMPTZ NULL,
+    ADD COLUMN assigned_by UUID NULL REFERENCES users(id) ON DELETE SET NULL,
+    ADD CONSTRAINT user_roles_validity_check CHECK (valid_until IS NULL OR valid_until > valid_from);
+
+CREATE INDEX idx_user_roles_valid_from ON user_roles(valid_from);
+CREATE INDEX idx_user_roles_valid_until ON user_roles(valid_until) WHERE valid_until IS NOT NULL;
+
+-- How far the background sweep has bumped the permission version of users whose
+-- assignments started or ended. A single row, shared by all instances.
+CREATE TABLE role_assignment_sweeps (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    swept_until TIMESTAMPTZ NOT NULL
+);
+
+INSERT INTO role_assignment_sweeps (swept_until) VALUES (NOW());
+
+COMMENT ON TABLE role_assignment_sweeps IS 'Watermark of the role assignment validity sweep';
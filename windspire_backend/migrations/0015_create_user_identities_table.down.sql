@@ -0,0 +1,5 @@
+COMMENT ON COLUMN users.provider_id IS NULL;
+
+ALTER TABLE users ADD CONSTRAINT unique_provider_user UNIQUE (provider_id, provider_name);
+
+DROP TABLE IF EXISTS user_identities;
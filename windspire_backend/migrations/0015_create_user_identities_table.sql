@@ -0,0 +1,27 @@
+-- One row per login identity (provider + subject) linked to a user, so a user can
+-- sign in with several providers. Identities are looked up here, not on users.
+CREATE TABLE user_identities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider_name VARCHAR NOT NULL,
+    provider_subject VARCHAR NOT NULL,
+    email VARCHAR NULL,
+    email_verified BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ NULL,
+    CONSTRAINT unique_provider_identity UNIQUE (provider_name, provider_subject)
+);
+
+CREATE INDEX idx_user_identities_user_id ON user_identities(user_id);
+
+-- Existing links; whether their email was verified is unknown
+INSERT INTO user_identities (user_id, provider_name, provider_subject, email, created_at)
+SELECT id, provider_name, provider_id, email, COALESCE(created_at, NOW())
+FROM users
+WHERE provider_id IS NOT NULL AND provider_name IS NOT NULL;
+
+-- users.provider_id/provider_name now only record the identity of the most recent sign-in
+ALTER TABLE users DROP CONSTRAINT IF EXISTS unique_provider_user;
+
+COMMENT ON TABLE user_identities IS 'Login identities linked to users, one per provider subject';
+COMMENT ON COLUMN users.provider_id IS 'Provider subject of the most recent sign-in; see user_identities';
@@ -0,0 +1,6 @@
+DELETE FROM permissions WHERE id = '01964081-3000-0000-0000-000000000004';
+DELETE FROM roles WHERE id = '01964081-0000-0000-0000-000000000004';
+
+ALTER TABLE users
+    DROP CONSTRAINT IF EXISTS users_account_status_check,
+    DROP COLUMN IF EXISTS account_status;
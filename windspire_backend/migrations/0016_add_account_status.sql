@@ -0,0 +1,26 @@
+-- Accounts signed up without a verified email are restricted or pending until
+-- the user signs in with a verified email (see UNVERIFIED_EMAIL_POLICY)
+ALTER TABLE users
+    ADD COLUMN account_status VARCHAR NOT NULL DEFAULT 'active',
+    ADD CONSTRAINT users_account_status_check
+        CHECK (account_status IN ('active', 'restricted', 'pending'));
+
+-- Read-only access for restricted accounts
+INSERT INTO roles (id, name, description) VALUES
+('01964081-0000-0000-0000-000000000004', 'restricted', 'Read-only access until the email address is verified');
+
+INSERT INTO role_permissions (role_id, permission_id) VALUES
+('01964081-0000-0000-0000-000000000004', '01964081-1000-0000-0000-000000000004'), -- users:read_own
+('01964081-0000-0000-0000-000000000004', '01964081-2000-0000-0000-000000000001'), -- countries:read
+('01964081-0000-0000-0000-000000000004', '01964081-3000-0000-0000-000000000001'); -- boats:read
+
+-- Registering boats (/api/boats/my) was open to every signed-in user
+INSERT INTO permissions (id, name, description, resource, action) VALUES
+('01964081-3000-0000-0000-000000000004', 'boats:create', 'Register own boats', 'boats', 'create');
+
+INSERT INTO role_permissions (role_id, permission_id) VALUES
+('01964081-0000-0000-0000-000000000001', '01964081-3000-0000-0000-000000000004'), -- admin
+('01964081-0000-0000-0000-000000000002', '01964081-3000-0000-0000-000000000004'), -- moderator
+('01964081-0000-0000-0000-000000000003', '01964081-3000-0000-0000-000000000004'); -- user
+
+COMMENT ON COLUMN users.account_status IS 'active, restricted or pending (email not verified)';
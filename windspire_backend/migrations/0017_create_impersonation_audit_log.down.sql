@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS impersonation_audit_log;
+
+DELETE FROM permissions WHERE id = '01964081-1000-0000-0000-000000000006';
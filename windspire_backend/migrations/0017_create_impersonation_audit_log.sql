@@ -0,0 +1,28 @@
+-- Lets support staff sign in as another user to see what they see
+INSERT INTO permissions (id, name, description, resource, action) VALUES
+('01964081-1000-0000-0000-000000000006', 'users:impersonate', 'Act as another user with a short-lived token', 'users', 'impersonate');
+
+INSERT INTO role_permissions (role_id, permission_id) VALUES
+('01964081-0000-0000-0000-000000000001', '01964081-1000-0000-0000-000000000006'); -- admin
+
+-- Every impersonation token issued and every request made with one. No foreign
+-- keys, so the trail outlives the users involved.
+CREATE TABLE impersonation_audit_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event VARCHAR NOT NULL CHECK (event IN ('token_issued', 'request')),
+    actor_id UUID NOT NULL,
+    actor_email VARCHAR NOT NULL,
+    target_user_id UUID NOT NULL,
+    token_id VARCHAR NOT NULL,
+    method VARCHAR NOT NULL,
+    path VARCHAR NOT NULL,
+    status_code INTEGER NULL,
+    reason VARCHAR NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_impersonation_audit_log_actor_id ON impersonation_audit_log(actor_id, created_at);
+CREATE INDEX idx_impersonation_audit_log_target_user_id ON impersonation_audit_log(target_user_id, created_at);
+
+COMMENT ON TABLE impersonation_audit_log IS 'Audit trail of impersonation tokens and the requests made with them';
+COMMENT ON COLUMN impersonation_audit_log.status_code IS 'Response status; NULL if the request did not complete';
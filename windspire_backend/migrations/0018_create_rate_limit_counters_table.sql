@@ -0,0 +1,13 @@
+-- Request counters shared by all instances when RATE_LIMIT_STORE=postgres.
+-- One row per key (budget + client) and fixed window; old windows are pruned.
+CREATE UNLOGGED TABLE rate_limit_counters (
+    key VARCHAR NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    count INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (key, window_start)
+);
+
+CREATE INDEX idx_rate_limit_counters_expires_at ON rate_limit_counters(expires_at);
+
+COMMENT ON TABLE rate_limit_counters IS 'Fixed-window request counters for rate limiting; rows can be pruned after expires_at';
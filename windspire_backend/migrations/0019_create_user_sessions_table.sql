@@ -0,0 +1,20 @@
+-- One row per login, so users can see where they are signed in and end a session.
+-- A session shares its id with the refresh token family of the login, and access
+-- tokens carry it as their `sid` claim.
+CREATE TABLE user_sessions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_agent VARCHAR NULL,
+    ip_address VARCHAR NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX idx_user_sessions_user_id ON user_sessions(user_id);
+CREATE INDEX idx_user_sessions_revoked_at ON user_sessions(revoked_at) WHERE revoked_at IS NOT NULL;
+
+COMMENT ON TABLE user_sessions IS 'Logins of a user; the id is also the refresh token family id';
+COMMENT ON COLUMN user_sessions.expires_at IS 'Expiry of the newest refresh token of the session';
+COMMENT ON COLUMN user_sessions.revoked_at IS 'Set when the session was ended; its access tokens are rejected';
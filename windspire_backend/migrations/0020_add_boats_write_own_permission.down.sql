@@ -0,0 +1 @@
+DELETE FROM permissions WHERE id = '01964081-3000-0000-0000-000000000005';
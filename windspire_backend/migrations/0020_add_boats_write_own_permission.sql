@@ -0,0 +1,8 @@
+-- Boat owners may update their own boats (PUT /api/v1/boats/{boat_id})
+INSERT INTO permissions (id, name, description, resource, action) VALUES
+('01964081-3000-0000-0000-000000000005', 'boats:write_own', 'Update own boats', 'boats', 'write_own');
+
+INSERT INTO role_permissions (role_id, permission_id) VALUES
+('01964081-0000-0000-0000-000000000001', '01964081-3000-0000-0000-000000000005'), -- admin
+('01964081-0000-0000-0000-000000000002', '01964081-3000-0000-0000-000000000005'), -- moderator
+('01964081-0000-0000-0000-000000000003', '01964081-3000-0000-0000-000000000005'); -- user
@@ -1,12 +1,16 @@
+pub mod openapi;
+
 use crate::application::handlers::boat_owner_handlers::{
     add_owner_to_boat, get_boats_for_user, get_owners_for_boat, remove_owner_from_boat,
 };
 use axum::{
+    http::header,
     middleware,
     routing::{delete, get, post, put},
     Router,
 };
 use tower_http::cors::CorsLayer;
+use utoipa_scalar::{Scalar, Servable};
 
 use crate::application::{
     commands::{
@@ -17,12 +21,41 @@ use crate::application::{
         update_boat_command::update_boat_command, update_country_command::update_country_command,
         update_user_command::update_user_command,
     },
+    error::AppError,
+    handlers::admin_handlers::{
+        assign_user_role_handler, create_permission_handler, create_role_handler,
+        delete_permission_handler, delete_role_handler, get_role_handler,
+        grant_role_permission_handler, list_permissions_handler, list_roles_handler,
+        list_user_roles_handler, remove_user_role_handler, revoke_role_permission_handler,
+        update_permission_handler, update_role_handler,
+    },
+    handlers::api_key_handlers::{
+        create_api_key_handler, create_service_account_api_key_handler,
+        create_service_account_handler, list_api_keys_handler,
+        list_service_account_api_keys_handler, list_service_accounts_handler,
+        revoke_api_key_handler, revoke_service_account_api_key_handler,
+    },
     handlers::auth_handlers::{
-        firebase_auth_handler, logout_handler, me_handler, refresh_token_handler,
+        firebase_auth_handler, identity_provider_auth_handler, jwks_handler, logout_all_handler,
+        logout_handler, me_handler, refresh_token_handler, revoke_token_handler,
+        test_issuer_token_handler,
+    },
+    handlers::health_handlers::health_handler,
+    handlers::identity_handlers::{
+        link_identity_handler, list_identities_handler, unlink_identity_handler,
     },
+    handlers::impersonation_handlers::{
+        impersonate_user_handler, list_impersonation_audit_handler,
+    },
+    handlers::session_handlers::{list_sessions_handler, revoke_session_handler},
     middleware::{
         auth_middleware::jwt_auth_middleware,
-        rbac_middleware::{require_boats_write, require_permission},
+        correlation_id::{correlation_id_middleware, X_CORRELATION_ID},
+        deprecation::{deprecation_middleware, Deprecation, DEPRECATION, SUNSET},
+        impersonation::NotImpersonating,
+        ownership::{BoatOwned, SelfOwned},
+        rate_limit::{rate_limit_middleware, RateLimit},
+        rbac_middleware::{RequireOwnPermission, RequirePermission},
     },
     queries::{
         get_boats_query::get_boats_query, get_countries_query::get_countries_query,
@@ -33,7 +66,14 @@ use crate::application::{
     },
 };
 
+use crate::application::approuter::openapi::{api_doc, openapi_json_handler};
+use crate::application::config::AuthMode;
+use crate::application::services::rate_limit_service::RateLimitBudget;
 use crate::application::state::AppState;
+use crate::domain::models::rbac::{
+    BoatsCreate, BoatsWrite, CountriesDelete, CountriesWrite, RolesRead, RolesWrite, UsersDelete,
+    UsersImpersonate, UsersRead, UsersWrite,
+};
 
 pub fn create_router(app_state: AppState) -> Router {
     // CORS configuration from environment/config
@@ -65,87 +105,336 @@ pub fn create_router(app_state: AppState) -> Router {
         .allow_origin(cors_origins)
         .allow_methods(cors_methods)
         .allow_headers(cors_headers)
+        .expose_headers([X_CORRELATION_ID, DEPRECATION, SUNSET, header::LINK])
         .allow_credentials(false);
 
-    // Public routes (no authentication required)
-    let public_routes = Router::new()
-        .route("/health", get(|| async { "Backend is running!" }))
-        .route("/auth/firebase", post(firebase_auth_handler))
-        .route("/auth/refresh", post(refresh_token_handler));
+    // The paths without a version are v1 under its old prefix, for clients
+    // that cannot be updated. They are only announced as deprecated once a
+    // date is configured.
+    let mut unversioned_routes = v1_routes(&app_state);
+    let api_versions = &app_state.config.api_versions;
+    if let Some(deprecated_at) = api_versions.unversioned_deprecated_at {
+        let mut deprecation = Deprecation::new(deprecated_at).successor("/api/v1");
+        if let Some(sunset) = api_versions.unversioned_sunset {
+            deprecation = deprecation.sunset(sunset);
+        }
+        unversioned_routes = unversioned_routes.layer(middleware::from_fn_with_state(
+            deprecation,
+            deprecation_middleware,
+        ));
+    }
+
+    // Each version is a separate route tree: when v2 changes an endpoint,
+    // v1 keeps its handler until v1 itself is retired
+    Router::new()
+        .nest("/api/v1", v1_routes(&app_state))
+        .nest("/api", unversioned_routes)
+        .layer(cors)
+        .layer(middleware::from_fn(correlation_id_middleware))
+        .with_state(app_state)
+}
+
+/// Version 1 of the API, nested at `/api/v1`
+fn v1_routes(app_state: &AppState) -> Router<AppState> {
+    // Each group of routes draws from its own rate limit budget
+    let rate_limit = |budget| {
+        middleware::from_fn_with_state(RateLimit::new(app_state, budget), rate_limit_middleware)
+    };
+
+    // Public routes (no authentication required, rate limited per client IP)
+    let mut public_routes = Router::new()
+        .route(
+            "/health",
+            get(health_handler).route_layer(rate_limit(RateLimitBudget::Public)),
+        )
+        .route(
+            "/openapi.json",
+            get(openapi_json_handler).route_layer(rate_limit(RateLimitBudget::Public)),
+        )
+        .route(
+            "/.well-known/jwks.json",
+            get(jwks_handler).route_layer(rate_limit(RateLimitBudget::Public)),
+        )
+        .route(
+            "/auth/firebase",
+            post(firebase_auth_handler).route_layer(rate_limit(RateLimitBudget::Login)),
+        )
+        .route(
+            "/auth/providers/{provider}",
+            post(identity_provider_auth_handler).route_layer(rate_limit(RateLimitBudget::Login)),
+        )
+        .route(
+            "/auth/refresh",
+            post(refresh_token_handler).route_layer(rate_limit(RateLimitBudget::Refresh)),
+        )
+        .route(
+            "/auth/revoke",
+            post(revoke_token_handler).route_layer(rate_limit(RateLimitBudget::Refresh)),
+        );
+
+    if app_state.config.auth_mode == AuthMode::TestIssuer {
+        public_routes = public_routes.route(
+            "/auth/test-issuer/token",
+            post(test_issuer_token_handler).route_layer(rate_limit(RateLimitBudget::Login)),
+        );
+    }
+
+    // Interactive API documentation rendering the OpenAPI document
+    let docs_routes = Router::from(Scalar::with_url(
+        "/docs",
+        api_doc(app_state.config.auth_mode),
+    ))
+    .route_layer(rate_limit(RateLimitBudget::Public));
 
-    // Protected routes (authentication required)
+    // Protected routes (authentication required, rate limited per user)
     let protected_routes = Router::new()
         .route("/auth/logout", post(logout_handler))
+        .route(
+            "/auth/logout/all",
+            post(logout_all_handler).route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
         .route("/auth/me", get(me_handler))
+        .route("/auth/identities", get(list_identities_handler))
+        .route(
+            "/auth/identities",
+            post(link_identity_handler)
+                .route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
+        .route(
+            "/auth/identities/{identity_id}",
+            delete(unlink_identity_handler)
+                .route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
+        .route("/auth/sessions", get(list_sessions_handler))
+        .route(
+            "/auth/sessions/{session_id}",
+            delete(revoke_session_handler)
+                .route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
+        .route("/api-keys", get(list_api_keys_handler))
+        .route(
+            "/api-keys",
+            post(create_api_key_handler)
+                .route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
+        .route(
+            "/api-keys/{key_id}",
+            delete(revoke_api_key_handler)
+                .route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
         .route("/users", get(get_users_query))
-        .route("/users/{user_id}", get(get_user_by_id_query))
+        .route(
+            "/users/{user_id}",
+            get(get_user_by_id_query).route_layer(middleware::from_extractor_with_state::<
+                RequireOwnPermission<UsersRead, SelfOwned>,
+                _,
+            >(app_state.clone())),
+        )
         .route("/users/{user_id}/profile", get(get_user_profile_query))
         .route("/boats", get(get_boats_query))
         .route("/boats/my", get(get_my_boats_query)) // Get user's boats
-        .route("/boats/my", post(create_user_boat_command)) // User boat creation
+        .route(
+            "/boats/my",
+            post(create_user_boat_command) // User boat creation
+                .route_layer(middleware::from_extractor::<RequirePermission<BoatsCreate>>()),
+        )
         .route("/countries", get(get_countries_query))
         .route("/countries/{country_id}", get(get_country_by_id_query))
         .route(
             "/countries/code/{country_code}",
             get(get_country_by_code_query),
         )
-        // Boat-owner endpoints
-        .route("/boats/{boat_id}/owners/{user_id}", post(add_owner_to_boat))
+        // Boat-owner endpoints. Changing owners hands out lasting access, so it
+        // is refused while impersonating; creating and editing boats is not,
+        // as that only does what the user could do and grants nobody anything.
+        .route(
+            "/boats/{boat_id}/owners/{user_id}",
+            post(add_owner_to_boat).route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
         .route(
             "/boats/{boat_id}/owners/{user_id}",
-            delete(remove_owner_from_boat),
+            delete(remove_owner_from_boat)
+                .route_layer(middleware::from_extractor::<NotImpersonating>()),
         )
         .route("/users/{user_id}/boats", get(get_boats_for_user))
         .route("/boats/{boat_id}/owners", get(get_owners_for_boat))
+        .layer(rate_limit(RateLimitBudget::Api))
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
             jwt_auth_middleware,
         ));
 
-    // Admin routes (authentication + admin permissions required)
+    // Admin routes (authentication + a permission per route required)
     let admin_routes = Router::new()
-        .route("/users", post(insert_user_command))
-        .route("/users/{user_id}", put(update_user_command))
-        .route("/users/{user_id}", delete(delete_user_command))
-        .route("/countries", post(insert_country_command))
-        .route("/countries/{country_id}", put(update_country_command))
-        .route("/countries/{country_id}", delete(delete_country_command))
+        .route(
+            "/users",
+            post(insert_user_command)
+                .route_layer(middleware::from_extractor::<RequirePermission<UsersWrite>>()),
+        )
+        .route(
+            "/users/{user_id}",
+            put(update_user_command).route_layer(middleware::from_extractor_with_state::<
+                RequireOwnPermission<UsersWrite, SelfOwned>,
+                _,
+            >(app_state.clone())),
+        )
+        .route(
+            "/users/{user_id}",
+            delete(delete_user_command)
+                .route_layer(middleware::from_extractor::<RequirePermission<UsersDelete>>()),
+        )
+        .route(
+            "/countries",
+            post(insert_country_command).route_layer(middleware::from_extractor::<
+                RequirePermission<CountriesWrite>,
+            >()),
+        )
+        .route(
+            "/countries/{country_id}",
+            put(update_country_command).route_layer(middleware::from_extractor::<
+                RequirePermission<CountriesWrite>,
+            >()),
+        )
+        .route(
+            "/countries/{country_id}",
+            delete(delete_country_command).route_layer(middleware::from_extractor::<
+                RequirePermission<CountriesDelete>,
+            >()),
+        )
+        .layer(middleware::from_extractor::<NotImpersonating>())
+        .layer(rate_limit(RateLimitBudget::Api))
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
-            require_permission(
-                crate::application::middleware::rbac_middleware::RequiredPermission::new(
-                    "admin:write",
-                ),
-            ),
-        ))
+            jwt_auth_middleware,
+        ));
+
+    // Boat management routes (authentication + boat permissions required;
+    // owners may update their own boats)
+    let boat_routes = Router::new()
+        .route(
+            "/boats",
+            post(insert_boat_command)
+                .route_layer(middleware::from_extractor::<RequirePermission<BoatsWrite>>()),
+        )
+        .route(
+            "/boats/{boat_id}",
+            put(update_boat_command).route_layer(middleware::from_extractor_with_state::<
+                RequireOwnPermission<BoatsWrite, BoatOwned>,
+                _,
+            >(app_state.clone())),
+        )
+        .route(
+            "/boats/{boat_id}",
+            delete(delete_boat_command)
+                .route_layer(middleware::from_extractor::<NotImpersonating>())
+                .route_layer(middleware::from_extractor::<RequirePermission<BoatsWrite>>()),
+        )
+        .layer(rate_limit(RateLimitBudget::Api))
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
             jwt_auth_middleware,
         ));
 
-    // Boat management routes (authentication + boat permissions required)
-    let boat_routes = Router::new()
-        .route("/boats", post(insert_boat_command))
-        .route("/boats/{boat_id}", put(update_boat_command))
-        .route("/boats/{boat_id}", delete(delete_boat_command))
+    // Service account routes (authentication + user management permissions required)
+    let service_account_routes = Router::new()
+        .route("/service-accounts", get(list_service_accounts_handler))
+        .route("/service-accounts", post(create_service_account_handler))
+        .route(
+            "/service-accounts/{user_id}/api-keys",
+            get(list_service_account_api_keys_handler),
+        )
+        .route(
+            "/service-accounts/{user_id}/api-keys",
+            post(create_service_account_api_key_handler),
+        )
+        .route(
+            "/service-accounts/{user_id}/api-keys/{key_id}",
+            delete(revoke_service_account_api_key_handler),
+        )
+        .layer(middleware::from_extractor::<RequirePermission<UsersWrite>>())
+        .layer(middleware::from_extractor::<NotImpersonating>())
+        .layer(rate_limit(RateLimitBudget::Api))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            jwt_auth_middleware,
+        ));
+
+    // Role administration routes (authentication + role permissions required)
+    let role_admin_read_routes = Router::new()
+        .route("/admin/roles", get(list_roles_handler))
+        .route("/admin/roles/{role_id}", get(get_role_handler))
+        .route("/admin/permissions", get(list_permissions_handler))
+        .route("/admin/users/{user_id}/roles", get(list_user_roles_handler))
+        .layer(middleware::from_extractor::<RequirePermission<RolesRead>>());
+
+    let role_admin_write_routes = Router::new()
+        .route("/admin/roles", post(create_role_handler))
+        .route("/admin/roles/{role_id}", put(update_role_handler))
+        .route("/admin/roles/{role_id}", delete(delete_role_handler))
+        .route(
+            "/admin/roles/{role_id}/permissions/{permission_id}",
+            put(grant_role_permission_handler),
+        )
+        .route(
+            "/admin/roles/{role_id}/permissions/{permission_id}",
+            delete(revoke_role_permission_handler),
+        )
+        .route("/admin/permissions", post(create_permission_handler))
+        .route(
+            "/admin/permissions/{permission_id}",
+            put(update_permission_handler),
+        )
+        .route(
+            "/admin/permissions/{permission_id}",
+            delete(delete_permission_handler),
+        )
+        .route(
+            "/admin/users/{user_id}/roles",
+            post(assign_user_role_handler),
+        )
+        .route(
+            "/admin/users/{user_id}/roles/{role_id}",
+            delete(remove_user_role_handler),
+        )
+        .layer(middleware::from_extractor::<RequirePermission<RolesWrite>>())
+        .layer(middleware::from_extractor::<NotImpersonating>());
+
+    let role_admin_routes = role_admin_read_routes
+        .merge(role_admin_write_routes)
+        .layer(rate_limit(RateLimitBudget::Api))
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
-            require_boats_write(),
-        ))
+            jwt_auth_middleware,
+        ));
+
+    // Impersonation routes (authentication + impersonation permission required)
+    let impersonation_routes = Router::new()
+        .route(
+            "/admin/users/{user_id}/impersonate",
+            post(impersonate_user_handler)
+                .route_layer(middleware::from_extractor::<NotImpersonating>()),
+        )
+        .route(
+            "/admin/impersonations",
+            get(list_impersonation_audit_handler),
+        )
+        .layer(middleware::from_extractor::<
+            RequirePermission<UsersImpersonate>,
+        >())
+        .layer(rate_limit(RateLimitBudget::Api))
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
             jwt_auth_middleware,
         ));
 
-    // Combine all routes with /api prefix for consistency between cargo run and func start
-    let api_routes = Router::new()
+    Router::new()
         .merge(public_routes)
+        .merge(docs_routes)
         .merge(protected_routes)
         .merge(admin_routes)
-        .merge(boat_routes);
-
-    Router::new()
-        .nest("/api", api_routes)
-        .layer(cors)
-        .with_state(app_state)
+        .merge(boat_routes)
+        .merge(service_account_routes)
+        .merge(role_admin_routes)
+        .merge(impersonation_routes)
+        .fallback(|| async { AppError::NotFound("Route not found".to_string()) })
 }
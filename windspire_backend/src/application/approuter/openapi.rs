@@ -0,0 +1,366 @@
+use axum::{extract::State, Json};
+use utoipa::{
+    openapi::{
+        header::HeaderBuilder,
+        path::Operation,
+        response::Response,
+        schema::{ObjectBuilder, Type},
+        security::{HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme},
+        Content, OpenApi as OpenApiDocument, Ref, RefOr,
+    },
+    Modify, OpenApi,
+};
+
+use crate::application::{
+    commands, config::AuthMode, error::FieldError, error::ProblemDetails, error::PROBLEM_JSON,
+    handlers, queries, state::AppState,
+};
+
+/// Path of the route that only exists with `AUTH_MODE=test-issuer`
+const TEST_ISSUER_PATH: &str = "/auth/test-issuer/token";
+
+/// The OpenAPI document of the API. Paths are relative to `/api/v1`. Routes
+/// gated by a permission list it as the scope of both security schemes;
+/// error responses are filled in by [`ProblemResponses`].
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Windspire API",
+        description = "Boats, their owners and the users and countries they belong to. \
+            Successful JSON responses are wrapped in `{\"success\": true, \"data\": ...}`; \
+            errors are `application/problem+json` documents with a stable `code`. \
+            Retired endpoints answer with `Deprecation` and `Sunset` headers."
+    ),
+    servers((url = "/api/v1")),
+    paths(
+        openapi_json_handler,
+        handlers::health_handlers::health_handler,
+        handlers::auth_handlers::jwks_handler,
+        handlers::auth_handlers::firebase_auth_handler,
+        handlers::auth_handlers::identity_provider_auth_handler,
+        handlers::auth_handlers::test_issuer_token_handler,
+        handlers::auth_handlers::refresh_token_handler,
+        handlers::auth_handlers::revoke_token_handler,
+        handlers::auth_handlers::logout_handler,
+        handlers::auth_handlers::logout_all_handler,
+        handlers::auth_handlers::me_handler,
+        handlers::session_handlers::list_sessions_handler,
+        handlers::session_handlers::revoke_session_handler,
+        handlers::identity_handlers::list_identities_handler,
+        handlers::identity_handlers::link_identity_handler,
+        handlers::identity_handlers::unlink_identity_handler,
+        handlers::api_key_handlers::list_api_keys_handler,
+        handlers::api_key_handlers::create_api_key_handler,
+        handlers::api_key_handlers::revoke_api_key_handler,
+        handlers::api_key_handlers::list_service_accounts_handler,
+        handlers::api_key_handlers::create_service_account_handler,
+        handlers::api_key_handlers::list_service_account_api_keys_handler,
+        handlers::api_key_handlers::create_service_account_api_key_handler,
+        handlers::api_key_handlers::revoke_service_account_api_key_handler,
+        handlers::admin_handlers::list_roles_handler,
+        handlers::admin_handlers::get_role_handler,
+        handlers::admin_handlers::create_role_handler,
+        handlers::admin_handlers::update_role_handler,
+        handlers::admin_handlers::delete_role_handler,
+        handlers::admin_handlers::grant_role_permission_handler,
+        handlers::admin_handlers::revoke_role_permission_handler,
+        handlers::admin_handlers::list_permissions_handler,
+        handlers::admin_handlers::create_permission_handler,
+        handlers::admin_handlers::update_permission_handler,
+        handlers::admin_handlers::delete_permission_handler,
+        handlers::admin_handlers::list_user_roles_handler,
+        handlers::admin_handlers::assign_user_role_handler,
+        handlers::admin_handlers::remove_user_role_handler,
+        handlers::impersonation_handlers::impersonate_user_handler,
+        handlers::impersonation_handlers::list_impersonation_audit_handler,
+        queries::get_boats_query::get_boats_query,
+        queries::get_my_boats_query::get_my_boats_query,
+        commands::insert_boat_command::insert_boat_command,
+        commands::create_user_boat_command::create_user_boat_command,
+        commands::update_boat_command::update_boat_command,
+        commands::delete_boat_command::delete_boat_command,
+        handlers::boat_owner_handlers::get_owners_for_boat,
+        handlers::boat_owner_handlers::add_owner_to_boat,
+        handlers::boat_owner_handlers::remove_owner_from_boat,
+        handlers::boat_owner_handlers::get_boats_for_user,
+        queries::get_users_query::get_users_query,
+        queries::get_user_by_id_query::get_user_by_id_query,
+        queries::get_user_profile_query::get_user_profile_query,
+        commands::insert_user_command::insert_user_command,
+        commands::update_user_command::update_user_command,
+        commands::delete_user_command::delete_user_command,
+        queries::get_countries_query::get_countries_query,
+        queries::get_country_by_id_query::get_country_by_id_query,
+        queries::get_country_by_code_query::get_country_by_code_query,
+        commands::insert_country_command::insert_country_command,
+        commands::update_country_command::update_country_command,
+        commands::delete_country_command::delete_country_command,
+    ),
+    components(schemas(ProblemDetails, FieldError)),
+    modifiers(&SecuritySchemes, &ProblemResponses),
+    security(("bearer_auth" = []), ("api_key" = [])),
+    tags(
+        (name = "meta", description = "Health and API description"),
+        (name = "auth", description = "Signing in and out and the current user"),
+        (name = "sessions", description = "Sessions of the current user"),
+        (name = "identities", description = "Identity providers linked to the current user"),
+        (name = "api-keys", description = "API keys of the current user"),
+        (name = "service-accounts", description = "Service accounts and their API keys"),
+        (name = "roles", description = "Roles, their permissions and who holds them"),
+        (name = "permissions", description = "Permissions that can be granted to roles"),
+        (name = "impersonation", description = "Acting as another user"),
+        (name = "boats"),
+        (name = "boat-owners", description = "Who owns which boat"),
+        (name = "users"),
+        (name = "countries"),
+    )
+)]
+pub struct ApiDoc;
+
+/// The document served by this instance: the test issuer route is only
+/// listed when it is mounted
+pub fn api_doc(auth_mode: AuthMode) -> OpenApiDocument {
+    let mut openapi = ApiDoc::openapi();
+    if auth_mode != AuthMode::TestIssuer {
+        openapi.paths.paths.remove(TEST_ISSUER_PATH);
+    }
+    openapi
+}
+
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    tag = "meta",
+    responses(
+        (status = 200, description = "This OpenAPI document", body = Object),
+    ),
+    security(()),
+)]
+pub async fn openapi_json_handler(State(app_state): State<AppState>) -> Json<OpenApiDocument> {
+    Json(api_doc(app_state.config.auth_mode))
+}
+
+/// Both schemes are sent as `Authorization: Bearer`; the server tells them
+/// apart by the `wsk_` prefix of API keys
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some(
+                        "Access token from `/auth/firebase`, `/auth/providers/{provider}` or \
+                         `/auth/refresh`. An expired token is answered with a 401 whose `code` \
+                         is `token_expired`.",
+                    ))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("wsk_<key id>_<secret>")
+                    .description(Some(
+                        "API key from `/api-keys` or a service account. It carries the \
+                         permissions its owner had when it was created.",
+                    ))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Gives every error response its `application/problem+json` body and adds
+/// the errors any operation can answer with: 401 when it needs credentials,
+/// 400 and 415 when it takes a body, and 429 and 500 everywhere.
+struct ProblemResponses;
+
+impl Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        for path_item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut path_item.get,
+                &mut path_item.post,
+                &mut path_item.put,
+                &mut path_item.patch,
+                &mut path_item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                add_common_errors(operation);
+                for (status, response) in operation.responses.responses.iter_mut() {
+                    if let (true, RefOr::T(response)) = (is_error_status(status), response) {
+                        response.content.clear();
+                        response.content.insert(
+                            PROBLEM_JSON.to_string(),
+                            Content::new(Some(Ref::from_schema_name("ProblemDetails"))),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn add_common_errors(operation: &mut Operation) {
+    let is_public = operation
+        .security
+        .as_ref()
+        .is_some_and(|requirements| requirements.contains(&SecurityRequirement::default()));
+    let has_body = operation.request_body.is_some();
+    let responses = &mut operation.responses.responses;
+
+    let mut add = |status: &str, response: Response| {
+        responses
+            .entry(status.to_string())
+            .or_insert(RefOr::T(response));
+    };
+    if has_body {
+        add(
+            "400",
+            Response::new("The body is malformed or invalid; `errors` lists each offending field"),
+        );
+        add("415", Response::new("The body is not JSON"));
+    }
+    if !is_public {
+        add(
+            "401",
+            Response::new("Missing or invalid credentials; `token_expired` when the access token should be refreshed"),
+        );
+    }
+    let mut rate_limited = Response::new("Rate limit exceeded");
+    rate_limited.headers.insert(
+        "Retry-After".to_string(),
+        HeaderBuilder::new()
+            .schema(ObjectBuilder::new().schema_type(Type::Integer))
+            .description(Some("Seconds until the rate limit allows another request"))
+            .build(),
+    );
+    add("429", rate_limited);
+    add("500", Response::new("Internal server error"));
+}
+
+fn is_error_status(status: &str) -> bool {
+    status.parse::<u16>().is_ok_and(|status| status >= 400)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+    /// Every `(method, path)` routed in `mod.rs`, read from the `.route(..)`
+    /// calls so a new route cannot go undocumented
+    fn routed_operations() -> BTreeSet<(String, String)> {
+        let source = include_str!("mod.rs");
+        let mut operations = BTreeSet::new();
+
+        for (start, call) in source.match_indices(".route(") {
+            let arguments = &source[start + call.len()..];
+            let path = arguments.split('"').nth(1).expect("route without a path");
+
+            // Method routers are the calls at the top level of the arguments,
+            // e.g. `get` and `route_layer` in `get(handler).route_layer(..)`
+            let mut depth = 0;
+            let mut identifier = String::new();
+            for c in arguments.chars() {
+                match c {
+                    '(' => {
+                        if depth == 0 && METHODS.contains(&identifier.as_str()) {
+                            operations.insert((identifier.clone(), path.to_string()));
+                        }
+                        depth += 1;
+                        identifier.clear();
+                    }
+                    ')' if depth == 0 => break,
+                    ')' => {
+                        depth -= 1;
+                        identifier.clear();
+                    }
+                    c if c.is_alphanumeric() || c == '_' => identifier.push(c),
+                    _ => identifier.clear(),
+                }
+            }
+        }
+        operations
+    }
+
+    fn documented_operations(openapi: &OpenApiDocument) -> BTreeSet<(String, String)> {
+        let mut operations = BTreeSet::new();
+        for (path, item) in &openapi.paths.paths {
+            let methods = [
+                ("get", &item.get),
+                ("post", &item.post),
+                ("put", &item.put),
+                ("patch", &item.patch),
+                ("delete", &item.delete),
+            ];
+            for (method, operation) in methods {
+                if operation.is_some() {
+                    operations.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        operations
+    }
+
+    #[test]
+    fn test_every_route_is_documented() {
+        let routed = routed_operations();
+        let documented = documented_operations(&api_doc(AuthMode::TestIssuer));
+        assert!(routed.len() > 50, "Parsed only {} routes", routed.len());
+
+        let undocumented: Vec<_> = routed.difference(&documented).collect();
+        assert!(
+            undocumented.is_empty(),
+            "Routes missing from ApiDoc: {:?}",
+            undocumented
+        );
+        let unrouted: Vec<_> = documented.difference(&routed).collect();
+        assert!(
+            unrouted.is_empty(),
+            "Documented operations without a route: {:?}",
+            unrouted
+        );
+    }
+
+    #[test]
+    fn test_test_issuer_route_follows_auth_mode() {
+        let documented = documented_operations(&api_doc(AuthMode::Production));
+        assert!(!documented.contains(&("post".to_string(), TEST_ISSUER_PATH.to_string())));
+    }
+
+    #[test]
+    fn test_operations_document_security_and_errors() {
+        let openapi = api_doc(AuthMode::TestIssuer);
+        let json = serde_json::to_value(&openapi).unwrap();
+
+        assert!(json["openapi"].as_str().unwrap().starts_with("3.1"));
+        assert_eq!(json["security"][0]["bearer_auth"], serde_json::json!([]));
+
+        let delete_user = &json["paths"]["/users/{user_id}"]["delete"];
+        assert_eq!(
+            delete_user["security"][0]["bearer_auth"],
+            serde_json::json!(["users:delete"])
+        );
+        assert_eq!(
+            delete_user["responses"]["401"]["content"][PROBLEM_JSON]["schema"]["$ref"],
+            "#/components/schemas/ProblemDetails"
+        );
+
+        let refresh = &json["paths"]["/auth/refresh"]["post"];
+        assert!(refresh["responses"]["400"].is_object());
+        assert!(refresh["responses"].get("401").is_some());
+        assert!(json["paths"]["/health"]["get"]["responses"]
+            .get("401")
+            .is_none());
+    }
+}
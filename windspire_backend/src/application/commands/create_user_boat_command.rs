@@ -1,61 +1,59 @@
 use crate::{
     application::{
-        http_response::{internal_server_error_json_response, json_response},
+        error::AppError,
+        http_response::{json_response, ApiResponse},
+        middleware::validated_json::ValidatedJson,
         state::AppState,
     },
     domain::{
         interface::boat_repository::BoatRepository,
-        models::{auth::AuthContext, boat::BoatCreate},
+        models::{
+            auth::AuthContext,
+            boat::{Boat, BoatCreate},
+        },
         repositories::boat_owner_repository::BoatOwnerRepository,
     },
     infrastructure::repositories::sqlx_boat_repository::SqlxBoatRepository,
 };
-use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
-    Extension,
-};
+use axum::{extract::State, http::StatusCode, response::Response, Extension};
 use serde_json::json;
-use validator::Validate;
 
+#[utoipa::path(
+    post,
+    path = "/boats/my",
+    tag = "boats",
+    request_body = BoatCreate,
+    responses(
+        (status = 201, description = "The boat, owned by the caller", body = ApiResponse<Boat>),
+        (status = 409, description = "Unknown country"),
+    ),
+    security(("bearer_auth" = ["boats:create"]), ("api_key" = ["boats:create"])),
+)]
 pub async fn create_user_boat_command(
     State(app_state): State<AppState>,
     Extension(auth_context): Extension<AuthContext>,
-    Json(boat_create): Json<BoatCreate>,
-) -> impl IntoResponse {
-    // Validate the boat_create data
-    if let Err(e) = boat_create.validate() {
-        return json_response(
-            StatusCode::BAD_REQUEST,
-            json!({ "success": false, "message": e }),
-        );
-    }
-
+    ValidatedJson(boat_create): ValidatedJson<BoatCreate>,
+) -> Result<Response, AppError> {
     let boat_repository = SqlxBoatRepository;
     let owner_repository = BoatOwnerRepository::new(&app_state.db_pool);
 
     // Create the boat
-    match boat_repository
+    let boat = boat_repository
         .insert(&app_state.db_pool, boat_create)
+        .await?;
+
+    // Assign ownership to the creating user
+    if let Err(e) = owner_repository
+        .add_owner_to_boat(boat.id, auth_context.user.id)
         .await
     {
-        Ok(boat) => {
-            // Assign ownership to the creating user
-            if let Err(e) = owner_repository
-                .add_owner_to_boat(boat.id, auth_context.user.id)
-                .await
-            {
-                // If ownership assignment fails, we should probably delete the boat
-                // For now, just log the error and continue
-                eprintln!("Failed to assign boat ownership: {}", e);
-            }
-
-            json_response(
-                StatusCode::CREATED,
-                json!({ "success": true, "data": boat }),
-            )
-        }
-        Err(e) => internal_server_error_json_response(e),
+        // If ownership assignment fails, we should probably delete the boat
+        // For now, just log the error and continue
+        eprintln!("Failed to assign boat ownership: {}", e);
     }
+
+    Ok(json_response(
+        StatusCode::CREATED,
+        json!({ "success": true, "data": boat }),
+    ))
 }
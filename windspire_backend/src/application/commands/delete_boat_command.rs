@@ -1,8 +1,7 @@
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, Confirmation},
         state::AppState,
     },
     domain::interface::boat_repository::BoatRepository,
@@ -11,22 +10,35 @@ use crate::{
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
 };
-use serde_json::json;
 use uuid::Uuid;
 
+#[utoipa::path(
+    delete,
+    path = "/boats/{boat_id}",
+    tag = "boats",
+    responses(
+        (status = 200, description = "The boat is deleted", body = Confirmation),
+        (status = 403, description = "Called with an impersonation token"),
+        (status = 404, description = "Boat not found"),
+    ),
+    security(("bearer_auth" = ["boats:write"]), ("api_key" = ["boats:write"])),
+)]
 pub async fn delete_boat_command(
     State(app_state): State<AppState>,
     Path(boat_id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let repository = SqlxBoatRepository;
     match repository.delete(&app_state.db_pool, boat_id).await {
-        Ok(_) => json_response(
+        Ok(_) => Ok(json_response(
             StatusCode::OK,
-            json!({ "success": true, "message": "Boat deleted successfully" }),
-        ),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("Boat not found"),
-        Err(e) => internal_server_error_json_response(e),
+            Confirmation {
+                success: true,
+                message: "Boat deleted successfully".to_string(),
+            },
+        )),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Boat not found".to_string())),
+        Err(e) => Err(e.into()),
     }
 }
@@ -1,8 +1,7 @@
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
         state::AppState,
     },
     domain::interface::country_repository::CountryRepository,
@@ -11,23 +10,37 @@ use crate::{
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
 };
 use serde_json::json;
 
 use uuid::Uuid;
 
+#[utoipa::path(
+    delete,
+    path = "/countries/{country_id}",
+    tag = "countries",
+    responses(
+        (status = 200, description = "The country is deleted; `data` is null", body = ApiResponse<serde_json::Value>),
+        (status = 404, description = "Country not found"),
+        (status = 409, description = "The country is still in use"),
+    ),
+    security(("bearer_auth" = ["countries:delete"]), ("api_key" = ["countries:delete"])),
+)]
 pub async fn delete_country_command(
     State(app_state): State<AppState>,
     Path(country_id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let repository = SqlxCountryRepository;
     match repository
         .delete_country(&app_state.db_pool, country_id)
         .await
     {
-        Ok(users) => json_response(StatusCode::OK, json!({ "success" : true, "data" : users })),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("Country not found"),
-        Err(e) => internal_server_error_json_response(e),
+        Ok(users) => Ok(json_response(
+            StatusCode::OK,
+            json!({ "success" : true, "data" : users }),
+        )),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Country not found".to_string())),
+        Err(e) => Err(e.into()),
     }
 }
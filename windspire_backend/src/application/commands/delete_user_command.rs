@@ -1,30 +1,50 @@
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
         state::AppState,
     },
-    domain::repositories::user_repository::UserRepository,
-    infrastructure::repositories::sqlx_user_repository::SqlxUserRepository,
+    domain::{models::rbac::DeleteUserOutcome, repositories::role_repository::RoleRepository},
+    infrastructure::repositories::sqlx_role_repository::SqlxRoleRepository,
 };
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
 };
 use serde_json::json;
 
 use uuid::Uuid;
 
+#[utoipa::path(
+    delete,
+    path = "/users/{user_id}",
+    tag = "users",
+    responses(
+        (status = 200, description = "The user is deleted; `data` is null", body = ApiResponse<serde_json::Value>),
+        (status = 403, description = "Called with an impersonation token"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "Cannot delete the last admin"),
+    ),
+    security(("bearer_auth" = ["users:delete"]), ("api_key" = ["users:delete"])),
+)]
 pub async fn delete_user_command(
     State(app_state): State<AppState>,
     Path(user_id): Path<Uuid>,
-) -> impl IntoResponse {
-    let repository = SqlxUserRepository;
-    match repository.delete_user(&app_state.db_pool, user_id).await {
-        Ok(users) => json_response(StatusCode::OK, json!({ "success": true, "data": users })),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("User not found"),
-        Err(e) => internal_server_error_json_response(e),
+) -> Result<Response, AppError> {
+    // The last-admin check and the delete run in one transaction, so two
+    // concurrent deletes cannot remove the last two admins
+    match SqlxRoleRepository
+        .delete_user_unless_last_admin(&app_state.db_pool, user_id)
+        .await?
+    {
+        DeleteUserOutcome::Deleted => Ok(json_response(
+            StatusCode::OK,
+            json!({ "success": true, "data": null }),
+        )),
+        DeleteUserOutcome::NotFound => Err(AppError::NotFound("User not found".to_string())),
+        DeleteUserOutcome::LastAdmin => Err(AppError::Conflict(
+            "Cannot delete the last admin".to_string(),
+        )),
     }
 }
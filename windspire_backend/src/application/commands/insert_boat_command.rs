@@ -1,38 +1,38 @@
 use crate::{
     application::{
-        http_response::{internal_server_error_json_response, json_response},
+        error::AppError,
+        http_response::{json_response, ApiResponse},
+        middleware::validated_json::ValidatedJson,
         state::AppState,
     },
-    domain::{interface::boat_repository::BoatRepository, models::boat::BoatCreate},
+    domain::{
+        interface::boat_repository::BoatRepository,
+        models::boat::{Boat, BoatCreate},
+    },
     infrastructure::repositories::sqlx_boat_repository::SqlxBoatRepository,
 };
-use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
-};
+use axum::{extract::State, http::StatusCode, response::Response};
 use serde_json::json;
 
-use validator::Validate;
-
+#[utoipa::path(
+    post,
+    path = "/boats",
+    tag = "boats",
+    request_body = BoatCreate,
+    responses(
+        (status = 200, description = "The boat", body = ApiResponse<Boat>),
+        (status = 409, description = "Unknown country"),
+    ),
+    security(("bearer_auth" = ["boats:write"]), ("api_key" = ["boats:write"])),
+)]
 pub async fn insert_boat_command(
     State(app_state): State<AppState>,
-    Json(boat_create): Json<BoatCreate>,
-) -> impl IntoResponse {
-    // Validate the user_create data
-    match boat_create.validate() {
-        Ok(_) => (),
-        Err(e) => {
-            return json_response(
-                StatusCode::BAD_REQUEST,
-                json!({ "success": false, "message": e }),
-            );
-        }
-    };
-
+    ValidatedJson(boat_create): ValidatedJson<BoatCreate>,
+) -> Result<Response, AppError> {
     let repository = SqlxBoatRepository;
-    match repository.insert(&app_state.db_pool, boat_create).await {
-        Ok(users) => json_response(StatusCode::OK, json!({ "success": true, "data": users })),
-        Err(e) => internal_server_error_json_response(e),
-    }
+    let boat = repository.insert(&app_state.db_pool, boat_create).await?;
+    Ok(json_response(
+        StatusCode::OK,
+        json!({ "success": true, "data": boat }),
+    ))
 }
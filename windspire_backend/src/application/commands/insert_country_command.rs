@@ -1,40 +1,41 @@
-use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
-};
+use axum::{extract::State, http::StatusCode, response::Response};
 use serde_json::json;
-use validator::Validate;
 
 use crate::{
     application::{
-        http_response::{internal_server_error_json_response, json_response},
+        error::AppError,
+        http_response::{json_response, ApiResponse},
+        middleware::validated_json::ValidatedJson,
         state::AppState,
     },
-    domain::{interface::country_repository::CountryRepository, models::country::CountryCreate},
+    domain::{
+        interface::country_repository::CountryRepository,
+        models::country::{Country, CountryCreate},
+    },
     infrastructure::repositories::sqlx_country_repository::SqlxCountryRepository,
 };
 
+#[utoipa::path(
+    post,
+    path = "/countries",
+    tag = "countries",
+    request_body = CountryCreate,
+    responses(
+        (status = 200, description = "The country", body = ApiResponse<Country>),
+        (status = 409, description = "The country already exists"),
+    ),
+    security(("bearer_auth" = ["countries:write"]), ("api_key" = ["countries:write"])),
+)]
 pub async fn insert_country_command(
     State(app_state): State<AppState>,
-    Json(country_create): Json<CountryCreate>,
-) -> impl IntoResponse {
-    // Validate the country_create data
-    match country_create.validate() {
-        Ok(_) => (),
-        Err(e) => {
-            return json_response(
-                StatusCode::BAD_REQUEST,
-                json!({ "success": false, "message": e }),
-            );
-        }
-    };
+    ValidatedJson(country_create): ValidatedJson<CountryCreate>,
+) -> Result<Response, AppError> {
     let repository = SqlxCountryRepository;
-    match repository
+    let country = repository
         .insert_country(&app_state.db_pool, country_create)
-        .await
-    {
-        Ok(country) => json_response(StatusCode::OK, json!({ "success": true, "data": country })),
-        Err(e) => internal_server_error_json_response(e),
-    }
+        .await?;
+    Ok(json_response(
+        StatusCode::OK,
+        json!({ "success": true, "data": country }),
+    ))
 }
@@ -1,41 +1,40 @@
 use crate::{
     application::{
-        http_response::{internal_server_error_json_response, json_response},
+        error::AppError,
+        http_response::{json_response, ApiResponse},
+        middleware::validated_json::ValidatedJson,
         state::AppState,
     },
-    domain::{models::user::UserCreate, repositories::user_repository::UserRepository},
+    domain::{
+        models::user::{User, UserCreate},
+        repositories::user_repository::UserRepository,
+    },
     infrastructure::repositories::sqlx_user_repository::SqlxUserRepository,
 };
-use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
-};
+use axum::{extract::State, http::StatusCode, response::Response};
 use serde_json::json;
 
-use validator::Validate;
-
+#[utoipa::path(
+    post,
+    path = "/users",
+    tag = "users",
+    request_body = UserCreate,
+    responses(
+        (status = 200, description = "The user", body = ApiResponse<User>),
+        (status = 409, description = "Email is already taken or unknown country"),
+    ),
+    security(("bearer_auth" = ["users:write"]), ("api_key" = ["users:write"])),
+)]
 pub async fn insert_user_command(
     State(app_state): State<AppState>,
-    Json(user_create): Json<UserCreate>,
-) -> impl IntoResponse {
-    // Validate the user_create data
-    match user_create.validate() {
-        Ok(_) => (),
-        Err(e) => {
-            return json_response(
-                StatusCode::BAD_REQUEST,
-                json!({ "success": false, "message": e }),
-            );
-        }
-    };
-
+    ValidatedJson(user_create): ValidatedJson<UserCreate>,
+) -> Result<Response, AppError> {
     let repository = SqlxUserRepository;
-    match repository
+    let user = repository
         .insert_user(&app_state.db_pool, user_create)
-        .await
-    {
-        Ok(users) => json_response(StatusCode::OK, json!({ "success": true, "data": users })),
-        Err(e) => internal_server_error_json_response(e),
-    }
+        .await?;
+    Ok(json_response(
+        StatusCode::OK,
+        json!({ "success": true, "data": user }),
+    ))
 }
@@ -1,45 +1,55 @@
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
+        middleware::validated_json::ValidatedJson,
         state::AppState,
     },
-    domain::{interface::boat_repository::BoatRepository, models::boat::BoatUpdate},
+    domain::{
+        interface::boat_repository::BoatRepository,
+        models::boat::{Boat, BoatUpdate},
+    },
     infrastructure::repositories::sqlx_boat_repository::SqlxBoatRepository,
 };
 use axum::{
-    extract::{Json, Path, State},
+    extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
 };
 use serde_json::json;
 use uuid::Uuid;
-use validator::Validate;
 
+#[utoipa::path(
+    put,
+    path = "/boats/{boat_id}",
+    tag = "boats",
+    request_body = BoatUpdate,
+    responses(
+        (status = 200, description = "The updated boat", body = ApiResponse<Boat>),
+        (status = 403, description = "A boat the caller does not own, without `boats:write`"),
+        (status = 404, description = "Boat not found"),
+        (status = 409, description = "Unknown country"),
+    ),
+    security(
+        ("bearer_auth" = ["boats:write"]), ("bearer_auth" = ["boats:write_own"]),
+        ("api_key" = ["boats:write"]), ("api_key" = ["boats:write_own"]),
+    ),
+)]
 pub async fn update_boat_command(
     State(app_state): State<AppState>,
     Path(boat_id): Path<Uuid>,
-    Json(boat_update): Json<BoatUpdate>,
-) -> impl IntoResponse {
-    // Validate the boat_update data
-    match boat_update.validate() {
-        Ok(_) => (),
-        Err(e) => {
-            return json_response(
-                StatusCode::BAD_REQUEST,
-                json!({ "success": false, "message": e }),
-            );
-        }
-    };
-
+    ValidatedJson(boat_update): ValidatedJson<BoatUpdate>,
+) -> Result<Response, AppError> {
     let repository = SqlxBoatRepository;
     match repository
         .update(&app_state.db_pool, boat_id, boat_update)
         .await
     {
-        Ok(boat) => json_response(StatusCode::OK, json!({ "success": true, "data": boat })),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("Boat not found"),
-        Err(e) => internal_server_error_json_response(e),
+        Ok(boat) => Ok(json_response(
+            StatusCode::OK,
+            json!({ "success": true, "data": boat }),
+        )),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Boat not found".to_string())),
+        Err(e) => Err(e.into()),
     }
 }
@@ -1,47 +1,52 @@
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
+        middleware::validated_json::ValidatedJson,
         state::AppState,
     },
-    domain::{interface::country_repository::CountryRepository, models::country::CountryUpdate},
+    domain::{
+        interface::country_repository::CountryRepository,
+        models::country::{Country, CountryUpdate},
+    },
     infrastructure::repositories::sqlx_country_repository::SqlxCountryRepository,
 };
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
-    Json,
+    response::Response,
 };
 use serde_json::json;
 
 use uuid::Uuid;
-use validator::Validate;
 
+#[utoipa::path(
+    put,
+    path = "/countries/{country_id}",
+    tag = "countries",
+    request_body = CountryUpdate,
+    responses(
+        (status = 200, description = "The updated country", body = ApiResponse<Country>),
+        (status = 404, description = "Country not found"),
+        (status = 409, description = "Another country has the same codes"),
+    ),
+    security(("bearer_auth" = ["countries:write"]), ("api_key" = ["countries:write"])),
+)]
 pub async fn update_country_command(
     State(app_state): State<AppState>,
     Path(country_id): Path<Uuid>,
-    Json(country_update): Json<CountryUpdate>,
-) -> impl IntoResponse {
-    // Validate the country_update data
-    match country_update.validate() {
-        Ok(_) => (),
-        Err(e) => {
-            return json_response(
-                StatusCode::BAD_REQUEST,
-                json!({ "success": false, "message": e }),
-            );
-        }
-    };
-
+    ValidatedJson(country_update): ValidatedJson<CountryUpdate>,
+) -> Result<Response, AppError> {
     let repository = SqlxCountryRepository;
     match repository
         .update_country(&app_state.db_pool, country_id, country_update)
         .await
     {
-        Ok(country) => json_response(StatusCode::OK, json!({ "success": true, "data": country })),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("Country not found"),
-        Err(e) => internal_server_error_json_response(e),
+        Ok(country) => Ok(json_response(
+            StatusCode::OK,
+            json!({ "success": true, "data": country }),
+        )),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Country not found".to_string())),
+        Err(e) => Err(e.into()),
     }
 }
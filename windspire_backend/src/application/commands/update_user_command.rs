@@ -1,35 +1,80 @@
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
+        middleware::{rbac_middleware::user_has_permission, validated_json::ValidatedJson},
         state::AppState,
     },
-    domain::{models::user::UserUpdate, repositories::user_repository::UserRepository},
+    domain::{
+        models::{
+            auth::AuthContext,
+            rbac::PERMISSION_USERS_WRITE,
+            user::{User, UserUpdate},
+        },
+        repositories::user_repository::UserRepository,
+    },
     infrastructure::repositories::sqlx_user_repository::SqlxUserRepository,
 };
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
-    Json,
+    response::Response,
+    Extension,
 };
 use serde_json::json;
 
 use uuid::Uuid;
 
+#[utoipa::path(
+    put,
+    path = "/users/{user_id}",
+    tag = "users",
+    request_body = UserUpdate,
+    responses(
+        (status = 200, description = "The updated user", body = ApiResponse<User>),
+        (status = 403, description = "Another user's account, or a new email address, without `users:write`"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "Email is already taken or unknown country"),
+    ),
+    security(
+        ("bearer_auth" = ["users:write"]), ("bearer_auth" = ["users:write_own"]),
+        ("api_key" = ["users:write"]), ("api_key" = ["users:write_own"]),
+    ),
+)]
 pub async fn update_user_command(
     State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
     Path(user_id): Path<Uuid>,
-    Json(user_update): Json<UserUpdate>,
-) -> impl IntoResponse {
+    ValidatedJson(user_update): ValidatedJson<UserUpdate>,
+) -> Result<Response, AppError> {
     let repository = SqlxUserRepository;
+
+    // Sign-in links identities to accounts by email, so users updating their
+    // own account with `users:write_own` may not change it
+    if !user_has_permission(&auth_context.user, PERMISSION_USERS_WRITE) {
+        let current = match repository.get_user_by_id(&app_state.db_pool, user_id).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => {
+                return Err(AppError::NotFound("User not found".to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !current.email.eq_ignore_ascii_case(&user_update.email) {
+            return Err(AppError::Forbidden(
+                "Changing the email address requires users:write".to_string(),
+            ));
+        }
+    }
+
     match repository
         .update_user(&app_state.db_pool, user_id, user_update)
         .await
     {
-        Ok(users) => json_response(StatusCode::OK, json!({ "success": true, "data": users })),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("User not found"),
-        Err(e) => internal_server_error_json_response(e),
+        Ok(users) => Ok(json_response(
+            StatusCode::OK,
+            json!({ "success": true, "data": users }),
+        )),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("User not found".to_string())),
+        Err(e) => Err(e.into()),
     }
 }
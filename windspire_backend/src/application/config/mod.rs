@@ -1,24 +1,142 @@
+use chrono::{DateTime, Utc};
 use std::env;
 
+use crate::application::services::firebase_service::FIREBASE_CERTIFICATES_URL;
+use crate::domain::models::user::AccountStatus;
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub database_url: String,
     pub server_address: String,
+    pub auth_mode: AuthMode,
+    pub unverified_email_policy: UnverifiedEmailPolicy,
     pub firebase: FirebaseConfig,
+    pub oidc_providers: Vec<OidcProviderConfig>,
     pub jwt: JwtConfig,
     pub cors: CorsConfig,
+    pub rate_limit: RateLimitConfig,
+    pub api_versions: ApiVersionsConfig,
+}
+
+/// How Firebase ID tokens are verified (`AUTH_MODE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMode {
+    /// Tokens signed by Google (default)
+    Production,
+    /// Unsigned tokens from the Firebase Auth Emulator
+    FirebaseEmulator,
+    /// Tokens minted by the built-in test issuer, for end-to-end tests
+    TestIssuer,
+}
+
+impl AuthMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthMode::Production => "production",
+            AuthMode::FirebaseEmulator => "firebase-emulator",
+            AuthMode::TestIssuer => "test-issuer",
+        }
+    }
+}
+
+impl std::str::FromStr for AuthMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "production" => Ok(AuthMode::Production),
+            "firebase-emulator" => Ok(AuthMode::FirebaseEmulator),
+            "test-issuer" => Ok(AuthMode::TestIssuer),
+            other => Err(format!("Unknown AUTH_MODE: {}", other)),
+        }
+    }
+}
+
+/// What happens when someone signs up with an email the identity provider has
+/// not verified (`UNVERIFIED_EMAIL_POLICY`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnverifiedEmailPolicy {
+    /// No account is created
+    Reject,
+    /// The account gets read-only access (default)
+    Restricted,
+    /// The account is created but cannot log in
+    Pending,
+}
+
+impl UnverifiedEmailPolicy {
+    /// The status of a new account, or `None` if it may not be created
+    pub fn sign_up_status(&self, email_verified: bool) -> Option<AccountStatus> {
+        if email_verified {
+            return Some(AccountStatus::Active);
+        }
+        match self {
+            UnverifiedEmailPolicy::Reject => None,
+            UnverifiedEmailPolicy::Restricted => Some(AccountStatus::Restricted),
+            UnverifiedEmailPolicy::Pending => Some(AccountStatus::Pending),
+        }
+    }
+}
+
+impl std::str::FromStr for UnverifiedEmailPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "reject" => Ok(UnverifiedEmailPolicy::Reject),
+            "restricted" => Ok(UnverifiedEmailPolicy::Restricted),
+            "pending" => Ok(UnverifiedEmailPolicy::Pending),
+            other => Err(format!("Unknown UNVERIFIED_EMAIL_POLICY: {}", other)),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct FirebaseConfig {
     pub project_id: String,
+    pub certificates_url: String,
+}
+
+/// A generic OpenID Connect identity provider (e.g. Keycloak), configured with
+/// `OIDC_PROVIDERS=<name>,...` and `OIDC_<NAME>_ISSUER` / `_AUDIENCE` /
+/// `_DISCOVERY_URL` (optional, defaults to the issuer's well-known document)
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    pub issuer: String,
+    pub audience: String,
+    pub discovery_url: Option<String>,
+}
+
+impl OidcProviderConfig {
+    fn from_env(name: &str) -> Result<Self, env::VarError> {
+        let prefix = format!("OIDC_{}", name.to_uppercase().replace('-', "_"));
+        Ok(OidcProviderConfig {
+            name: name.to_string(),
+            issuer: env::var(format!("{}_ISSUER", prefix))?,
+            audience: env::var(format!("{}_AUDIENCE", prefix))?,
+            discovery_url: env::var(format!("{}_DISCOVERY_URL", prefix)).ok(),
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct JwtConfig {
     pub secret: String,
     pub expiration_hours: i64,
+    /// Lifetime of impersonation tokens, which cannot be refreshed
+    pub impersonation_expiration_minutes: i64,
     pub issuer: String,
+    pub audience: String,
+    pub refresh_token_expiration_days: i64,
+    /// HS256 (signed with `secret`), RS256 or EdDSA (signed with `signing_key_file`)
+    pub algorithm: String,
+    pub signing_key_id: String,
+    pub signing_key_file: Option<String>,
+    /// Previous signing keys as `kid=file@retired_at`, see `RetiredKeySpec`
+    pub retired_keys: Vec<String>,
+    /// How long a retired key keeps verifying tokens after its retirement
+    pub retired_key_grace_hours: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -28,21 +146,174 @@ pub struct CorsConfig {
     pub allowed_headers: Vec<String>,
 }
 
+/// Request budgets per client (`RATE_LIMIT_*`). Public routes count per IP
+/// address, protected routes per user.
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub store: RateLimitStoreKind,
+    /// Take the client IP from the last `X-Forwarded-For` entry. Only safe
+    /// directly behind a proxy that appends to the header, since clients can
+    /// send anything.
+    pub trust_forwarded_for: bool,
+    /// Login with an ID token, which fetches provider keys and creates users
+    pub login: RateLimitRule,
+    /// Token refresh and revocation
+    pub refresh: RateLimitRule,
+    /// Other public routes
+    pub public: RateLimitRule,
+    /// Authenticated routes
+    pub api: RateLimitRule,
+}
+
+/// Where rate limit counters live (`RATE_LIMIT_STORE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitStoreKind {
+    /// Per instance (default)
+    Memory,
+    /// Shared by all instances
+    Postgres,
+}
+
+impl std::str::FromStr for RateLimitStoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "memory" => Ok(RateLimitStoreKind::Memory),
+            "postgres" => Ok(RateLimitStoreKind::Postgres),
+            other => Err(format!("Unknown RATE_LIMIT_STORE: {}", other)),
+        }
+    }
+}
+
+/// A budget of `requests` per `window_seconds`, written as `requests/seconds`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitRule {
+    pub requests: u32,
+    pub window_seconds: u32,
+}
+
+impl RateLimitRule {
+    pub const fn new(requests: u32, window_seconds: u32) -> Self {
+        Self {
+            requests,
+            window_seconds,
+        }
+    }
+
+    fn from_env(name: &str, default: RateLimitRule) -> Self {
+        env::var(name)
+            .ok()
+            .and_then(|rule| rule.parse().ok())
+            .unwrap_or(default)
+    }
+}
+
+impl std::str::FromStr for RateLimitRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid rate limit {}, expected requests/seconds", s);
+        let (requests, window_seconds) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let rule = RateLimitRule::new(
+            requests.trim().parse().map_err(|_| invalid())?,
+            window_seconds.trim().parse().map_err(|_| invalid())?,
+        );
+        if rule.requests == 0 || rule.window_seconds == 0 {
+            return Err(invalid());
+        }
+        Ok(rule)
+    }
+}
+
+/// Retirement of the paths without a version, which serve v1 (`/api/boats`
+/// is `/api/v1/boats`). Nothing is announced until
+/// `UNVERSIONED_API_DEPRECATED_AT` is set.
+#[derive(Debug, Clone, Default)]
+pub struct ApiVersionsConfig {
+    /// Sent as the `Deprecation` header of the unversioned paths
+    pub unversioned_deprecated_at: Option<DateTime<Utc>>,
+    /// Sent as their `Sunset` header (`UNVERSIONED_API_SUNSET`), only once
+    /// they are deprecated
+    pub unversioned_sunset: Option<DateTime<Utc>>,
+}
+
+/// An RFC 3339 date from the environment; unset or invalid is `None`
+fn date_from_env(name: &str) -> Option<DateTime<Utc>> {
+    env::var(name)
+        .ok()
+        .and_then(|date| DateTime::parse_from_rfc3339(date.trim()).ok())
+        .map(|date| date.to_utc())
+}
+
 impl AppConfig {
     pub fn from_env() -> Result<Self, env::VarError> {
+        let jwt_algorithm = env::var("JWT_ALGORITHM").unwrap_or("HS256".to_string());
+        let jwt_expiration_hours = env::var("JWT_EXPIRATION_HOURS")
+            .unwrap_or("24".to_string())
+            .parse()
+            .unwrap_or(24);
+
         Ok(AppConfig {
             database_url: env::var("DATABASE_URL")?,
             server_address: env::var("SERVER_ADDRESS").unwrap_or("127.0.0.1:3000".to_string()),
+            // Anything unrecognised falls back to the safe default
+            auth_mode: env::var("AUTH_MODE")
+                .ok()
+                .and_then(|mode| mode.parse().ok())
+                .unwrap_or(AuthMode::Production),
+            // Unrecognised policies reject, the safest choice
+            unverified_email_policy: match env::var("UNVERIFIED_EMAIL_POLICY") {
+                Ok(policy) => policy.parse().unwrap_or(UnverifiedEmailPolicy::Reject),
+                Err(_) => UnverifiedEmailPolicy::Restricted,
+            },
             firebase: FirebaseConfig {
                 project_id: env::var("FIREBASE_PROJECT_ID").unwrap_or("windspire-dev".to_string()),
+                certificates_url: env::var("FIREBASE_CERTIFICATES_URL")
+                    .unwrap_or(FIREBASE_CERTIFICATES_URL.to_string()),
             },
+            oidc_providers: env::var("OIDC_PROVIDERS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(OidcProviderConfig::from_env)
+                .collect::<Result<_, _>>()?,
             jwt: JwtConfig {
-                secret: env::var("JWT_SECRET")?,
-                expiration_hours: env::var("JWT_EXPIRATION_HOURS")
-                    .unwrap_or("24".to_string())
-                    .parse()
-                    .unwrap_or(24),
+                secret: env::var("JWT_SECRET").or_else(|e| {
+                    // Only HS256 signs with the shared secret
+                    if jwt_algorithm == "HS256" {
+                        Err(e)
+                    } else {
+                        Ok(String::new())
+                    }
+                })?,
+                expiration_hours: jwt_expiration_hours,
+                impersonation_expiration_minutes: env::var("IMPERSONATION_EXPIRATION_MINUTES")
+                    .ok()
+                    .and_then(|minutes| minutes.parse().ok())
+                    .unwrap_or(15),
                 issuer: env::var("JWT_ISSUER").unwrap_or("windspire".to_string()),
+                audience: env::var("JWT_AUDIENCE").unwrap_or("windspire-api".to_string()),
+                refresh_token_expiration_days: env::var("REFRESH_TOKEN_EXPIRATION_DAYS")
+                    .unwrap_or("30".to_string())
+                    .parse()
+                    .unwrap_or(30),
+                algorithm: jwt_algorithm,
+                signing_key_id: env::var("JWT_SIGNING_KEY_ID").unwrap_or("default".to_string()),
+                signing_key_file: env::var("JWT_SIGNING_KEY_FILE").ok(),
+                retired_keys: env::var("JWT_RETIRED_KEYS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                // Long enough for every token signed with the retired key to expire
+                retired_key_grace_hours: env::var("JWT_RETIRED_KEY_GRACE_HOURS")
+                    .ok()
+                    .and_then(|hours| hours.parse().ok())
+                    .unwrap_or(jwt_expiration_hours),
             },
             cors: CorsConfig {
                 allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
@@ -63,6 +334,72 @@ impl AppConfig {
                     "Accept".to_string(),
                 ],
             },
+            rate_limit: RateLimitConfig {
+                enabled: env::var("RATE_LIMIT_ENABLED")
+                    .map(|enabled| enabled != "false")
+                    .unwrap_or(true),
+                store: env::var("RATE_LIMIT_STORE")
+                    .ok()
+                    .and_then(|store| store.parse().ok())
+                    .unwrap_or(RateLimitStoreKind::Memory),
+                trust_forwarded_for: env::var("RATE_LIMIT_TRUST_FORWARDED_FOR")
+                    .map(|trust| trust == "true")
+                    .unwrap_or(false),
+                login: RateLimitRule::from_env("RATE_LIMIT_LOGIN", RateLimitRule::new(10, 60)),
+                refresh: RateLimitRule::from_env("RATE_LIMIT_REFRESH", RateLimitRule::new(30, 60)),
+                public: RateLimitRule::from_env("RATE_LIMIT_PUBLIC", RateLimitRule::new(120, 60)),
+                api: RateLimitRule::from_env("RATE_LIMIT_API", RateLimitRule::new(600, 60)),
+            },
+            api_versions: ApiVersionsConfig {
+                unversioned_deprecated_at: date_from_env("UNVERSIONED_API_DEPRECATED_AT"),
+                unversioned_sunset: date_from_env("UNVERSIONED_API_SUNSET"),
+            },
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_auth_mode() {
+        assert_eq!("production".parse(), Ok(AuthMode::Production));
+        assert_eq!("firebase-emulator".parse(), Ok(AuthMode::FirebaseEmulator));
+        assert_eq!("Test-Issuer".parse(), Ok(AuthMode::TestIssuer));
+        assert!("debug".parse::<AuthMode>().is_err());
+    }
+
+    #[test]
+    fn test_parse_rate_limit_rule() {
+        assert_eq!("10/60".parse(), Ok(RateLimitRule::new(10, 60)));
+        assert_eq!(" 5 / 1 ".parse(), Ok(RateLimitRule::new(5, 1)));
+        assert!("10".parse::<RateLimitRule>().is_err());
+        assert!("0/60".parse::<RateLimitRule>().is_err());
+        assert!("10/0".parse::<RateLimitRule>().is_err());
+        assert!("ten/60".parse::<RateLimitRule>().is_err());
+    }
+
+    #[test]
+    fn test_unverified_email_policy() {
+        assert_eq!("Pending".parse(), Ok(UnverifiedEmailPolicy::Pending));
+        assert!("allow".parse::<UnverifiedEmailPolicy>().is_err());
+
+        for policy in [
+            UnverifiedEmailPolicy::Reject,
+            UnverifiedEmailPolicy::Restricted,
+            UnverifiedEmailPolicy::Pending,
+        ] {
+            assert_eq!(policy.sign_up_status(true), Some(AccountStatus::Active));
+        }
+        assert_eq!(UnverifiedEmailPolicy::Reject.sign_up_status(false), None);
+        assert_eq!(
+            UnverifiedEmailPolicy::Restricted.sign_up_status(false),
+            Some(AccountStatus::Restricted)
+        );
+        assert_eq!(
+            UnverifiedEmailPolicy::Pending.sign_up_status(false),
+            Some(AccountStatus::Pending)
+        );
+    }
+}
@@ -0,0 +1,463 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use crate::application::middleware::correlation_id::current_correlation_id;
+use crate::application::services::api_key_service::ApiKeyError;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Prefix of the `type` URI of every problem; the error code completes it
+const PROBLEM_TYPE_PREFIX: &str = "urn:windspire:problem:";
+
+/// An error a handler or extractor answers with. Rendered as an RFC 7807
+/// `application/problem+json` body carrying a stable `code` and the request's
+/// correlation id. Internal details such as database errors are logged, never
+/// sent to the client.
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(String),
+    /// The body could not be deserialised or failed validation
+    Validation(Vec<FieldError>),
+    Unauthorized(String),
+    /// The access token expired; the client should refresh it and retry
+    TokenExpired,
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    TooManyRequests {
+        retry_after_seconds: u64,
+    },
+    /// `RowNotFound` and constraint violations are answered as 404 and 409,
+    /// anything else as a 500
+    Database(sqlx::Error),
+    /// The message is logged; the client only learns that something failed
+    Internal(String),
+}
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) | AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) | AppError::TokenExpired => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Database(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND,
+            AppError::Database(e) if is_constraint_violation(e) => StatusCode::CONFLICT,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine-readable error code, stable across releases
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::TokenExpired => "token_expired",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::NotFound(_) | AppError::Database(sqlx::Error::RowNotFound) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Database(e) if is_constraint_violation(e) => "conflict",
+            AppError::PayloadTooLarge => "payload_too_large",
+            AppError::UnsupportedMediaType => "unsupported_media_type",
+            AppError::TooManyRequests { .. } => "rate_limited",
+            AppError::Database(_) => "database_error",
+            AppError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Human-readable explanation, safe to show to the client
+    pub fn detail(&self) -> String {
+        match self {
+            AppError::BadRequest(message)
+            | AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::NotFound(message)
+            | AppError::Conflict(message) => message.clone(),
+            AppError::Validation(_) => "Request validation failed".to_string(),
+            AppError::TokenExpired => "Token has expired".to_string(),
+            AppError::PayloadTooLarge => "Request body is too large".to_string(),
+            AppError::UnsupportedMediaType => {
+                "Expected a JSON body with `Content-Type: application/json`".to_string()
+            }
+            AppError::TooManyRequests { .. } => "Too many requests".to_string(),
+            AppError::Database(sqlx::Error::RowNotFound) => "Resource not found".to_string(),
+            AppError::Database(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                "Resource already exists".to_string()
+            }
+            AppError::Database(e) if is_constraint_violation(e) => {
+                "Request conflicts with related data".to_string()
+            }
+            AppError::Database(_) | AppError::Internal(_) => "Internal server error".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Validation(errors) => {
+                write!(f, "Validation failed:")?;
+                for error in errors {
+                    write!(
+                        f,
+                        " {}: {};",
+                        error.field.as_deref().unwrap_or("body"),
+                        error.message
+                    )?;
+                }
+                Ok(())
+            }
+            AppError::Database(e) => write!(f, "Database error: {}", e),
+            AppError::Internal(message) => write!(f, "{}", message),
+            _ => write!(f, "{}", self.detail()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        AppError::Database(err)
+    }
+}
+
+impl From<ApiKeyError> for AppError {
+    fn from(err: ApiKeyError) -> Self {
+        match err {
+            ApiKeyError::NotFound => AppError::NotFound(err.to_string()),
+            ApiKeyError::UnknownPermission(_) => AppError::BadRequest(err.to_string()),
+            ApiKeyError::PermissionNotHeld(_) => AppError::Forbidden(err.to_string()),
+            ApiKeyError::InvalidKey | ApiKeyError::ExpiredKey | ApiKeyError::RevokedKey => {
+                AppError::Unauthorized(err.to_string())
+            }
+            ApiKeyError::Database(e) => AppError::Database(e),
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(err: ValidationErrors) -> Self {
+        AppError::Validation(FieldError::from_validation_errors(&err, None))
+    }
+}
+
+/// One entry of the `errors` list of a `validation_failed` problem
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the offending field as sent by the client, e.g. `sailNumber` or
+    /// `items[0].name`; absent when the body as a whole is at fault
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub params: Map<String, Value>,
+}
+
+impl FieldError {
+    pub fn new(field: Option<String>, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            code: code.to_string(),
+            message: message.into(),
+            params: Map::new(),
+        }
+    }
+
+    /// Flattens validator errors into a list sorted by field. The validator
+    /// only knows Rust field names; given the request body, each is replaced
+    /// by the key the client actually sent (`sail_number` -> `sailNumber`).
+    pub fn from_validation_errors(errors: &ValidationErrors, body: Option<&Value>) -> Vec<Self> {
+        let mut field_errors = Vec::new();
+        collect_field_errors(errors, None, body, &mut field_errors);
+        field_errors
+    }
+}
+
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    parent: Option<&str>,
+    body: Option<&Value>,
+    out: &mut Vec<FieldError>,
+) {
+    let sorted: BTreeMap<_, _> = errors.errors().iter().collect();
+    for (name, kind) in sorted {
+        let key = json_key(name, body);
+        let field = match (parent, name.as_ref()) {
+            // Struct-level validations are reported under `__all__`
+            (_, "__all__") => parent.map(str::to_string),
+            (Some(parent), _) => Some(format!("{}.{}", parent, key)),
+            (None, _) => Some(key.clone()),
+        };
+        let value = body.and_then(|body| body.get(&key));
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => out.extend(errors.iter().map(|error| {
+                FieldError {
+                    message: error.message.as_ref().map_or_else(
+                        || format!("{} is invalid", field.as_deref().unwrap_or("Request")),
+                        |message| message.to_string(),
+                    ),
+                    field: field.clone(),
+                    code: error.code.to_string(),
+                    params: error
+                        .params
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.clone()))
+                        .collect(),
+                }
+            })),
+            ValidationErrorsKind::Struct(errors) => {
+                collect_field_errors(errors, field.as_deref(), value, out)
+            }
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    let item = format!("{}[{}]", field.as_deref().unwrap_or_default(), index);
+                    let value = value.and_then(|value| value.get(index));
+                    collect_field_errors(errors, Some(&item), value, out);
+                }
+            }
+        }
+    }
+}
+
+/// The key of `body` a Rust field was read from: the name itself, or its
+/// camelCase form for models with `#[serde(rename_all = "camelCase")]`
+fn json_key(name: &str, body: Option<&Value>) -> String {
+    let Some(object) = body.and_then(Value::as_object) else {
+        return name.to_string();
+    };
+    if object.contains_key(name) {
+        return name.to_string();
+    }
+
+    let mut camel_case = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                camel_case.extend(c.to_uppercase());
+                upper = false;
+            }
+            c => camel_case.push(c),
+        }
+    }
+    if object.contains_key(&camel_case) {
+        camel_case
+    } else {
+        name.to_string()
+    }
+}
+
+/// The body of every error response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// One of the codes of [`AppError::code`]
+    pub code: String,
+    pub correlation_id: String,
+    /// Only on `validation_failed` problems
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let correlation_id = current_correlation_id().unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        if status.is_server_error() {
+            tracing::error!("Request failed: {}", self);
+        }
+
+        let problem = ProblemDetails {
+            problem_type: format!("{}{}", PROBLEM_TYPE_PREFIX, self.code()),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: self.detail(),
+            code: self.code().to_string(),
+            correlation_id,
+            errors: match &self {
+                AppError::Validation(errors) => errors.clone(),
+                _ => Vec::new(),
+            },
+        };
+
+        let mut response = (status, Json(problem)).into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        if let AppError::TooManyRequests {
+            retry_after_seconds,
+        } = self
+        {
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after_seconds));
+        }
+        response
+    }
+}
+
+fn is_constraint_violation(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Database(e) => e.is_unique_violation() || e.is_foreign_key_violation(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use validator::Validate;
+
+    async fn problem(error: AppError) -> (StatusCode, Option<String>, Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, content_type, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_problem_details() {
+        let (status, content_type, body) =
+            problem(AppError::NotFound("Boat not found".to_string())).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(content_type.as_deref(), Some(PROBLEM_JSON));
+        assert_eq!(body["type"], "urn:windspire:problem:not_found");
+        assert_eq!(body["title"], "Not Found");
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["detail"], "Boat not found");
+        assert_eq!(body["code"], "not_found");
+        assert!(body["correlation_id"]
+            .as_str()
+            .is_some_and(|id| !id.is_empty()));
+        assert!(body.get("errors").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_database_errors_are_not_exposed() {
+        let error = sqlx::Error::Protocol("SELECT secret FROM users WHERE id = 42".to_string());
+        let (status, _, body) = problem(AppError::from(error)).await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["code"], "database_error");
+        assert_eq!(body["detail"], "Internal server error");
+        assert!(!body.to_string().contains("SELECT"));
+
+        let (status, _, body) = problem(AppError::from(sqlx::Error::RowNotFound)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_validation_errors() {
+        let mut errors = ValidationErrors::new();
+        errors.add("email", validator::ValidationError::new("email"));
+        let (status, _, body) = problem(AppError::from(errors)).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "validation_failed");
+        assert_eq!(body["errors"][0]["field"], "email");
+        assert_eq!(body["errors"][0]["code"], "email");
+        assert_eq!(body["errors"][0]["message"], "email is invalid");
+        assert!(body["errors"][0]["params"].is_object());
+    }
+
+    #[derive(Debug, Validate)]
+    struct Crew {
+        #[validate(length(min = 2, message = "Name must contain 2 at least characters"))]
+        first_name: String,
+        #[validate(nested)]
+        boats: Vec<Boat>,
+    }
+
+    #[derive(Debug, Validate)]
+    struct Boat {
+        #[validate(length(min = 1))]
+        sail_number: String,
+    }
+
+    #[test]
+    fn test_field_errors_use_client_field_names() {
+        let crew = Crew {
+            first_name: "A".to_string(),
+            boats: vec![
+                Boat {
+                    sail_number: "NOR 1".to_string(),
+                },
+                Boat {
+                    sail_number: String::new(),
+                },
+            ],
+        };
+        let body = serde_json::json!({
+            "firstName": "A",
+            "boats": [{ "sailNumber": "NOR 1" }, { "sailNumber": "" }],
+        });
+        let errors = crew.validate().unwrap_err();
+
+        let field_errors = FieldError::from_validation_errors(&errors, Some(&body));
+        let fields: Vec<_> = field_errors
+            .iter()
+            .map(|error| (error.field.as_deref(), error.code.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                (Some("boats[1].sailNumber"), "length"),
+                (Some("firstName"), "length")
+            ]
+        );
+        assert_eq!(
+            field_errors[1].message,
+            "Name must contain 2 at least characters"
+        );
+        assert_eq!(field_errors[1].params["min"], 2);
+        assert_eq!(field_errors[1].params["value"], "A");
+
+        // Without the body the Rust names are all there is
+        let field_errors = FieldError::from_validation_errors(&errors, None);
+        assert_eq!(field_errors[1].field.as_deref(), Some("first_name"));
+    }
+
+    #[tokio::test]
+    async fn test_too_many_requests_sets_retry_after() {
+        let response = AppError::TooManyRequests {
+            retry_after_seconds: 30,
+        }
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+    }
+}
@@ -0,0 +1,760 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::application::error::AppError;
+use crate::application::http_response::{json_response, ok_json_response, ApiResponse, Message};
+use crate::application::middleware::rbac_middleware::user_has_permission;
+use crate::application::middleware::validated_json::ValidatedJson;
+use crate::application::state::AppState;
+use crate::domain::models::auth::{AuthContext, AuthUser};
+use crate::domain::models::permission_matcher::PermissionPattern;
+use crate::domain::models::rbac::{
+    AssignRole, AssignRoleOutcome, CreatePermission, CreateRole, Permission, PermissionName,
+    RemoveRoleOutcome, Role, RoleAssignment, RoleWithPermissions, UserRoleCreate, BUILT_IN_ROLES,
+    ROLE_ADMIN,
+};
+use crate::domain::repositories::permission_repository::PermissionRepository;
+use crate::domain::repositories::role_repository::RoleRepository;
+use crate::domain::repositories::user_repository::UserRepository;
+use crate::infrastructure::repositories::sqlx_permission_repository::SqlxPermissionRepository;
+use crate::infrastructure::repositories::sqlx_role_repository::SqlxRoleRepository;
+use crate::infrastructure::repositories::sqlx_user_repository::SqlxUserRepository;
+
+// Roles
+
+#[utoipa::path(
+    get,
+    path = "/admin/roles",
+    tag = "roles",
+    responses(
+        (status = 200, description = "All roles", body = ApiResponse<Vec<Role>>),
+    ),
+    security(("bearer_auth" = ["roles:read"]), ("api_key" = ["roles:read"])),
+)]
+pub async fn list_roles_handler(State(app_state): State<AppState>) -> Result<Response, AppError> {
+    match SqlxRoleRepository.get_all_roles(&app_state.db_pool).await {
+        Ok(roles) => Ok(ok_json_response(roles)),
+        Err(e) => Err(admin_error(e, "Role not found")),
+    }
+}
+
+/// A role together with the permissions it grants
+#[utoipa::path(
+    get,
+    path = "/admin/roles/{role_id}",
+    tag = "roles",
+    responses(
+        (status = 200, description = "The role and its permissions", body = ApiResponse<RoleWithPermissions>),
+        (status = 404, description = "Role not found"),
+    ),
+    security(("bearer_auth" = ["roles:read"]), ("api_key" = ["roles:read"])),
+)]
+pub async fn get_role_handler(
+    State(app_state): State<AppState>,
+    Path(role_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    let role = match SqlxRoleRepository.get_role_by_id(pool, role_id).await {
+        Ok(role) => role,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+
+    match SqlxRoleRepository.get_role_permissions(pool, role_id).await {
+        Ok(permissions) => Ok(ok_json_response(RoleWithPermissions { role, permissions })),
+        Err(e) => Err(admin_error(e, "Role not found")),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/roles",
+    tag = "roles",
+    request_body = CreateRole,
+    responses(
+        (status = 201, description = "The role", body = ApiResponse<Role>),
+        (status = 409, description = "Name is already taken"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn create_role_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    ValidatedJson(request): ValidatedJson<CreateRole>,
+) -> Result<Response, AppError> {
+    match SqlxRoleRepository
+        .create_role(
+            &app_state.db_pool,
+            &request.name,
+            Some(&request.description),
+        )
+        .await
+    {
+        Ok(role) => {
+            tracing::info!(
+                "User {} created role {}",
+                auth_context.user.email,
+                role.name
+            );
+            Ok(json_response(
+                StatusCode::CREATED,
+                json!({ "success": true, "data": role }),
+            ))
+        }
+        Err(e) => Err(admin_error(e, "Role not found")),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/admin/roles/{role_id}",
+    tag = "roles",
+    request_body = CreateRole,
+    responses(
+        (status = 200, description = "The updated role", body = ApiResponse<Role>),
+        (status = 403, description = "Only admins can change the admin role"),
+        (status = 404, description = "Role not found"),
+        (status = 409, description = "Name is already taken, or renaming a built-in role"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn update_role_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(role_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreateRole>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    let role = match SqlxRoleRepository.get_role_by_id(pool, role_id).await {
+        Ok(role) => role,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+    reject_role_change(&auth_context.user, &role)?;
+    if is_built_in_role(&role) && role.name != request.name {
+        return Err(AppError::Conflict(
+            "Built-in roles cannot be renamed".to_string(),
+        ));
+    }
+
+    match SqlxRoleRepository
+        .update_role(pool, role_id, &request.name, Some(&request.description))
+        .await
+    {
+        Ok(role) => Ok(ok_json_response(role)),
+        Err(e) => Err(admin_error(e, "Role not found")),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/roles/{role_id}",
+    tag = "roles",
+    responses(
+        (status = 200, description = "The role is deleted", body = ApiResponse<Message>),
+        (status = 404, description = "Role not found"),
+        (status = 409, description = "Built-in roles cannot be deleted"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn delete_role_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(role_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    let role = match SqlxRoleRepository.get_role_by_id(pool, role_id).await {
+        Ok(role) => role,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+    if is_built_in_role(&role) {
+        return Err(AppError::Conflict(
+            "Built-in roles cannot be deleted".to_string(),
+        ));
+    }
+
+    match SqlxRoleRepository.delete_role(pool, role_id).await {
+        Ok(()) => {
+            tracing::info!(
+                "User {} deleted role {}",
+                auth_context.user.email,
+                role.name
+            );
+            Ok(ok_json_response(json!({ "message": "Role deleted" })))
+        }
+        Err(e) => Err(admin_error(e, "Role not found")),
+    }
+}
+
+/// Grant a permission to a role. Callers can only grant what they hold themselves.
+#[utoipa::path(
+    put,
+    path = "/admin/roles/{role_id}/permissions/{permission_id}",
+    tag = "roles",
+    params(
+        ("role_id" = Uuid, Path, description = "ID of the role"),
+        ("permission_id" = Uuid, Path, description = "ID of the permission"),
+    ),
+    responses(
+        (status = 200, description = "The role grants the permission", body = ApiResponse<Message>),
+        (status = 403, description = "Granting a permission the caller does not hold, or changing the admin role as a non-admin"),
+        (status = 404, description = "Role or permission not found"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn grant_role_permission_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path((role_id, permission_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    let role = match SqlxRoleRepository.get_role_by_id(pool, role_id).await {
+        Ok(role) => role,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+    let permission = match SqlxPermissionRepository
+        .get_permission_by_id(pool, permission_id)
+        .await
+    {
+        Ok(permission) => permission,
+        Err(e) => return Err(admin_error(e, "Permission not found")),
+    };
+    reject_role_change(&auth_context.user, &role)?;
+    if !user_has_permission(&auth_context.user, &permission.name) {
+        return Err(AppError::Forbidden(
+            "Cannot grant a permission you don't have".to_string(),
+        ));
+    }
+
+    match SqlxRoleRepository
+        .assign_permission_to_role(pool, role_id, permission_id)
+        .await
+    {
+        Ok(()) => {
+            tracing::info!(
+                "User {} granted {} to role {}",
+                auth_context.user.email,
+                permission.name,
+                role.name
+            );
+            Ok(ok_json_response(json!({ "message": "Permission granted" })))
+        }
+        Err(e) => Err(admin_error(e, "Permission not found")),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/roles/{role_id}/permissions/{permission_id}",
+    tag = "roles",
+    params(
+        ("role_id" = Uuid, Path, description = "ID of the role"),
+        ("permission_id" = Uuid, Path, description = "ID of the permission"),
+    ),
+    responses(
+        (status = 200, description = "The role no longer grants the permission", body = ApiResponse<Message>),
+        (status = 403, description = "Only admins can change the admin role"),
+        (status = 404, description = "Role or permission not found"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn revoke_role_permission_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path((role_id, permission_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    let role = match SqlxRoleRepository.get_role_by_id(pool, role_id).await {
+        Ok(role) => role,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+    reject_role_change(&auth_context.user, &role)?;
+
+    match SqlxRoleRepository
+        .remove_permission_from_role(pool, role_id, permission_id)
+        .await
+    {
+        Ok(()) => {
+            tracing::info!(
+                "User {} revoked permission {} from role {}",
+                auth_context.user.email,
+                permission_id,
+                role.name
+            );
+            Ok(ok_json_response(json!({ "message": "Permission revoked" })))
+        }
+        Err(e) => Err(admin_error(e, "Permission not found")),
+    }
+}
+
+// Permissions
+
+#[utoipa::path(
+    get,
+    path = "/admin/permissions",
+    tag = "permissions",
+    responses(
+        (status = 200, description = "All permissions", body = ApiResponse<Vec<Permission>>),
+    ),
+    security(("bearer_auth" = ["roles:read"]), ("api_key" = ["roles:read"])),
+)]
+pub async fn list_permissions_handler(
+    State(app_state): State<AppState>,
+) -> Result<Response, AppError> {
+    match SqlxPermissionRepository
+        .get_all_permissions(&app_state.db_pool)
+        .await
+    {
+        Ok(permissions) => Ok(ok_json_response(permissions)),
+        Err(e) => Err(admin_error(e, "Permission not found")),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/permissions",
+    tag = "permissions",
+    request_body = CreatePermission,
+    responses(
+        (status = 201, description = "The permission", body = ApiResponse<Permission>),
+        (status = 400, description = "Invalid permission name"),
+        (status = 409, description = "Name is already taken"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn create_permission_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    ValidatedJson(request): ValidatedJson<CreatePermission>,
+) -> Result<Response, AppError> {
+    let pattern = PermissionPattern::parse(&request.name).ok_or_else(invalid_permission_name)?;
+
+    match SqlxPermissionRepository
+        .create_permission(
+            &app_state.db_pool,
+            &request.name,
+            Some(&request.description),
+            pattern.resource,
+            pattern.action,
+        )
+        .await
+    {
+        Ok(permission) => {
+            tracing::info!(
+                "User {} created permission {}",
+                auth_context.user.email,
+                permission.name
+            );
+            Ok(json_response(
+                StatusCode::CREATED,
+                json!({ "success": true, "data": permission }),
+            ))
+        }
+        Err(e) => Err(admin_error(e, "Permission not found")),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/admin/permissions/{permission_id}",
+    tag = "permissions",
+    request_body = CreatePermission,
+    responses(
+        (status = 200, description = "The updated permission", body = ApiResponse<Permission>),
+        (status = 400, description = "Invalid permission name"),
+        (status = 404, description = "Permission not found"),
+        (status = 409, description = "Name is already taken, or renaming a permission required by routes"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn update_permission_handler(
+    State(app_state): State<AppState>,
+    Path(permission_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreatePermission>,
+) -> Result<Response, AppError> {
+    let pattern = PermissionPattern::parse(&request.name).ok_or_else(invalid_permission_name)?;
+
+    let pool = &app_state.db_pool;
+    let permission = match SqlxPermissionRepository
+        .get_permission_by_id(pool, permission_id)
+        .await
+    {
+        Ok(permission) => permission,
+        Err(e) => return Err(admin_error(e, "Permission not found")),
+    };
+    if is_built_in_permission(&permission.name) && permission.name != request.name {
+        return Err(AppError::Conflict(
+            "Permissions required by routes cannot be renamed".to_string(),
+        ));
+    }
+
+    match SqlxPermissionRepository
+        .update_permission(
+            pool,
+            permission_id,
+            &request.name,
+            Some(&request.description),
+            pattern.resource,
+            pattern.action,
+        )
+        .await
+    {
+        Ok(permission) => Ok(ok_json_response(permission)),
+        Err(e) => Err(admin_error(e, "Permission not found")),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/permissions/{permission_id}",
+    tag = "permissions",
+    responses(
+        (status = 200, description = "The permission is deleted", body = ApiResponse<Message>),
+        (status = 404, description = "Permission not found"),
+        (status = 409, description = "Permissions required by routes cannot be deleted"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn delete_permission_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(permission_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    let permission = match SqlxPermissionRepository
+        .get_permission_by_id(pool, permission_id)
+        .await
+    {
+        Ok(permission) => permission,
+        Err(e) => return Err(admin_error(e, "Permission not found")),
+    };
+    if is_built_in_permission(&permission.name) {
+        return Err(AppError::Conflict(
+            "Permissions required by routes cannot be deleted".to_string(),
+        ));
+    }
+
+    match SqlxPermissionRepository
+        .delete_permission(pool, permission_id)
+        .await
+    {
+        Ok(()) => {
+            tracing::info!(
+                "User {} deleted permission {}",
+                auth_context.user.email,
+                permission.name
+            );
+            Ok(ok_json_response(json!({ "message": "Permission deleted" })))
+        }
+        Err(e) => Err(admin_error(e, "Permission not found")),
+    }
+}
+
+// User roles
+
+#[utoipa::path(
+    get,
+    path = "/admin/users/{user_id}/roles",
+    tag = "roles",
+    responses(
+        (status = 200, description = "Roles assigned to the user", body = ApiResponse<Vec<RoleAssignment>>),
+        (status = 404, description = "User not found"),
+    ),
+    security(("bearer_auth" = ["roles:read"]), ("api_key" = ["roles:read"])),
+)]
+pub async fn list_user_roles_handler(
+    State(app_state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    if let Err(e) = SqlxUserRepository.get_user_by_id(pool, user_id).await {
+        return Err(admin_error(e, "User not found"));
+    }
+
+    match SqlxRoleRepository
+        .get_user_role_assignments(pool, user_id)
+        .await
+    {
+        Ok(assignments) => Ok(ok_json_response(assignments)),
+        Err(e) => Err(admin_error(e, "User not found")),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/roles",
+    tag = "roles",
+    request_body = AssignRole,
+    responses(
+        (status = 201, description = "The role is assigned", body = ApiResponse<Message>),
+        (status = 200, description = "The user had the role; its window is replaced", body = ApiResponse<Message>),
+        (status = 400, description = "The window is empty or already over"),
+        (status = 403, description = "Only admins can hand out the admin role"),
+        (status = 404, description = "User or role not found"),
+        (status = 409, description = "The window would end or postpone the last permanent admin assignment"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn assign_user_role_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(user_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<AssignRole>,
+) -> Result<Response, AppError> {
+    reject_assignment_window(&request, Utc::now())?;
+
+    let pool = &app_state.db_pool;
+    if let Err(e) = SqlxUserRepository.get_user_by_id(pool, user_id).await {
+        return Err(admin_error(e, "User not found"));
+    }
+    let role = match SqlxRoleRepository
+        .get_role_by_id(pool, request.role_id)
+        .await
+    {
+        Ok(role) => role,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+    reject_role_change(&auth_context.user, &role)?;
+
+    // Assigning a role hands out all of its permissions
+    let permissions = match SqlxRoleRepository.get_role_permissions(pool, role.id).await {
+        Ok(permissions) => permissions,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+    if let Some(missing) = permissions
+        .iter()
+        .find(|p| !user_has_permission(&auth_context.user, &p.name))
+    {
+        return Err(AppError::Forbidden(format!(
+            "Cannot assign a role granting a permission you don't have: {}",
+            missing.name
+        )));
+    }
+
+    let assignment = UserRoleCreate {
+        user_id,
+        role_id: role.id,
+        valid_from: request.valid_from,
+        valid_until: request.valid_until,
+        assigned_by: Some(auth_context.user.id),
+    };
+
+    match SqlxRoleRepository
+        .assign_role_to_user(pool, &assignment)
+        .await
+    {
+        Ok(AssignRoleOutcome::LastAdmin) => Err(AppError::Conflict(
+            "The admin role of the last admin must start now and never end".to_string(),
+        )),
+        Ok(outcome) => {
+            app_state.permission_version_service.invalidate(user_id);
+            tracing::info!(
+                "User {} assigned role {} to user {} (valid from {:?} until {:?})",
+                auth_context.user.email,
+                role.name,
+                user_id,
+                request.valid_from,
+                request.valid_until
+            );
+            if outcome == AssignRoleOutcome::Updated {
+                return Ok(ok_json_response(
+                    json!({ "message": "Role assignment updated" }),
+                ));
+            }
+            Ok(json_response(
+                StatusCode::CREATED,
+                json!({ "success": true, "data": { "message": "Role assigned" } }),
+            ))
+        }
+        Err(e) => Err(admin_error(e, "User not found")),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/users/{user_id}/roles/{role_id}",
+    tag = "roles",
+    params(
+        ("user_id" = Uuid, Path, description = "ID of the user"),
+        ("role_id" = Uuid, Path, description = "ID of the role"),
+    ),
+    responses(
+        (status = 200, description = "The role is removed", body = ApiResponse<Message>),
+        (status = 403, description = "Only admins can change the admin role"),
+        (status = 404, description = "Role not found or not assigned to the user"),
+        (status = 409, description = "The user is the only permanent admin left"),
+    ),
+    security(("bearer_auth" = ["roles:write"]), ("api_key" = ["roles:write"])),
+)]
+pub async fn remove_user_role_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path((user_id, role_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, AppError> {
+    let pool = &app_state.db_pool;
+    let role = match SqlxRoleRepository.get_role_by_id(pool, role_id).await {
+        Ok(role) => role,
+        Err(e) => return Err(admin_error(e, "Role not found")),
+    };
+    reject_role_change(&auth_context.user, &role)?;
+
+    match SqlxRoleRepository
+        .remove_role_from_user(pool, user_id, role_id)
+        .await
+    {
+        Ok(RemoveRoleOutcome::Removed) => {
+            app_state.permission_version_service.invalidate(user_id);
+            tracing::info!(
+                "User {} removed role {} from user {}",
+                auth_context.user.email,
+                role.name,
+                user_id
+            );
+            Ok(ok_json_response(json!({ "message": "Role removed" })))
+        }
+        Ok(RemoveRoleOutcome::NotAssigned) => Err(AppError::NotFound(
+            "User does not have the role".to_string(),
+        )),
+        Ok(RemoveRoleOutcome::LastAdmin) => Err(AppError::Conflict(
+            "Cannot remove the admin role from the last admin".to_string(),
+        )),
+        Err(e) => Err(admin_error(e, "User not found")),
+    }
+}
+
+/// Only admins may change the admin role or hand it out, so holders of
+/// `roles:write` cannot promote themselves
+fn reject_role_change(user: &AuthUser, role: &Role) -> Result<(), AppError> {
+    if role.name == ROLE_ADMIN && !user.roles.iter().any(|r| r == ROLE_ADMIN) {
+        return Err(AppError::Forbidden(
+            "Only admins can change the admin role".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Fails when the assignment window is empty or already over
+fn reject_assignment_window(request: &AssignRole, now: DateTime<Utc>) -> Result<(), AppError> {
+    let Some(valid_until) = request.valid_until else {
+        return Ok(());
+    };
+
+    if valid_until <= request.valid_from.unwrap_or(now).max(now) {
+        return Err(AppError::BadRequest(
+            "valid_until must be in the future and after valid_from".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn is_built_in_role(role: &Role) -> bool {
+    BUILT_IN_ROLES.contains(&role.name.as_str())
+}
+
+fn is_built_in_permission(name: &str) -> bool {
+    name.parse::<PermissionName>().is_ok()
+}
+
+fn invalid_permission_name() -> AppError {
+    AppError::BadRequest(
+        "Permission names are resource:action or scope:{id}:resource:action".to_string(),
+    )
+}
+
+fn admin_error(error: sqlx::Error, not_found: &str) -> AppError {
+    match &error {
+        sqlx::Error::RowNotFound => AppError::NotFound(not_found.to_string()),
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            AppError::Conflict("Name is already taken".to_string())
+        }
+        _ => AppError::Database(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_user(roles: Vec<&str>) -> AuthUser {
+        AuthUser {
+            id: Uuid::new_v4(),
+            email: "test@example.com".to_string(),
+            first_name: "Test".to_string(),
+            last_name: "User".to_string(),
+            provider_id: "123".to_string(),
+            provider_name: "google".to_string(),
+            avatar_url: None,
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            permissions: vec!["roles:write".to_string()],
+            permission_version: 0,
+        }
+    }
+
+    fn create_test_role(name: &str) -> Role {
+        Role {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: String::new(),
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_only_admins_change_the_admin_role() {
+        let admin = create_test_user(vec![ROLE_ADMIN]);
+        let role_manager = create_test_user(vec!["user"]);
+
+        assert!(reject_role_change(&admin, &create_test_role(ROLE_ADMIN)).is_ok());
+        assert_eq!(
+            reject_role_change(&role_manager, &create_test_role(ROLE_ADMIN))
+                .err()
+                .map(|e| e.status()),
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert!(reject_role_change(&role_manager, &create_test_role("club_official")).is_ok());
+    }
+
+    #[test]
+    fn test_assignment_window() {
+        let now = Utc::now();
+        let assign = |valid_from, valid_until| AssignRole {
+            role_id: Uuid::new_v4(),
+            valid_from,
+            valid_until,
+        };
+        let weekend_start = now + chrono::Duration::days(2);
+        let weekend_end = now + chrono::Duration::days(4);
+
+        assert!(reject_assignment_window(&assign(None, None), now).is_ok());
+        assert!(
+            reject_assignment_window(&assign(Some(weekend_start), Some(weekend_end)), now).is_ok()
+        );
+        assert!(reject_assignment_window(&assign(None, Some(weekend_end)), now).is_ok());
+        // Ends before it starts
+        assert!(
+            reject_assignment_window(&assign(Some(weekend_end), Some(weekend_start)), now).is_err()
+        );
+        // Already over
+        assert!(reject_assignment_window(
+            &assign(
+                Some(now - chrono::Duration::days(4)),
+                Some(now - chrono::Duration::days(2))
+            ),
+            now
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_built_in_roles_and_permissions() {
+        assert!(is_built_in_role(&create_test_role("user")));
+        assert!(!is_built_in_role(&create_test_role("club_official")));
+        assert!(is_built_in_permission("boats:write"));
+        assert!(!is_built_in_permission("boats:*"));
+    }
+}
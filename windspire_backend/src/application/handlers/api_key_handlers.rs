@@ -0,0 +1,292 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::application::error::AppError;
+use crate::application::handlers::auth_handlers::get_default_country_id;
+use crate::application::http_response::{json_response, ok_json_response, ApiResponse, Message};
+use crate::application::middleware::validated_json::ValidatedJson;
+use crate::application::services::api_key_service::IssuedApiKey;
+use crate::application::state::AppState;
+use crate::domain::models::api_key::{ApiKey, CreateApiKey, CreateServiceAccount, ServiceAccount};
+use crate::domain::models::auth::AuthContext;
+use crate::domain::repositories::api_key_repository::ApiKeyRepository;
+use crate::infrastructure::repositories::sqlx_api_key_repository::SqlxApiKeyRepository;
+
+/// Create an API key for the current user, scoped to a subset of their permissions
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    tag = "api-keys",
+    request_body = CreateApiKey,
+    responses(
+        (status = 201, description = "The key; its secret is never shown again", body = ApiResponse<IssuedApiKey>),
+        (status = 400, description = "Unknown permission or expiry in the past"),
+        (status = 403, description = "Called with an API key or an impersonation token, or asking for permissions the caller does not hold"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn create_api_key_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    ValidatedJson(request): ValidatedJson<CreateApiKey>,
+) -> Result<Response, AppError> {
+    reject_key_issue(&auth_context, &request)?;
+
+    let user = &auth_context.user;
+    let issued = app_state
+        .api_key_service
+        .create(
+            &app_state.db_pool,
+            user.id,
+            user.id,
+            &request,
+            &user.permissions,
+        )
+        .await?;
+
+    tracing::info!(
+        "User {} created API key {}",
+        user.email,
+        issued.api_key.prefix
+    );
+    Ok(created_api_key_response(issued))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    tag = "api-keys",
+    responses(
+        (status = 200, description = "API keys of the user", body = ApiResponse<Vec<ApiKey>>),
+    ),
+)]
+pub async fn list_api_keys_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Response, AppError> {
+    let keys = app_state
+        .api_key_service
+        .list(&app_state.db_pool, auth_context.user.id)
+        .await?;
+    Ok(ok_json_response(keys))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{key_id}",
+    tag = "api-keys",
+    responses(
+        (status = 200, description = "The key is revoked", body = ApiResponse<Message>),
+        (status = 403, description = "Called with an impersonation token"),
+        (status = 404, description = "API key not found"),
+    ),
+)]
+pub async fn revoke_api_key_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(key_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    app_state
+        .api_key_service
+        .revoke(&app_state.db_pool, auth_context.user.id, key_id)
+        .await?;
+    Ok(ok_json_response(json!({ "message": "API key revoked" })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/service-accounts",
+    tag = "service-accounts",
+    request_body = CreateServiceAccount,
+    responses(
+        (status = 201, description = "The service account", body = ApiResponse<ServiceAccount>),
+    ),
+    security(("bearer_auth" = ["users:write"]), ("api_key" = ["users:write"])),
+)]
+pub async fn create_service_account_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    ValidatedJson(request): ValidatedJson<CreateServiceAccount>,
+) -> Result<Response, AppError> {
+    let country_id = get_default_country_id(&app_state.db_pool).await?;
+    let account = SqlxApiKeyRepository
+        .create_service_account(&app_state.db_pool, &request.name, country_id)
+        .await?;
+
+    tracing::info!(
+        "User {} created service account {}",
+        auth_context.user.email,
+        account.id
+    );
+    Ok(json_response(
+        StatusCode::CREATED,
+        json!({ "success": true, "data": account }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/service-accounts",
+    tag = "service-accounts",
+    responses(
+        (status = 200, description = "All service accounts", body = ApiResponse<Vec<ServiceAccount>>),
+    ),
+    security(("bearer_auth" = ["users:write"]), ("api_key" = ["users:write"])),
+)]
+pub async fn list_service_accounts_handler(
+    State(app_state): State<AppState>,
+) -> Result<Response, AppError> {
+    let accounts = SqlxApiKeyRepository
+        .get_service_accounts(&app_state.db_pool)
+        .await?;
+    Ok(ok_json_response(accounts))
+}
+
+/// Create a key for a service account. The key can only carry permissions the
+/// caller holds, since it is all the service account will be able to do.
+#[utoipa::path(
+    post,
+    path = "/service-accounts/{user_id}/api-keys",
+    tag = "service-accounts",
+    params(("user_id" = Uuid, Path, description = "ID of the service account")),
+    request_body = CreateApiKey,
+    responses(
+        (status = 201, description = "The key; its secret is never shown again", body = ApiResponse<IssuedApiKey>),
+        (status = 400, description = "Unknown permission or expiry in the past"),
+        (status = 403, description = "Called with an API key or an impersonation token, or asking for permissions the caller does not hold"),
+        (status = 404, description = "Service account not found"),
+    ),
+    security(("bearer_auth" = ["users:write"])),
+)]
+pub async fn create_service_account_api_key_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(service_account_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreateApiKey>,
+) -> Result<Response, AppError> {
+    reject_key_issue(&auth_context, &request)?;
+    reject_unknown_service_account(&app_state, service_account_id).await?;
+
+    let user = &auth_context.user;
+    let issued = app_state
+        .api_key_service
+        .create(
+            &app_state.db_pool,
+            service_account_id,
+            user.id,
+            &request,
+            &user.permissions,
+        )
+        .await?;
+
+    tracing::info!(
+        "User {} created API key {} for service account {}",
+        user.email,
+        issued.api_key.prefix,
+        service_account_id
+    );
+    Ok(created_api_key_response(issued))
+}
+
+#[utoipa::path(
+    get,
+    path = "/service-accounts/{user_id}/api-keys",
+    tag = "service-accounts",
+    params(("user_id" = Uuid, Path, description = "ID of the service account")),
+    responses(
+        (status = 200, description = "API keys of the service account", body = ApiResponse<Vec<ApiKey>>),
+        (status = 404, description = "Service account not found"),
+    ),
+    security(("bearer_auth" = ["users:write"]), ("api_key" = ["users:write"])),
+)]
+pub async fn list_service_account_api_keys_handler(
+    State(app_state): State<AppState>,
+    Path(service_account_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    reject_unknown_service_account(&app_state, service_account_id).await?;
+
+    let keys = app_state
+        .api_key_service
+        .list(&app_state.db_pool, service_account_id)
+        .await?;
+    Ok(ok_json_response(keys))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/service-accounts/{user_id}/api-keys/{key_id}",
+    tag = "service-accounts",
+    params(
+        ("user_id" = Uuid, Path, description = "ID of the service account"),
+        ("key_id" = Uuid, Path, description = "ID of the API key"),
+    ),
+    responses(
+        (status = 200, description = "The key is revoked", body = ApiResponse<Message>),
+        (status = 404, description = "Service account or API key not found"),
+    ),
+    security(("bearer_auth" = ["users:write"]), ("api_key" = ["users:write"])),
+)]
+pub async fn revoke_service_account_api_key_handler(
+    State(app_state): State<AppState>,
+    Path((service_account_id, key_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, AppError> {
+    reject_unknown_service_account(&app_state, service_account_id).await?;
+
+    app_state
+        .api_key_service
+        .revoke(&app_state.db_pool, service_account_id, key_id)
+        .await?;
+    Ok(ok_json_response(json!({ "message": "API key revoked" })))
+}
+
+/// Fails when a key may not be created. New keys need an interactive
+/// session, so a leaked key cannot mint keys that outlive it.
+fn reject_key_issue(auth_context: &AuthContext, request: &CreateApiKey) -> Result<(), AppError> {
+    if auth_context.api_key_id.is_some() {
+        return Err(AppError::Forbidden(
+            "API keys cannot create API keys".to_string(),
+        ));
+    }
+
+    if request
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= chrono::Utc::now())
+    {
+        return Err(AppError::BadRequest(
+            "expires_at must be in the future".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fails with 404 when `user_id` is not a service account
+async fn reject_unknown_service_account(
+    app_state: &AppState,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    match SqlxApiKeyRepository
+        .get_service_account(&app_state.db_pool, user_id)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(sqlx::Error::RowNotFound) => {
+            Err(AppError::NotFound("Service account not found".to_string()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn created_api_key_response(issued: IssuedApiKey) -> Response {
+    // The only time the key is ever shown
+    json_response(
+        StatusCode::CREATED,
+        json!({ "success": true, "data": issued }),
+    )
+}
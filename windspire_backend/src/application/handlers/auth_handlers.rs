@@ -1,40 +1,66 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
 use serde::{Deserialize, Serialize};
-
-use uuid::Uuid;
-
-use crate::application::http_response::ok_json_response;
-
-use crate::domain::models::auth::AuthUser;
-use crate::domain::models::user::{OAuthUserCreate, User};
+use utoipa::ToSchema;
+
+use uuid::{NoContext, Timestamp, Uuid};
+use validator::Validate;
+
+use crate::application::error::AppError;
+use crate::application::handlers::session_handlers::end_session;
+use crate::application::http_response::{ok_json_response, ApiResponse, Message};
+use crate::application::middleware::client_info::ClientInfo;
+use crate::application::middleware::validated_json::ValidatedJson;
+use crate::application::services::firebase_service::FIREBASE_PROVIDER_NAME;
+use crate::application::services::refresh_token_service::RefreshTokenError;
+use crate::application::services::test_issuer::TestIdTokenRequest;
+
+use crate::domain::models::auth::{Actor, AuthContext, AuthUser, Claims};
+use crate::domain::models::rbac::UserWithRoles;
+use crate::domain::models::user::{AccountStatus, OAuthUserCreate, User};
+use crate::domain::models::user_identity::{LinkIdentityOutcome, UserIdentityCreate};
+use crate::domain::models::user_session::UserSessionCreate;
+use crate::domain::repositories::user_identity_repository::UserIdentityRepository;
 use crate::domain::repositories::user_repository::UserRepository;
+use crate::infrastructure::repositories::sqlx_user_identity_repository::SqlxUserIdentityRepository;
 use crate::infrastructure::repositories::sqlx_user_repository::SqlxUserRepository;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct RefreshTokenRequest {
     pub refresh_token: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct FirebaseAuthRequest {
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct IdTokenAuthRequest {
     pub id_token: String,
     pub display_name: Option<String>, // Optional display name for registration
 }
 
-#[derive(Debug, Serialize)]
-pub struct FirebaseAuthResponse {
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct IdTokenAuthResponse {
     pub success: bool,
     pub data: Option<AuthTokenData>,
     pub message: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct AuthTokenData {
     pub token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
     pub user: UserInfo,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserInfo {
     pub id: String,
     pub email: String,
@@ -42,67 +68,279 @@ pub struct UserInfo {
     pub picture: Option<String>,
 }
 
+/// `data` of a successful `/auth/refresh`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TokenRefreshResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+}
+
+/// `data` of `/auth/me`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CurrentUser {
+    pub id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub picture: Option<String>,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    pub account_status: AccountStatus,
+    pub email_verified: bool,
+    pub impersonated_by: Option<Actor>, // Set when using an impersonation token
+}
+
+/// `data` of `/auth/test-issuer/token`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TestIdTokenResponse {
+    pub id_token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "A new access token and the refresh token replacing the one sent", body = ApiResponse<TokenRefreshResponse>),
+        (status = 401, description = "Unknown, expired or already used refresh token"),
+    ),
+    security(()),
+)]
 pub async fn refresh_token_handler(
     State(app_state): State<crate::application::state::AppState>,
-    Json(request): Json<RefreshTokenRequest>,
-) -> impl IntoResponse {
-    // For now, we'll treat the refresh token as a JWT token
-    // In a production system, you'd want separate refresh tokens
-    let claims = match app_state.jwt_service.validate_token(&request.refresh_token) {
-        Ok(claims) => claims,
+    ValidatedJson(request): ValidatedJson<RefreshTokenRequest>,
+) -> Result<Response, AppError> {
+    // Replaying an already used refresh token revokes the whole family
+    let presented = match app_state
+        .refresh_token_service
+        .redeemable(&app_state.db_pool, &request.refresh_token)
+        .await
+    {
+        Ok(presented) => presented,
+        Err(RefreshTokenError::Database(e)) => {
+            return Err(AppError::Internal(format!(
+                "Failed to look up refresh token: {}",
+                e
+            )));
+        }
         Err(e) => {
-            tracing::error!("Invalid refresh token: {}", e);
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({
-                    "success": false,
-                    "message": "Invalid refresh token"
-                })),
-            )
-                .into_response();
+            tracing::warn!("Refresh token rejected: {}", e);
+            return Err(AppError::Unauthorized("Invalid refresh token".to_string()));
         }
     };
 
-    let new_token = match app_state.jwt_service.refresh_token(&claims) {
-        Ok(token) => token,
+    // Roles and permissions are re-read so the new access token reflects the
+    // current RBAC state instead of whatever the previous token carried. This
+    // happens before the token is spent, so a failure here leaves the client
+    // with a token it can retry with.
+    let auth_user = load_auth_user(&app_state.db_pool, presented.user_id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to load user for token refresh: {}", e)))?;
+
+    // Exchange the opaque refresh token for its successor
+    let refresh_token = match app_state
+        .refresh_token_service
+        .rotate(&app_state.db_pool, &presented)
+        .await
+    {
+        Ok(refresh_token) => refresh_token,
+        Err(RefreshTokenError::Database(e)) => {
+            return Err(AppError::Internal(format!(
+                "Failed to rotate refresh token: {}",
+                e
+            )));
+        }
         Err(e) => {
-            tracing::error!("Failed to refresh token: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "success": false,
-                    "message": "Failed to refresh token"
-                })),
-            )
-                .into_response();
+            tracing::warn!("Refresh token rejected: {}", e);
+            return Err(AppError::Unauthorized("Invalid refresh token".to_string()));
         }
     };
 
-    let response = serde_json::json!({
-        "access_token": new_token,
-        "token_type": "Bearer",
-        "expires_in": app_state.config.jwt.expiration_hours * 3600
-    });
+    // The family id is the session id
+    let access_token = app_state
+        .jwt_service
+        .generate_session_token(&auth_user, refresh_token.family_id)
+        .map_err(|e| AppError::Internal(format!("Failed to refresh token: {}", e)))?;
+
+    if let Err(e) = app_state
+        .session_service
+        .extend(
+            &app_state.db_pool,
+            refresh_token.family_id,
+            refresh_token.expires_at,
+        )
+        .await
+    {
+        tracing::error!("Failed to record session refresh: {}", e);
+    }
+
+    Ok(ok_json_response(TokenRefreshResponse {
+        access_token,
+        refresh_token: refresh_token.token,
+        token_type: "Bearer".to_string(),
+        expires_in: app_state.config.jwt.expiration_hours * 3600,
+    }))
+}
 
-    ok_json_response(response).into_response()
+#[utoipa::path(
+    post,
+    path = "/auth/revoke",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "The refresh token is revoked; unknown tokens are not reported", body = ApiResponse<Message>),
+    ),
+    security(()),
+)]
+pub async fn revoke_token_handler(
+    State(app_state): State<crate::application::state::AppState>,
+    ValidatedJson(request): ValidatedJson<RefreshTokenRequest>,
+) -> Result<Response, AppError> {
+    // Unknown tokens are not reported back, in line with RFC 7009
+    match app_state
+        .refresh_token_service
+        .revoke(&app_state.db_pool, &request.refresh_token)
+        .await
+    {
+        Ok(revoked) => {
+            // The login's access tokens go along with its refresh tokens
+            if let Err(e) = end_session(&app_state, revoked.user_id, revoked.family_id).await {
+                tracing::error!("Failed to end session of revoked refresh token: {}", e);
+            }
+            Ok(ok_json_response(serde_json::json!({
+                "message": "Refresh token revoked"
+            })))
+        }
+        Err(RefreshTokenError::InvalidToken) => Ok(ok_json_response(serde_json::json!({
+            "message": "Refresh token revoked"
+        }))),
+        Err(e) => Err(AppError::Internal(format!(
+            "Failed to revoke refresh token: {}",
+            e
+        ))),
+    }
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    request_body(content = Option<LogoutRequest>, description = "Send the refresh token along to revoke it too"),
+    responses(
+        (status = 200, description = "The session is ended", body = ApiResponse<Message>),
+        (status = 400, description = "Called with an API key"),
+    ),
+    security(("bearer_auth" = [])),
+)]
 pub async fn logout_handler(
-    State(_app_state): State<crate::application::state::AppState>,
-) -> impl IntoResponse {
-    // For JWT tokens, logout is typically handled client-side
-    // by removing the token from storage
-    tracing::info!("User logout requested");
+    State(app_state): State<crate::application::state::AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    claims: Option<Extension<Claims>>,
+    request: Option<Json<LogoutRequest>>,
+) -> Result<Response, AppError> {
+    // API keys are not sessions; they are revoked through the API key endpoints
+    let Some(Extension(claims)) = claims else {
+        return Err(api_key_logout_error());
+    };
 
-    ok_json_response(serde_json::json!({
+    // Log out this session: deny the presented access token until it expires,
+    // end the session it was issued to and, when the client sends it along,
+    // its refresh token family
+    app_state
+        .token_revocation_service
+        .revoke_token(&app_state.db_pool, auth_context.user.id, &claims)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to revoke access token: {}", e)))?;
+
+    if let Some(session_id) = claims.sid.as_deref().and_then(|sid| sid.parse().ok()) {
+        if let Err(e) = end_session(&app_state, auth_context.user.id, session_id).await {
+            tracing::error!("Failed to end session on logout: {}", e);
+        }
+    }
+
+    if let Some(refresh_token) = request.and_then(|Json(r)| r.refresh_token) {
+        match app_state
+            .refresh_token_service
+            .revoke(&app_state.db_pool, &refresh_token)
+            .await
+        {
+            Ok(_) | Err(RefreshTokenError::InvalidToken) => {}
+            Err(e) => tracing::error!("Failed to revoke refresh token on logout: {}", e),
+        }
+    }
+
+    tracing::info!("User {} logged out", auth_context.user.email);
+
+    Ok(ok_json_response(serde_json::json!({
         "message": "Successfully logged out"
-    }))
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/logout/all",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Every session of the user is ended", body = ApiResponse<Message>),
+        (status = 400, description = "Called with an API key"),
+        (status = 403, description = "Called with an impersonation token"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn logout_all_handler(
+    State(app_state): State<crate::application::state::AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Response, AppError> {
+    if auth_context.api_key_id.is_some() {
+        return Err(api_key_logout_error());
+    }
+
+    // Log out everywhere: every access token issued so far and every refresh
+    // token family of the user stop working
+    let user_id = auth_context.user.id;
+
+    app_state
+        .token_revocation_service
+        .revoke_all_for_user(&app_state.db_pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to revoke access tokens: {}", e)))?;
+
+    app_state
+        .refresh_token_service
+        .revoke_all_for_user(&app_state.db_pool, user_id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to revoke refresh tokens: {}", e)))?;
+
+    tracing::info!(
+        "User {} logged out of all sessions",
+        auth_context.user.email
+    );
+
+    Ok(ok_json_response(serde_json::json!({
+        "message": "Successfully logged out of all sessions"
+    })))
 }
 
+fn api_key_logout_error() -> AppError {
+    AppError::BadRequest("API keys cannot log out; revoke the key instead".to_string())
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/me",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The authenticated user", body = ApiResponse<CurrentUser>),
+    ),
+)]
 pub async fn me_handler(
-    State(_app_state): State<crate::application::state::AppState>,
+    State(app_state): State<crate::application::state::AppState>,
     request: axum::extract::Request,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     // Extract auth context from request extensions
     let auth_context = match request
         .extensions()
@@ -110,84 +348,162 @@ pub async fn me_handler(
     {
         Some(ctx) => ctx,
         None => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({
-                    "success": false,
-                    "message": "Authentication required"
-                })),
-            )
-                .into_response();
+            return Err(AppError::Unauthorized(
+                "Authentication required".to_string(),
+            ));
         }
     };
 
     tracing::info!("Getting current user info for: {}", auth_context.user.email);
 
+    // Unlike the rest, the account state can change during the token's lifetime
+    let account_state = SqlxUserRepository
+        .get_account_state(&app_state.db_pool, auth_context.user.id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to get account state: {}", e)))?;
+
     // Return the user info from the JWT token
-    let user_info = serde_json::json!({
-        "id": auth_context.user.id,
-        "email": auth_context.user.email,
-        "name": format!("{} {}", auth_context.user.first_name, auth_context.user.last_name),
-        "first_name": auth_context.user.first_name,
-        "last_name": auth_context.user.last_name,
-        "picture": auth_context.user.avatar_url,
-        "roles": auth_context.user.roles,
-        "permissions": auth_context.user.permissions
-    });
-
-    ok_json_response(user_info)
+    let user = &auth_context.user;
+    Ok(ok_json_response(CurrentUser {
+        id: user.id,
+        email: user.email.clone(),
+        name: format!("{} {}", user.first_name, user.last_name),
+        first_name: user.first_name.clone(),
+        last_name: user.last_name.clone(),
+        picture: user.avatar_url.clone(),
+        roles: user.roles.clone(),
+        permissions: user.permissions.clone(),
+        account_status: account_state.status,
+        email_verified: account_state.email_verified,
+        impersonated_by: auth_context.impersonator.clone(),
+    }))
 }
 
-pub async fn firebase_auth_handler(
+/// Public keys that verify our access tokens, for services that accept them
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "auth",
+    responses(
+        (status = 200, description = "JSON Web Key Set", body = Object),
+    ),
+    security(()),
+)]
+pub async fn jwks_handler(
     State(app_state): State<crate::application::state::AppState>,
-    Json(payload): Json<FirebaseAuthRequest>,
 ) -> impl IntoResponse {
-    tracing::info!("Processing Firebase authentication");
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(app_state.jwt_service.jwks()),
+    )
+}
+
+/// Mint an ID token from the built-in test issuer (`AUTH_MODE=test-issuer` only),
+/// to be exchanged at `/auth/firebase` like a real Firebase token
+#[utoipa::path(
+    post,
+    path = "/auth/test-issuer/token",
+    tag = "auth",
+    request_body = TestIdTokenRequest,
+    responses(
+        (status = 200, description = "An ID token for `/auth/firebase`", body = ApiResponse<TestIdTokenResponse>),
+        (status = 404, description = "The test issuer is not enabled"),
+    ),
+    security(()),
+)]
+pub async fn test_issuer_token_handler(
+    State(app_state): State<crate::application::state::AppState>,
+    ValidatedJson(request): ValidatedJson<TestIdTokenRequest>,
+) -> Result<Response, AppError> {
+    let Some(test_issuer) = &app_state.test_issuer else {
+        return Err(AppError::NotFound("Test issuer is not enabled".to_string()));
+    };
+
+    let id_token = test_issuer
+        .issue_id_token(&request)
+        .map_err(|e| AppError::Internal(format!("Failed to issue test ID token: {}", e)))?;
+    Ok(ok_json_response(TestIdTokenResponse { id_token }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/firebase",
+    tag = "auth",
+    request_body = IdTokenAuthRequest,
+    responses(
+        (status = 200, description = "Logged in; signs up users on their first login", body = IdTokenAuthResponse),
+        (status = 401, description = "Invalid ID token"),
+        (status = 403, description = "Email not verified or account pending"),
+    ),
+    security(()),
+)]
+pub async fn firebase_auth_handler(
+    State(app_state): State<crate::application::state::AppState>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<IdTokenAuthRequest>,
+) -> Result<Response, AppError> {
+    identity_provider_auth_handler(
+        State(app_state),
+        Path(FIREBASE_PROVIDER_NAME.to_string()),
+        client,
+        ValidatedJson(payload),
+    )
+    .await
+}
+
+/// Log in with an ID token issued by one of the configured identity providers
+#[utoipa::path(
+    post,
+    path = "/auth/providers/{provider}",
+    tag = "auth",
+    params(("provider" = String, Path, description = "Name of a configured identity provider")),
+    request_body = IdTokenAuthRequest,
+    responses(
+        (status = 200, description = "Logged in; signs up users on their first login", body = IdTokenAuthResponse),
+        (status = 401, description = "Invalid ID token"),
+        (status = 403, description = "Email not verified or account pending"),
+        (status = 404, description = "Unknown identity provider"),
+    ),
+    security(()),
+)]
+pub async fn identity_provider_auth_handler(
+    State(app_state): State<crate::application::state::AppState>,
+    Path(provider_name): Path<String>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<IdTokenAuthRequest>,
+) -> Result<Response, AppError> {
+    let Some(provider) = app_state.identity_providers.get(&provider_name) else {
+        return Err(AppError::NotFound("Unknown identity provider".to_string()));
+    };
+
+    tracing::info!("Processing {} authentication", provider.name());
     tracing::info!(
         "Received payload - display_name: {:?}",
         payload.display_name
     );
 
-    // Verify Firebase ID token
-    #[cfg(debug_assertions)]
-    let firebase_result = app_state
-        .firebase_service
-        .verify_id_token_unsafe(&payload.id_token)
-        .await;
-
-    #[cfg(not(debug_assertions))]
-    let firebase_result = app_state
-        .firebase_service
-        .verify_id_token(&payload.id_token)
-        .await;
-
-    let firebase_user = match firebase_result {
-        Ok(user) => user,
+    // Verify the provider's ID token
+    let identity = match provider.verify_id_token(&payload.id_token).await {
+        Ok(identity) => identity,
         Err(e) => {
-            tracing::error!("Firebase token verification failed: {}", e);
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(FirebaseAuthResponse {
-                    success: false,
-                    data: None,
-                    message: Some("Invalid Firebase token".to_string()),
-                }),
-            )
-                .into_response();
+            tracing::error!("{} token verification failed: {}", provider.name(), e);
+            return Err(AppError::Unauthorized("Invalid identity token".to_string()));
         }
     };
 
     let user_repository = SqlxUserRepository;
+    let identity_repository = SqlxUserIdentityRepository;
 
-    // Check if user exists by Firebase UID first, then fall back to email
+    // Check if user exists by provider subject first, then fall back to email
     let existing_user = user_repository
-        .get_user_by_provider_id(&app_state.db_pool, &firebase_user.uid, "firebase")
+        .get_user_by_provider_id(&app_state.db_pool, &identity.subject, provider.name())
         .await;
 
     let user = match existing_user {
         Ok(user_by_provider) => {
             tracing::info!(
-                "Existing user found by Firebase UID: {}",
+                "Existing user found by {} subject: {}",
+                provider.name(),
                 user_by_provider.email
             );
 
@@ -263,6 +579,36 @@ pub async fn firebase_auth_handler(
                 )
             };
 
+            if let Err(e) = identity_repository
+                .touch_identity(
+                    &app_state.db_pool,
+                    provider.name(),
+                    &identity.subject,
+                    identity.email.as_deref(),
+                    identity.email_verified,
+                )
+                .await
+            {
+                tracing::error!("Failed to record {} sign-in: {}", provider.name(), e);
+            }
+
+            // Record which of the user's identities was used most recently
+            if user_by_provider.provider_id.as_deref() != Some(identity.subject.as_str())
+                || user_by_provider.provider_name.as_deref() != Some(provider.name())
+            {
+                if let Err(e) = user_repository
+                    .update_oauth_info(
+                        &app_state.db_pool,
+                        user_by_provider.id,
+                        &identity.subject,
+                        provider.name(),
+                    )
+                    .await
+                {
+                    tracing::error!("Failed to update provider info: {}", e);
+                }
+            }
+
             User {
                 id: user_by_provider.id,
                 first_name,
@@ -270,51 +616,74 @@ pub async fn firebase_auth_handler(
                 email: user_by_provider.email,
                 phone: None,
                 country_id: user_by_provider.country_id,
-                provider_id: user_by_provider.provider_id,
-                provider_name: user_by_provider.provider_name,
-                avatar_url: firebase_user.picture.clone(),
+                provider_id: Some(identity.subject.clone()),
+                provider_name: Some(provider.name().to_string()),
+                avatar_url: identity.picture.clone(),
                 created_at: None,
                 updated_at: None,
             }
         }
         Err(_) => {
-            // Check if user exists by email
-            let existing_user_by_email = user_repository
-                .get_user_by_email(
-                    &app_state.db_pool,
-                    &firebase_user.email.clone().unwrap_or_default(),
-                )
-                .await;
+            // Only link to an existing user by email when the provider has verified
+            // the address; otherwise anyone could take over an account by signing up
+            // elsewhere with the same email
+            let existing_user_by_email = match identity.verified_email() {
+                Some(email) => user_repository
+                    .get_user_by_email(&app_state.db_pool, email)
+                    .await
+                    .ok(),
+                None => None,
+            };
 
             match existing_user_by_email {
-                Ok(user_by_email) => {
+                Some(user_by_email) => {
                     tracing::info!(
-                        "Existing user found by email, updating Firebase info: {}",
+                        "Existing user found by verified email, linking {} identity: {}",
+                        provider.name(),
                         user_by_email.email
                     );
 
-                    // Update Firebase provider info
+                    let new_identity = UserIdentityCreate {
+                        user_id: user_by_email.id,
+                        provider_name: provider.name().to_string(),
+                        provider_subject: identity.subject.clone(),
+                        email: identity.email.clone(),
+                        email_verified: identity.email_verified,
+                    };
+                    match identity_repository
+                        .link_identity(&app_state.db_pool, &new_identity)
+                        .await
+                    {
+                        Ok(
+                            LinkIdentityOutcome::Linked(_) | LinkIdentityOutcome::AlreadyLinked(_),
+                        ) => {}
+                        Ok(LinkIdentityOutcome::LinkedToOtherUser) => {
+                            return Err(AppError::Conflict(
+                                "Identity is linked to another user".to_string(),
+                            ));
+                        }
+                        Err(e) => {
+                            return Err(AppError::Internal(format!(
+                                "Failed to link identity: {}",
+                                e
+                            )));
+                        }
+                    }
+
+                    // Update provider info
                     if let Err(e) = user_repository
                         .update_oauth_info(
                             &app_state.db_pool,
                             user_by_email.id,
-                            &firebase_user.uid,
-                            "firebase",
+                            &identity.subject,
+                            provider.name(),
                         )
                         .await
                     {
-                        tracing::error!("Failed to update Firebase info: {}", e);
-                        return (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(FirebaseAuthResponse {
-                                success: false,
-                                data: None,
-                                message: Some(
-                                    "Failed to update user Firebase information".to_string(),
-                                ),
-                            }),
-                        )
-                            .into_response();
+                        return Err(AppError::Internal(format!(
+                            "Failed to update provider info: {}",
+                            e
+                        )));
                     }
 
                     User {
@@ -324,36 +693,44 @@ pub async fn firebase_auth_handler(
                         email: user_by_email.email,
                         phone: None,
                         country_id: user_by_email.country_id,
-                        provider_id: Some(firebase_user.uid.clone()),
-                        provider_name: Some("firebase".to_string()),
-                        avatar_url: firebase_user.picture.clone(),
+                        provider_id: Some(identity.subject.clone()),
+                        provider_name: Some(provider.name().to_string()),
+                        avatar_url: identity.picture.clone(),
                         created_at: None,
                         updated_at: None,
                     }
                 }
-                Err(_) => {
+                None => {
+                    let Some(account_status) = app_state
+                        .config
+                        .unverified_email_policy
+                        .sign_up_status(identity.verified_email().is_some())
+                    else {
+                        tracing::warn!(
+                            "Rejected {} sign-up without a verified email",
+                            provider.name()
+                        );
+                        return Err(email_not_verified());
+                    };
+
                     tracing::info!(
-                        "Creating new user from Firebase: {}",
-                        firebase_user
-                            .email
-                            .as_ref()
-                            .unwrap_or(&"unknown".to_string())
+                        "Creating new {} user from {}: {}",
+                        account_status.as_str(),
+                        provider.name(),
+                        identity.email.as_ref().unwrap_or(&"unknown".to_string())
                     );
 
                     // Get default country ID
-                    let country_id = match get_default_country_id(&app_state.db_pool).await {
-                        Ok(id) => id,
-                        Err(response) => return response.into_response(),
-                    };
+                    let country_id = get_default_country_id(&app_state.db_pool).await?;
 
-                    // Extract name parts - prefer display_name from request, fall back to Firebase token
-                    tracing::info!("Firebase user name field: {:?}", firebase_user.name);
+                    // Extract name parts - prefer display_name from request, fall back to the ID token
+                    tracing::info!("Identity name field: {:?}", identity.name);
                     tracing::info!("Request display_name field: {:?}", payload.display_name);
 
                     let display_name = payload
                         .display_name
                         .as_deref()
-                        .or(firebase_user.name.as_deref())
+                        .or(identity.name.as_deref())
                         .unwrap_or("Firebase User");
 
                     tracing::info!("Using display_name: {}", display_name);
@@ -374,15 +751,17 @@ pub async fn firebase_auth_handler(
 
                     // Create new user
                     let new_user = OAuthUserCreate {
-                        email: firebase_user
-                            .email
-                            .clone()
-                            .unwrap_or_else(|| format!("{}@firebase.local", firebase_user.uid)),
+                        email: identity.email.clone().unwrap_or_else(|| {
+                            format!("{}@{}.local", identity.subject, provider.name())
+                        }),
                         first_name,
                         last_name,
-                        provider_id: firebase_user.uid.clone(),
-                        provider_name: "firebase".to_string(),
-                        avatar_url: firebase_user.picture.clone(),
+                        provider_id: identity.subject.clone(),
+                        provider_name: provider.name().to_string(),
+                        provider_email: identity.email.clone(),
+                        email_verified: identity.email_verified,
+                        account_status,
+                        avatar_url: identity.picture.clone(),
                         country_id,
                     };
 
@@ -392,16 +771,10 @@ pub async fn firebase_auth_handler(
                     {
                         Ok(created_user) => created_user,
                         Err(e) => {
-                            tracing::error!("Failed to create user: {}", e);
-                            return (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(FirebaseAuthResponse {
-                                    success: false,
-                                    data: None,
-                                    message: Some("Failed to create user account".to_string()),
-                                }),
-                            )
-                                .into_response();
+                            return Err(AppError::Internal(format!(
+                                "Failed to create user: {}",
+                                e
+                            )));
                         }
                     }
                 }
@@ -409,6 +782,46 @@ pub async fn firebase_auth_handler(
         }
     };
 
+    let account_state = match user_repository
+        .get_account_state(&app_state.db_pool, user.id)
+        .await
+    {
+        Ok(account_state) => account_state,
+        Err(e) => {
+            return Err(AppError::Internal(format!(
+                "Failed to get account state: {}",
+                e
+            )));
+        }
+    };
+
+    // Only a sign-in verifying the account's own email lifts a restricted or
+    // pending account. A different verified address, e.g. of a linked
+    // identity, says nothing about who controls the account's email.
+    if account_state.status != AccountStatus::Active {
+        if !identity.verifies_email(&user.email) {
+            if account_state.status == AccountStatus::Pending {
+                return Err(email_not_verified());
+            }
+        } else {
+            match user_repository
+                .activate_user(&app_state.db_pool, user.id)
+                .await
+            {
+                Ok(_) => {
+                    tracing::info!("Activated user {} after email verification", user.id);
+                    app_state.permission_version_service.invalidate(user.id);
+                }
+                Err(e) => {
+                    return Err(AppError::Internal(format!(
+                        "Failed to activate user: {}",
+                        e
+                    )));
+                }
+            }
+        }
+    }
+
     // Get user roles and permissions
     let user_with_roles = match user_repository
         .get_user_with_roles(&app_state.db_pool, user.id)
@@ -416,61 +829,62 @@ pub async fn firebase_auth_handler(
     {
         Ok(user_with_roles) => user_with_roles,
         Err(e) => {
-            tracing::error!("Failed to get user roles: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(FirebaseAuthResponse {
-                    success: false,
-                    data: None,
-                    message: Some("Failed to get user permissions".to_string()),
-                }),
-            )
-                .into_response();
+            return Err(AppError::Internal(format!(
+                "Failed to get user roles: {}",
+                e
+            )));
         }
     };
 
     // Create AuthUser for JWT
-    let auth_user = AuthUser {
-        id: user.id,
-        email: user.email.clone(),
-        first_name: user.first_name.clone(),
-        last_name: user.last_name.clone(),
-        provider_id: user.provider_id.unwrap_or_default(),
-        provider_name: user.provider_name.unwrap_or_default(),
-        avatar_url: user.avatar_url.clone(),
-        roles: user_with_roles
-            .roles
-            .iter()
-            .map(|r| r.name.clone())
-            .collect(),
-        permissions: user_with_roles
-            .permissions
-            .iter()
-            .map(|p| p.name.clone())
-            .collect(),
-    };
+    let auth_user = build_auth_user(&user, &user_with_roles);
 
-    // Generate JWT token
-    let jwt_token = match app_state.jwt_service.generate_token(&auth_user) {
-        Ok(token) => token,
+    // Start a new session and its refresh token family for this login
+    let session_id = Uuid::new_v7(Timestamp::now(NoContext));
+    let refresh_token = match app_state
+        .refresh_token_service
+        .issue(&app_state.db_pool, user.id, session_id)
+        .await
+    {
+        Ok(refresh_token) => refresh_token,
         Err(e) => {
-            tracing::error!("Failed to generate JWT token: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(FirebaseAuthResponse {
-                    success: false,
-                    data: None,
-                    message: Some("Failed to generate authentication token".to_string()),
-                }),
-            )
-                .into_response();
+            return Err(AppError::Internal(format!(
+                "Failed to issue refresh token: {}",
+                e
+            )));
         }
     };
 
-    let response = FirebaseAuthResponse {
+    let session = UserSessionCreate {
+        id: session_id,
+        user_id: user.id,
+        user_agent: client.user_agent,
+        ip_address: client.ip_address,
+        expires_at: refresh_token.expires_at,
+    };
+    if let Err(e) = app_state
+        .session_service
+        .start(&app_state.db_pool, &session)
+        .await
+    {
+        return Err(AppError::Internal(format!(
+            "Failed to record session: {}",
+            e
+        )));
+    }
+
+    // Generate JWT token
+    let jwt_token = app_state
+        .jwt_service
+        .generate_session_token(&auth_user, session_id)
+        .map_err(|e| AppError::Internal(format!("Failed to generate JWT token: {}", e)))?;
+
+    let response = IdTokenAuthResponse {
         success: true,
         data: Some(AuthTokenData {
             token: jwt_token,
+            refresh_token: refresh_token.token,
+            expires_in: app_state.config.jwt.expiration_hours * 3600,
             user: UserInfo {
                 id: user.id.to_string(),
                 email: user.email,
@@ -481,28 +895,52 @@ pub async fn firebase_auth_handler(
         message: None,
     };
 
-    (StatusCode::OK, Json(response)).into_response()
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+fn email_not_verified() -> AppError {
+    AppError::Forbidden("Email address must be verified".to_string())
 }
 
-async fn get_default_country_id(
-    pool: &sqlx::PgPool,
-) -> Result<Uuid, (StatusCode, Json<serde_json::Value>)> {
+fn build_auth_user(user: &User, user_with_roles: &UserWithRoles) -> AuthUser {
+    AuthUser {
+        id: user.id,
+        email: user.email.clone(),
+        first_name: user.first_name.clone(),
+        last_name: user.last_name.clone(),
+        provider_id: user.provider_id.clone().unwrap_or_default(),
+        provider_name: user.provider_name.clone().unwrap_or_default(),
+        avatar_url: user.avatar_url.clone(),
+        roles: user_with_roles
+            .roles
+            .iter()
+            .map(|r| r.name.clone())
+            .collect(),
+        permissions: user_with_roles
+            .permissions
+            .iter()
+            .map(|p| p.name.clone())
+            .collect(),
+        permission_version: user_with_roles.permission_version,
+    }
+}
+
+/// Load a user together with their current roles and permissions
+pub async fn load_auth_user(pool: &sqlx::PgPool, user_id: Uuid) -> Result<AuthUser, sqlx::Error> {
+    let user_repository = SqlxUserRepository;
+    let user = user_repository.get_user_by_id(pool, user_id).await?;
+    let user_with_roles = user_repository.get_user_with_roles(pool, user_id).await?;
+    Ok(build_auth_user(&user, &user_with_roles))
+}
+
+pub(crate) async fn get_default_country_id(pool: &sqlx::PgPool) -> Result<Uuid, AppError> {
     // Get Norway as default country (or first available country)
     let country = sqlx::query!(
         "SELECT id FROM countries WHERE iso_alpha_2 = 'NO' OR iso_alpha_3 = 'NOR' LIMIT 1"
     )
     .fetch_optional(pool)
     .await
-    .map_err(|e| {
-        tracing::error!("Failed to get default country: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "success": false,
-                "message": "Failed to get default country"
-            })),
-        )
-    })?;
+    .map_err(|e| AppError::Internal(format!("Failed to get default country: {}", e)))?;
 
     if let Some(country) = country {
         Ok(country.id)
@@ -511,23 +949,10 @@ async fn get_default_country_id(
         let any_country = sqlx::query!("SELECT id FROM countries LIMIT 1")
             .fetch_optional(pool)
             .await
-            .map_err(|e| {
-                tracing::error!("Failed to get any country: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "success": false,
-                        "message": "No countries available"
-                    })),
-                )
-            })?;
-
-        any_country.map(|c| c.id).ok_or((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "success": false,
-                "message": "No countries found in database"
-            })),
-        ))
+            .map_err(|e| AppError::Internal(format!("Failed to get any country: {}", e)))?;
+
+        any_country
+            .map(|c| c.id)
+            .ok_or_else(|| AppError::Internal("No countries found in database".to_string()))
     }
 }
@@ -1,82 +1,144 @@
+use crate::application::error::AppError;
+use crate::application::http_response::Confirmation;
+use crate::application::middleware::rbac_middleware::user_has_permission;
 use crate::application::state::AppState;
+use crate::domain::models::auth::AuthContext;
 use crate::domain::models::boat::Boat;
+use crate::domain::models::boat_owner::RemoveOwnerOutcome;
+use crate::domain::models::rbac::PERMISSION_BOATS_WRITE;
 use crate::domain::models::user::UserWithCountry;
 use crate::domain::repositories::boat_owner_repository::BoatOwnerRepository;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
-use serde_json;
 use uuid::Uuid;
 
+#[utoipa::path(
+    post,
+    path = "/boats/{boat_id}/owners/{user_id}",
+    tag = "boat-owners",
+    description = "Owners of the boat and holders of `boats:write` may add owners.",
+    responses(
+        (status = 200, description = "Owner added", body = Confirmation),
+        (status = 403, description = "Not an owner of the boat, or impersonating"),
+        (status = 404, description = "Boat or user not found"),
+    ),
+)]
 pub async fn add_owner_to_boat(
     State(state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
     Path((boat_id, user_id)): Path<(Uuid, Uuid)>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, AppError> {
     let repo = BoatOwnerRepository::new(&state.db_pool);
-    match repo.add_owner_to_boat(boat_id, user_id).await {
-        Ok(()) => {
-            let response = serde_json::json!({
-                "success": true,
-                "message": "Owner added successfully"
-            });
-            (StatusCode::OK, Json(response))
-        }
-        Err(e) => {
-            let response = serde_json::json!({
-                "success": false,
-                "message": e.to_string()
-            });
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
-        }
+    authorize_owner_change(&repo, &auth_context, boat_id).await?;
+
+    if !repo.user_exists(user_id).await? {
+        return Err(AppError::NotFound("User not found".to_string()));
     }
+
+    repo.add_owner_to_boat(boat_id, user_id).await?;
+    let response = Confirmation {
+        success: true,
+        message: "Owner added successfully".to_string(),
+    };
+    Ok((StatusCode::OK, Json(response)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/boats/{boat_id}/owners/{user_id}",
+    tag = "boat-owners",
+    description = "Owners of the boat and holders of `boats:write` may remove owners. Not available while impersonating.",
+    responses(
+        (status = 200, description = "Owner removed", body = Confirmation),
+        (status = 403, description = "Not an owner of the boat, or impersonating"),
+        (status = 404, description = "Boat not found, or the user is not an owner"),
+        (status = 409, description = "The user is the last owner of the boat"),
+    ),
+)]
 pub async fn remove_owner_from_boat(
     State(state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
     Path((boat_id, user_id)): Path<(Uuid, Uuid)>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, AppError> {
     let repo = BoatOwnerRepository::new(&state.db_pool);
+    authorize_owner_change(&repo, &auth_context, boat_id).await?;
+
     match repo.remove_owner_from_boat(boat_id, user_id).await {
-        Ok(()) => {
-            let response = serde_json::json!({
-                "success": true,
-                "message": "Owner removed successfully"
-            });
-            (StatusCode::OK, Json(response))
-        }
-        Err(e) => {
-            let response = serde_json::json!({
-                "success": false,
-                "message": e.to_string()
-            });
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        Ok(RemoveOwnerOutcome::Removed) => {
+            let response = Confirmation {
+                success: true,
+                message: "Owner removed successfully".to_string(),
+            };
+            Ok((StatusCode::OK, Json(response)))
         }
+        Ok(RemoveOwnerOutcome::NotAnOwner) => Err(AppError::NotFound(
+            "User is not an owner of this boat".to_string(),
+        )),
+        Ok(RemoveOwnerOutcome::LastOwner) => Err(AppError::Conflict(
+            "Cannot remove the last owner of a boat".to_string(),
+        )),
+        // The boat was deleted after the authorization check
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Boat not found".to_string())),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Owners of a boat and holders of `boats:write` may change its owners.
+/// Fails with 404 for an unknown boat and 403 otherwise when not allowed.
+async fn authorize_owner_change(
+    repo: &BoatOwnerRepository<'_>,
+    auth_context: &AuthContext,
+    boat_id: Uuid,
+) -> Result<(), AppError> {
+    if !repo.boat_exists(boat_id).await? {
+        return Err(AppError::NotFound("Boat not found".to_string()));
+    }
+
+    if user_has_permission(&auth_context.user, PERMISSION_BOATS_WRITE)
+        || repo.is_owner(boat_id, auth_context.user.id).await?
+    {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only owners of the boat can change its owners".to_string(),
+        ))
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}/boats",
+    tag = "boat-owners",
+    responses(
+        (status = 200, description = "Boats owned by the user", body = Vec<Boat>),
+    ),
+)]
 pub async fn get_boats_for_user(
     State(state): State<AppState>,
     Path(user_id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Result<Json<Vec<Boat>>, AppError> {
     let repo = BoatOwnerRepository::new(&state.db_pool);
-    let boats = repo
-        .get_boats_with_details_for_user(user_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    Ok::<Json<Vec<Boat>>, (StatusCode, String)>(Json(boats))
+    let boats = repo.get_boats_with_details_for_user(user_id).await?;
+    Ok(Json(boats))
 }
 
+#[utoipa::path(
+    get,
+    path = "/boats/{boat_id}/owners",
+    tag = "boat-owners",
+    responses(
+        (status = 200, description = "Owners of the boat", body = Vec<UserWithCountry>),
+    ),
+)]
 pub async fn get_owners_for_boat(
     State(state): State<AppState>,
     Path(boat_id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Result<Json<Vec<UserWithCountry>>, AppError> {
     let repo = BoatOwnerRepository::new(&state.db_pool);
-    let owners = repo
-        .get_owners_with_details_for_boat(boat_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    Ok::<Json<Vec<UserWithCountry>>, (StatusCode, String)>(Json(owners))
+    let owners = repo.get_owners_with_details_for_boat(boat_id).await?;
+    Ok(Json(owners))
 }
@@ -0,0 +1,12 @@
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "meta",
+    responses(
+        (status = 200, description = "The backend is up", body = String, content_type = "text/plain"),
+    ),
+    security(()),
+)]
+pub async fn health_handler() -> &'static str {
+    "Backend is running!"
+}
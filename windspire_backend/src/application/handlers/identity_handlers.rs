@@ -0,0 +1,175 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::application::error::AppError;
+use crate::application::http_response::{json_response, ok_json_response, ApiResponse, Message};
+use crate::application::middleware::validated_json::ValidatedJson;
+use crate::application::state::AppState;
+use crate::domain::models::auth::AuthContext;
+use crate::domain::models::user::AccountStatus;
+use crate::domain::models::user_identity::{
+    LinkIdentity, LinkIdentityOutcome, UnlinkIdentityOutcome, UserIdentity, UserIdentityCreate,
+};
+use crate::domain::repositories::user_identity_repository::UserIdentityRepository;
+use crate::domain::repositories::user_repository::UserRepository;
+use crate::infrastructure::repositories::sqlx_user_identity_repository::SqlxUserIdentityRepository;
+use crate::infrastructure::repositories::sqlx_user_repository::SqlxUserRepository;
+
+/// List the login identities linked to the current user
+#[utoipa::path(
+    get,
+    path = "/auth/identities",
+    tag = "identities",
+    responses(
+        (status = 200, description = "Identities linked to the user", body = ApiResponse<Vec<UserIdentity>>),
+        (status = 403, description = "Called with an API key"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn list_identities_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+) -> Result<Response, AppError> {
+    reject_api_key(&auth_context)?;
+
+    let identities = SqlxUserIdentityRepository
+        .get_identities_for_user(&app_state.db_pool, auth_context.user.id)
+        .await?;
+    Ok(ok_json_response(identities))
+}
+
+/// Link another provider's identity to the current user. The caller proves
+/// control of the identity with an ID token from that provider.
+#[utoipa::path(
+    post,
+    path = "/auth/identities",
+    tag = "identities",
+    request_body = LinkIdentity,
+    responses(
+        (status = 201, description = "The identity is linked", body = ApiResponse<UserIdentity>),
+        (status = 200, description = "The identity was already linked to the user", body = ApiResponse<UserIdentity>),
+        (status = 401, description = "Invalid ID token"),
+        (status = 403, description = "Called with an API key or an impersonation token, or before the account's email is verified"),
+        (status = 404, description = "Unknown identity provider"),
+        (status = 409, description = "The identity is linked to another user"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn link_identity_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    ValidatedJson(request): ValidatedJson<LinkIdentity>,
+) -> Result<Response, AppError> {
+    reject_api_key(&auth_context)?;
+
+    // A restricted account has not proven its email yet; an identity linked
+    // now could later activate it or let it claim the address
+    let account_state = SqlxUserRepository
+        .get_account_state(&app_state.db_pool, auth_context.user.id)
+        .await?;
+    if account_state.status != AccountStatus::Active {
+        return Err(AppError::Forbidden(
+            "Email address must be verified before linking identities".to_string(),
+        ));
+    }
+
+    let Some(provider) = app_state.identity_providers.get(&request.provider) else {
+        return Err(AppError::NotFound("Unknown identity provider".to_string()));
+    };
+
+    let identity = match provider.verify_id_token(&request.id_token).await {
+        Ok(identity) => identity,
+        Err(e) => {
+            tracing::warn!("{} token verification failed: {}", provider.name(), e);
+            return Err(AppError::Unauthorized("Invalid identity token".to_string()));
+        }
+    };
+
+    let new_identity = UserIdentityCreate {
+        user_id: auth_context.user.id,
+        provider_name: provider.name().to_string(),
+        provider_subject: identity.subject,
+        email: identity.email,
+        email_verified: identity.email_verified,
+    };
+
+    match SqlxUserIdentityRepository
+        .link_identity(&app_state.db_pool, &new_identity)
+        .await?
+    {
+        LinkIdentityOutcome::Linked(linked) => {
+            tracing::info!(
+                "User {} linked {} identity {}",
+                auth_context.user.email,
+                linked.provider_name,
+                linked.id
+            );
+            Ok(json_response(
+                StatusCode::CREATED,
+                json!({ "success": true, "data": linked }),
+            ))
+        }
+        LinkIdentityOutcome::AlreadyLinked(existing) => Ok(ok_json_response(existing)),
+        LinkIdentityOutcome::LinkedToOtherUser => Err(AppError::Conflict(
+            "Identity is linked to another user".to_string(),
+        )),
+    }
+}
+
+/// Unlink one of the current user's identities, as long as another remains
+#[utoipa::path(
+    delete,
+    path = "/auth/identities/{identity_id}",
+    tag = "identities",
+    responses(
+        (status = 200, description = "The identity is unlinked", body = ApiResponse<Message>),
+        (status = 403, description = "Called with an API key or an impersonation token"),
+        (status = 404, description = "Identity not found"),
+        (status = 409, description = "The last identity cannot be unlinked"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn unlink_identity_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(identity_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    reject_api_key(&auth_context)?;
+
+    match SqlxUserIdentityRepository
+        .unlink_identity(&app_state.db_pool, auth_context.user.id, identity_id)
+        .await?
+    {
+        UnlinkIdentityOutcome::Unlinked => {
+            tracing::info!(
+                "User {} unlinked identity {}",
+                auth_context.user.email,
+                identity_id
+            );
+            Ok(ok_json_response(json!({ "message": "Identity unlinked" })))
+        }
+        UnlinkIdentityOutcome::NotFound => {
+            Err(AppError::NotFound("Identity not found".to_string()))
+        }
+        UnlinkIdentityOutcome::LastIdentity => Err(AppError::Conflict(
+            "Cannot unlink the last identity".to_string(),
+        )),
+    }
+}
+
+/// Identities decide who can sign in as the user, so they are managed from an
+/// interactive session only
+fn reject_api_key(auth_context: &AuthContext) -> Result<(), AppError> {
+    match auth_context.api_key_id {
+        Some(_) => Err(AppError::Forbidden(
+            "API keys cannot manage identities".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
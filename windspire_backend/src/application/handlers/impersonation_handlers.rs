@@ -0,0 +1,143 @@
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    http::StatusCode,
+    response::Response,
+    Extension,
+};
+use uuid::Uuid;
+
+use crate::application::error::AppError;
+use crate::application::handlers::auth_handlers::load_auth_user;
+use crate::application::http_response::{json_response, ok_json_response, ApiResponse};
+use crate::application::middleware::validated_json::ValidatedJson;
+use crate::application::state::AppState;
+use crate::domain::models::auth::{Actor, AuthContext};
+use crate::domain::models::impersonation::{
+    ImpersonateUser, ImpersonatedUser, ImpersonationAuditCreate, ImpersonationAuditEntry,
+    ImpersonationAuditFilter, ImpersonationEvent, ImpersonationToken,
+};
+use crate::domain::models::rbac::ROLE_ADMIN;
+use crate::domain::repositories::impersonation_audit_repository::ImpersonationAuditRepository;
+use crate::infrastructure::repositories::sqlx_impersonation_audit_repository::SqlxImpersonationAuditRepository;
+
+/// Entries returned by `GET /admin/impersonations`
+const AUDIT_LIST_LIMIT: i64 = 500;
+
+/// Issue a short-lived token to act as `user_id`. There is no refresh token;
+/// when it expires, a new one has to be requested (and audited).
+#[utoipa::path(
+    post,
+    path = "/admin/users/{user_id}/impersonate",
+    tag = "impersonation",
+    request_body = ImpersonateUser,
+    responses(
+        (status = 201, description = "A short-lived token acting as the user; the issue is audited", body = ApiResponse<ImpersonationToken>),
+        (status = 400, description = "Impersonating yourself"),
+        (status = 403, description = "Called with an API key or an impersonation token, or the user is an admin"),
+        (status = 404, description = "User not found"),
+    ),
+    security(("bearer_auth" = ["users:impersonate"])),
+)]
+pub async fn impersonate_user_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(user_id): Path<Uuid>,
+    OriginalUri(uri): OriginalUri,
+    ValidatedJson(request): ValidatedJson<ImpersonateUser>,
+) -> Result<Response, AppError> {
+    if auth_context.api_key_id.is_some() {
+        return Err(AppError::Forbidden(
+            "API keys cannot impersonate users".to_string(),
+        ));
+    }
+    if user_id == auth_context.user.id {
+        return Err(AppError::BadRequest(
+            "Cannot impersonate yourself".to_string(),
+        ));
+    }
+
+    let target = match load_auth_user(&app_state.db_pool, user_id).await {
+        Ok(target) => target,
+        Err(sqlx::Error::RowNotFound) => {
+            return Err(AppError::NotFound("User not found".to_string()))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // Acting as an admin would hand out every permission there is
+    if target.roles.iter().any(|role| role == ROLE_ADMIN) {
+        return Err(AppError::Forbidden(
+            "Admins cannot be impersonated".to_string(),
+        ));
+    }
+
+    let actor = Actor {
+        sub: auth_context.user.id.to_string(),
+        email: auth_context.user.email.clone(),
+    };
+    let (token, claims) = app_state
+        .jwt_service
+        .generate_impersonation_token(&target, actor)
+        .map_err(|e| {
+            AppError::Internal(format!("Failed to generate impersonation token: {}", e))
+        })?;
+
+    // No token leaves without its audit entry
+    let entry = ImpersonationAuditCreate {
+        event: ImpersonationEvent::TokenIssued,
+        actor_id: auth_context.user.id,
+        actor_email: auth_context.user.email.clone(),
+        target_user_id: target.id,
+        token_id: claims.jti,
+        method: "POST".to_string(),
+        path: uri.path().to_string(),
+        status_code: Some(StatusCode::CREATED.as_u16().into()),
+        reason: Some(request.reason),
+    };
+    SqlxImpersonationAuditRepository
+        .record(&app_state.db_pool, &entry)
+        .await?;
+
+    tracing::warn!(
+        "User {} is impersonating user {}",
+        auth_context.user.email,
+        target.id
+    );
+
+    Ok(json_response(
+        StatusCode::CREATED,
+        ApiResponse {
+            success: true,
+            data: ImpersonationToken {
+                token,
+                expires_in: claims.exp - claims.iat,
+                user: ImpersonatedUser {
+                    id: target.id,
+                    email: target.email,
+                    roles: target.roles,
+                    permissions: target.permissions,
+                },
+            },
+        },
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/impersonations",
+    tag = "impersonation",
+    params(ImpersonationAuditFilter),
+    responses(
+        (status = 200, description = "The latest audit entries, newest first", body = ApiResponse<Vec<ImpersonationAuditEntry>>),
+    ),
+    security(("bearer_auth" = ["users:impersonate"]), ("api_key" = ["users:impersonate"])),
+)]
+pub async fn list_impersonation_audit_handler(
+    State(app_state): State<AppState>,
+    Query(filter): Query<ImpersonationAuditFilter>,
+) -> Result<Response, AppError> {
+    let entries = SqlxImpersonationAuditRepository
+        .list(&app_state.db_pool, &filter, AUDIT_LIST_LIMIT)
+        .await?;
+    Ok(ok_json_response(entries))
+}
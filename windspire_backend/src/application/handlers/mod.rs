@@ -1,2 +1,8 @@
+pub mod admin_handlers;
+pub mod api_key_handlers;
 pub mod auth_handlers;
 pub mod boat_owner_handlers;
+pub mod health_handlers;
+pub mod identity_handlers;
+pub mod impersonation_handlers;
+pub mod session_handlers;
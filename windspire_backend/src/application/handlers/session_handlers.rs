@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, State},
+    response::Response,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::application::error::AppError;
+use crate::application::http_response::{ok_json_response, ApiResponse, Message};
+use crate::application::state::AppState;
+use crate::domain::models::auth::{AuthContext, Claims};
+use crate::domain::models::user_session::SessionInfo;
+
+/// List the current user's active sessions, marking the one making the request
+#[utoipa::path(
+    get,
+    path = "/auth/sessions",
+    tag = "sessions",
+    responses(
+        (status = 200, description = "Active sessions of the user", body = ApiResponse<Vec<SessionInfo>>),
+        (status = 403, description = "Called with an API key"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn list_sessions_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    claims: Option<Extension<Claims>>,
+) -> Result<Response, AppError> {
+    reject_api_key(&auth_context)?;
+
+    let current_session = claims.and_then(|Extension(claims)| claims.sid);
+
+    let sessions = app_state
+        .session_service
+        .list_active(&app_state.db_pool, auth_context.user.id)
+        .await?;
+
+    Ok(ok_json_response(
+        sessions
+            .into_iter()
+            .map(|session| SessionInfo {
+                current: current_session.as_deref() == Some(session.id.to_string().as_str()),
+                session,
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// End one of the current user's sessions, e.g. on a lost device
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions/{session_id}",
+    tag = "sessions",
+    responses(
+        (status = 200, description = "The session is ended", body = ApiResponse<Message>),
+        (status = 403, description = "Called with an API key or an impersonation token"),
+        (status = 404, description = "Session not found"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn revoke_session_handler(
+    State(app_state): State<AppState>,
+    Extension(auth_context): Extension<AuthContext>,
+    Path(session_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    reject_api_key(&auth_context)?;
+
+    if !end_session(&app_state, auth_context.user.id, session_id).await? {
+        return Err(AppError::NotFound("Session not found".to_string()));
+    }
+
+    tracing::info!(
+        "User {} revoked session {}",
+        auth_context.user.email,
+        session_id
+    );
+    Ok(ok_json_response(json!({ "message": "Session revoked" })))
+}
+
+/// Revoke the session's access tokens and refresh tokens. `false` if the user
+/// has no such active session.
+pub(crate) async fn end_session(
+    app_state: &AppState,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let revoked = app_state
+        .token_revocation_service
+        .revoke_session(&app_state.db_pool, user_id, session_id)
+        .await?;
+    if revoked {
+        app_state
+            .refresh_token_service
+            .revoke_session(&app_state.db_pool, session_id)
+            .await?;
+    }
+
+    Ok(revoked)
+}
+
+/// API keys are not sessions and do not get to see or end them
+fn reject_api_key(auth_context: &AuthContext) -> Result<(), AppError> {
+    match auth_context.api_key_id {
+        Some(_) => Err(AppError::Forbidden(
+            "API keys cannot manage sessions".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
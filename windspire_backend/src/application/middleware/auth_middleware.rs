@@ -1,72 +1,156 @@
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    extract::{OriginalUri, Request, State},
+    http::HeaderMap,
     middleware::Next,
     response::Response,
 };
 
+use crate::application::error::AppError;
+use crate::application::services::api_key_service::{ApiKeyError, ApiKeyService};
 use crate::application::services::jwt_service::{JwtError, JwtService};
-use crate::domain::models::auth::{AuthContext, Claims};
-
-#[derive(Debug)]
-pub enum AuthError {
-    MissingToken,
-    InvalidToken(JwtError),
-    InternalError,
-}
-
-impl AuthError {
-    pub fn status_code(&self) -> StatusCode {
-        match self {
-            AuthError::MissingToken => StatusCode::UNAUTHORIZED,
-            AuthError::InvalidToken(_) => StatusCode::UNAUTHORIZED,
-            AuthError::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
-        }
-    }
-
-    pub fn message(&self) -> &'static str {
-        match self {
-            AuthError::MissingToken => "Missing authorization token",
-            AuthError::InvalidToken(_) => "Invalid or expired token",
-            AuthError::InternalError => "Internal server error",
-        }
-    }
-}
+use crate::domain::models::auth::{Actor, AuthContext, Claims};
+use crate::domain::models::impersonation::{ImpersonationAuditCreate, ImpersonationEvent};
+use crate::domain::repositories::impersonation_audit_repository::ImpersonationAuditRepository;
+use crate::infrastructure::repositories::sqlx_impersonation_audit_repository::SqlxImpersonationAuditRepository;
 
 pub async fn jwt_auth_middleware(
     State(app_state): State<crate::application::state::AppState>,
     headers: HeaderMap,
     mut request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, &'static str)> {
+) -> Result<Response, AppError> {
     // Extract Authorization header
     let auth_header = headers
         .get("Authorization")
         .and_then(|header| header.to_str().ok())
-        .ok_or((StatusCode::UNAUTHORIZED, "Missing authorization header"))?;
+        .ok_or_else(|| AppError::Unauthorized("Missing authorization header".to_string()))?;
 
     // Extract Bearer token
     let token = JwtService::extract_bearer_token(auth_header)
-        .ok_or((StatusCode::UNAUTHORIZED, "Invalid authorization format"))?;
+        .ok_or_else(|| AppError::Unauthorized("Invalid authorization format".to_string()))?;
+
+    // API keys are sent as Bearer tokens too and are told apart by their prefix
+    if ApiKeyService::is_api_key(token) {
+        let identity = app_state
+            .api_key_service
+            .authenticate(&app_state.db_pool, token)
+            .await
+            .map_err(api_key_rejection)?;
+
+        request.extensions_mut().insert(AuthContext {
+            user: identity.user,
+            token: token.to_string(),
+            api_key_id: Some(identity.api_key_id),
+            impersonator: None,
+        });
+
+        return Ok(next.run(request).await);
+    }
 
     // Validate token
     let claims = app_state
         .jwt_service
         .validate_token(token)
         .map_err(|e| match e {
-            JwtError::ExpiredToken => (StatusCode::UNAUTHORIZED, "Token has expired"),
-            _ => (StatusCode::UNAUTHORIZED, "Invalid token"),
+            JwtError::ExpiredToken => AppError::TokenExpired,
+            _ => AppError::Unauthorized("Invalid token".to_string()),
         })?;
 
+    // Reject tokens that were logged out before they expired
+    if app_state.token_revocation_service.is_revoked(&claims) {
+        return Err(AppError::Unauthorized("Token has been revoked".to_string()));
+    }
+
+    // Keep the session's last seen time current
+    if let Some(session_id) = claims.sid.as_deref().and_then(|sid| sid.parse().ok()) {
+        if let Err(e) = app_state
+            .session_service
+            .touch(&app_state.db_pool, session_id)
+            .await
+        {
+            tracing::error!("Failed to record session activity: {}", e);
+        }
+    }
+
+    // Use the current roles and permissions if they changed since the token was issued
+    let user = current_auth_user(&app_state, &claims)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => AppError::Unauthorized("User no longer exists".to_string()),
+            e => AppError::Internal(format!("Failed to resolve permissions: {}", e)),
+        })?;
+
+    // Every request made with an impersonation token is audited, or refused
+    let audit_id = match &claims.act {
+        Some(actor) => {
+            // Nested routers only see the rest of the path
+            let path = request
+                .extensions()
+                .get::<OriginalUri>()
+                .map_or(request.uri().path(), |uri| uri.path())
+                .to_string();
+            let method = request.method().to_string();
+            Some(record_impersonated_request(&app_state, &claims, actor, method, path).await?)
+        }
+        None => None,
+    };
+
     // Create auth context and add to request extensions
     let auth_context = AuthContext {
-        user: claims_to_auth_user(&claims),
+        user,
         token: token.to_string(),
+        api_key_id: None,
+        impersonator: claims.act.clone(),
     };
 
     request.extensions_mut().insert(auth_context);
+    request.extensions_mut().insert(claims);
+
+    let response = next.run(request).await;
+
+    if let Some(audit_id) = audit_id {
+        if let Err(e) = SqlxImpersonationAuditRepository
+            .set_status_code(
+                &app_state.db_pool,
+                audit_id,
+                response.status().as_u16().into(),
+            )
+            .await
+        {
+            tracing::error!("Failed to record impersonated response: {}", e);
+        }
+    }
 
-    Ok(next.run(request).await)
+    Ok(response)
+}
+
+async fn record_impersonated_request(
+    app_state: &crate::application::state::AppState,
+    claims: &Claims,
+    actor: &Actor,
+    method: String,
+    path: String,
+) -> Result<uuid::Uuid, AppError> {
+    let (Ok(actor_id), Ok(target_user_id)) = (actor.sub.parse(), claims.sub.parse()) else {
+        return Err(AppError::Unauthorized("Invalid token".to_string()));
+    };
+
+    let entry = ImpersonationAuditCreate {
+        event: ImpersonationEvent::Request,
+        actor_id,
+        actor_email: actor.email.clone(),
+        target_user_id,
+        token_id: claims.jti.clone(),
+        method,
+        path,
+        status_code: None,
+        reason: None,
+    };
+
+    SqlxImpersonationAuditRepository
+        .record(&app_state.db_pool, &entry)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to record impersonated request: {}", e)))
 }
 
 // Optional middleware that doesn't fail if no token is provided
@@ -79,12 +163,33 @@ pub async fn optional_jwt_auth_middleware(
     // Refactored to work with Rust 2021 (no let chains)
     if let Some(auth_header) = headers.get("Authorization").and_then(|h| h.to_str().ok()) {
         if let Some(token) = JwtService::extract_bearer_token(auth_header) {
-            if let Ok(claims) = app_state.jwt_service.validate_token(token) {
-                let auth_context = AuthContext {
-                    user: claims_to_auth_user(&claims),
-                    token: token.to_string(),
-                };
-                request.extensions_mut().insert(auth_context);
+            if ApiKeyService::is_api_key(token) {
+                if let Ok(identity) = app_state
+                    .api_key_service
+                    .authenticate(&app_state.db_pool, token)
+                    .await
+                {
+                    request.extensions_mut().insert(AuthContext {
+                        user: identity.user,
+                        token: token.to_string(),
+                        api_key_id: Some(identity.api_key_id),
+                        impersonator: None,
+                    });
+                }
+            } else if let Ok(claims) = app_state.jwt_service.validate_token(token) {
+                // Impersonated requests are only accepted where they are audited
+                if claims.act.is_none() && !app_state.token_revocation_service.is_revoked(&claims) {
+                    if let Ok(user) = current_auth_user(&app_state, &claims).await {
+                        let auth_context = AuthContext {
+                            user,
+                            token: token.to_string(),
+                            api_key_id: None,
+                            impersonator: None,
+                        };
+                        request.extensions_mut().insert(auth_context);
+                        request.extensions_mut().insert(claims);
+                    }
+                }
             }
         }
     }
@@ -92,6 +197,37 @@ pub async fn optional_jwt_auth_middleware(
     next.run(request).await
 }
 
+fn api_key_rejection(error: ApiKeyError) -> AppError {
+    match error {
+        ApiKeyError::ExpiredKey => AppError::Unauthorized("API key has expired".to_string()),
+        ApiKeyError::RevokedKey => AppError::Unauthorized("API key has been revoked".to_string()),
+        ApiKeyError::Database(e) => {
+            AppError::Internal(format!("Failed to authenticate API key: {}", e))
+        }
+        _ => AppError::Unauthorized("Invalid API key".to_string()),
+    }
+}
+
+/// The token's user, with roles and permissions re-resolved when the user's
+/// permission version moved past the one the token was issued at
+async fn current_auth_user(
+    app_state: &crate::application::state::AppState,
+    claims: &Claims,
+) -> Result<crate::domain::models::auth::AuthUser, sqlx::Error> {
+    let mut user = claims_to_auth_user(claims);
+
+    if let Some(resolved) = app_state
+        .permission_version_service
+        .resolve_stale(&app_state.db_pool, claims)
+        .await?
+    {
+        user.roles = resolved.roles;
+        user.permissions = resolved.permissions;
+    }
+
+    Ok(user)
+}
+
 fn claims_to_auth_user(claims: &Claims) -> crate::domain::models::auth::AuthUser {
     use uuid::Uuid;
 
@@ -115,6 +251,7 @@ fn claims_to_auth_user(claims: &Claims) -> crate::domain::models::auth::AuthUser
         avatar_url: claims.picture.clone(),
         roles: claims.roles.clone(),
         permissions: claims.permissions.clone(),
+        permission_version: claims.pv,
     }
 }
 
@@ -137,9 +274,17 @@ mod tests {
         let config = JwtConfig {
             secret: "test-secret".to_string(),
             expiration_hours: 1,
+            impersonation_expiration_minutes: 15,
             issuer: "test".to_string(),
+            audience: "test-api".to_string(),
+            refresh_token_expiration_days: 30,
+            algorithm: "HS256".to_string(),
+            signing_key_id: "default".to_string(),
+            signing_key_file: None,
+            retired_keys: vec![],
+            retired_key_grace_hours: 1,
         };
-        Arc::new(JwtService::new(config))
+        Arc::new(JwtService::new(config).unwrap())
     }
 
     fn create_test_user() -> AuthUser {
@@ -153,6 +298,7 @@ mod tests {
             avatar_url: Some("https://example.com/avatar.jpg".to_string()),
             roles: vec!["user".to_string()],
             permissions: vec!["users:read_own".to_string()],
+            permission_version: 0,
         }
     }
 
@@ -172,6 +318,8 @@ mod tests {
         let auth_context = AuthContext {
             user: claims_to_auth_user(&claims),
             token: token.clone(),
+            api_key_id: None,
+            impersonator: None,
         };
         request.extensions_mut().insert(auth_context);
 
@@ -189,7 +337,14 @@ mod tests {
             picture: Some("https://example.com/avatar.jpg".to_string()),
             roles: vec!["user".to_string()],
             permissions: vec!["users:read_own".to_string()],
+            pv: 0,
+            act: None,
+            sid: None,
+            iss: "test".to_string(),
+            aud: "test-api".to_string(),
+            jti: Uuid::new_v4().to_string(),
             iat: 1234567890,
+            nbf: 1234567890,
             exp: 1234567890 + 3600,
         };
 
@@ -0,0 +1,124 @@
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, Extensions, HeaderMap},
+};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use crate::application::state::AppState;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Longest user agent kept; anything beyond is cut off
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Where a request comes from, as recorded for sessions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(MAX_USER_AGENT_LENGTH).collect());
+
+        Ok(Self {
+            ip_address: client_ip(
+                &parts.headers,
+                &parts.extensions,
+                state.config.rate_limit.trust_forwarded_for,
+            ),
+            user_agent,
+        })
+    }
+}
+
+/// The client's IP address. `X-Forwarded-For` is only believed behind a proxy
+/// that sets it, as clients can send anything there. The proxy appends the
+/// address it saw, so only the last entry is taken; whatever the client sent
+/// comes before it.
+pub fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trust_forwarded_for: bool,
+) -> Option<String> {
+    let forwarded_for = trust_forwarded_for
+        .then(|| headers.get_all(X_FORWARDED_FOR).iter().next_back())
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty());
+    if let Some(ip) = forwarded_for {
+        return Some(ip.to_string());
+    }
+
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_from(address: &str, forwarded_for: Option<&str>) -> (HeaderMap, Extensions) {
+        let mut headers = HeaderMap::new();
+        if let Some(forwarded_for) = forwarded_for {
+            headers.insert(X_FORWARDED_FOR, forwarded_for.parse().unwrap());
+        }
+        let mut extensions = Extensions::new();
+        extensions.insert(ConnectInfo(address.parse::<SocketAddr>().unwrap()));
+        (headers, extensions)
+    }
+
+    #[test]
+    fn test_client_ip_uses_peer_address() {
+        let (headers, extensions) = request_from("203.0.113.7:51000", Some("198.51.100.1"));
+
+        assert_eq!(
+            client_ip(&headers, &extensions, false).as_deref(),
+            Some("203.0.113.7")
+        );
+        assert_eq!(client_ip(&headers, &Extensions::new(), false), None);
+    }
+
+    #[test]
+    fn test_client_ip_trusts_forwarded_for_when_configured() {
+        let (headers, extensions) = request_from("10.0.0.2:51000", Some("198.51.100.1"));
+        assert_eq!(
+            client_ip(&headers, &extensions, true).as_deref(),
+            Some("198.51.100.1")
+        );
+
+        // Entries before the one the proxy appended come from the client
+        let (headers, extensions) = request_from("10.0.0.2:51000", Some("spoofed, 198.51.100.1"));
+        assert_eq!(
+            client_ip(&headers, &extensions, true).as_deref(),
+            Some("198.51.100.1")
+        );
+
+        let (mut headers, extensions) = request_from("10.0.0.2:51000", Some("203.0.113.9"));
+        headers.append(X_FORWARDED_FOR, "198.51.100.1".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, &extensions, true).as_deref(),
+            Some("198.51.100.1")
+        );
+
+        let (headers, extensions) = request_from("10.0.0.2:51000", None);
+        assert_eq!(
+            client_ip(&headers, &extensions, true).as_deref(),
+            Some("10.0.0.2")
+        );
+    }
+}
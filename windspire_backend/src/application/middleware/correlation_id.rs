@@ -0,0 +1,81 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub const X_CORRELATION_ID: HeaderName = HeaderName::from_static("x-correlation-id");
+
+/// Longest correlation id accepted from a client; longer ones are replaced
+const MAX_CORRELATION_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
+
+/// Tags every request with a correlation id: the client's `X-Correlation-Id`
+/// when it sent a sensible one, a new UUID otherwise. The id is echoed in the
+/// response, attached to every log line of the request and put in error
+/// bodies, so a client report can be matched with the server logs.
+pub async fn correlation_id_middleware(request: Request, next: Next) -> Response {
+    let correlation_id = request
+        .headers()
+        .get(X_CORRELATION_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_correlation_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    // At error level, so the id is on every line that makes it into the logs
+    let span = tracing::error_span!("request", correlation_id = %correlation_id);
+    let mut response = CORRELATION_ID
+        .scope(correlation_id.clone(), next.run(request))
+        .instrument(span)
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&correlation_id) {
+        response.headers_mut().insert(X_CORRELATION_ID, value);
+    }
+    response
+}
+
+/// The correlation id of the request being handled, if any
+pub fn current_correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+fn is_valid_correlation_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_CORRELATION_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correlation_id_validation() {
+        assert!(is_valid_correlation_id(&Uuid::new_v4().to_string()));
+        assert!(is_valid_correlation_id("frontend.req_42"));
+        assert!(!is_valid_correlation_id(""));
+        assert!(!is_valid_correlation_id("id with spaces"));
+        assert!(!is_valid_correlation_id("<script>"));
+        assert!(!is_valid_correlation_id(&"a".repeat(129)));
+    }
+
+    #[tokio::test]
+    async fn test_current_correlation_id() {
+        assert_eq!(current_correlation_id(), None);
+
+        let id = CORRELATION_ID
+            .scope("abc-123".to_string(), async { current_correlation_id() })
+            .await;
+        assert_eq!(id.as_deref(), Some("abc-123"));
+    }
+}
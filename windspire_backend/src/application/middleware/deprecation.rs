@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+
+/// RFC 9745: when the endpoint was deprecated
+pub const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+/// RFC 8594: when the endpoint is expected to stop answering
+pub const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// State of `deprecation_middleware`: when the routes it is layered on were
+/// retired, when they go away and where their replacement lives. Retired
+/// routes keep working; the headers let clients notice before the sunset.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    since: DateTime<Utc>,
+    sunset: Option<DateTime<Utc>>,
+    successor: Option<String>,
+}
+
+impl Deprecation {
+    pub fn new(since: DateTime<Utc>) -> Self {
+        Self {
+            since,
+            sunset: None,
+            successor: None,
+        }
+    }
+
+    pub fn sunset(mut self, sunset: DateTime<Utc>) -> Self {
+        self.sunset = Some(sunset);
+        self
+    }
+
+    /// Prefix under which the same paths live in the version that replaces
+    /// these routes, e.g. `/api/v1`
+    pub fn successor(mut self, prefix: impl Into<String>) -> Self {
+        self.successor = Some(prefix.into());
+        self
+    }
+
+    /// `path` is the path of the request below the prefix the routes are
+    /// nested at
+    fn insert_headers(&self, headers: &mut HeaderMap, path: &str) {
+        // A structured field date: `@` and the Unix time
+        if let Ok(value) = HeaderValue::from_str(&format!("@{}", self.since.timestamp())) {
+            headers.insert(DEPRECATION, value);
+        }
+
+        if let Some(sunset) = self.sunset {
+            if let Ok(value) = HeaderValue::from_str(&http_date(sunset)) {
+                headers.insert(SUNSET, value);
+            }
+        }
+
+        if let Some(successor) = &self.successor {
+            let link = format!("<{}{}>; rel=\"successor-version\"", successor, path);
+            if let Ok(value) = HeaderValue::from_str(&link) {
+                headers.append(header::LINK, value);
+            }
+        }
+    }
+}
+
+pub async fn deprecation_middleware(
+    State(deprecation): State<Deprecation>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let mut response = next.run(request).await;
+    deprecation.insert_headers(response.headers_mut(), &path);
+    response
+}
+
+/// IMF-fixdate, the date format of HTTP headers
+fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_deprecation_headers() {
+        let deprecation = Deprecation::new(Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).unwrap())
+            .sunset(Utc.with_ymd_and_hms(2027, 4, 30, 0, 0, 0).unwrap())
+            .successor("/api/v1");
+
+        let mut headers = HeaderMap::new();
+        deprecation.insert_headers(&mut headers, "/boats/my");
+
+        assert_eq!(headers[DEPRECATION], "@1792195200");
+        assert_eq!(headers[SUNSET], "Fri, 30 Apr 2027 00:00:00 GMT");
+        assert_eq!(
+            headers[header::LINK],
+            "</api/v1/boats/my>; rel=\"successor-version\""
+        );
+    }
+
+    #[test]
+    fn test_deprecation_without_sunset_or_successor() {
+        let deprecation = Deprecation::new(Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).unwrap());
+
+        let mut headers = HeaderMap::new();
+        deprecation.insert_headers(&mut headers, "/boats");
+
+        assert!(headers.contains_key(DEPRECATION));
+        assert!(!headers.contains_key(SUNSET));
+        assert!(!headers.contains_key(header::LINK));
+    }
+}
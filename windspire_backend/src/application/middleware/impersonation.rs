@@ -0,0 +1,87 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use crate::application::error::AppError;
+use crate::domain::models::auth::AuthContext;
+
+/// Refuses requests made with an impersonation token. Layered on routes that
+/// go beyond seeing the app as the user does, such as minting credentials,
+/// changing how the user signs in, changing boat owners or administering other
+/// users, through
+/// `middleware::from_extractor::<NotImpersonating>()` behind the JWT middleware.
+pub struct NotImpersonating;
+
+impl<S> FromRequestParts<S> for NotImpersonating
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let auth_context = parts
+            .extensions
+            .get::<AuthContext>()
+            .ok_or_else(|| AppError::Unauthorized("Authentication required".to_string()))?;
+
+        if auth_context.impersonator.is_some() {
+            Err(AppError::Forbidden(
+                "Not allowed while impersonating".to_string(),
+            ))
+        } else {
+            Ok(Self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::auth::{Actor, AuthUser};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use uuid::Uuid;
+
+    fn auth_context(impersonator: Option<Actor>) -> AuthContext {
+        AuthContext {
+            user: AuthUser {
+                id: Uuid::new_v4(),
+                email: "sailor@example.com".to_string(),
+                first_name: "Test".to_string(),
+                last_name: "Sailor".to_string(),
+                provider_id: "123".to_string(),
+                provider_name: "firebase".to_string(),
+                avatar_url: None,
+                roles: vec!["user".to_string()],
+                permissions: vec![],
+                permission_version: 0,
+            },
+            token: "test-token".to_string(),
+            api_key_id: None,
+            impersonator,
+        }
+    }
+
+    async fn extract(auth_context: AuthContext) -> Result<NotImpersonating, StatusCode> {
+        let mut request = Request::builder().body(Body::empty()).unwrap();
+        request.extensions_mut().insert(auth_context);
+        let (mut parts, _) = request.into_parts();
+        NotImpersonating::from_request_parts(&mut parts, &())
+            .await
+            .map_err(|e| e.status())
+    }
+
+    #[tokio::test]
+    async fn test_not_impersonating_extractor() {
+        assert!(extract(auth_context(None)).await.is_ok());
+
+        let support = Actor {
+            sub: Uuid::new_v4().to_string(),
+            email: "support@example.com".to_string(),
+        };
+        assert_eq!(
+            extract(auth_context(Some(support))).await.err(),
+            Some(StatusCode::FORBIDDEN)
+        );
+    }
+}
@@ -1,2 +1,9 @@
 pub mod auth_middleware;
+pub mod client_info;
+pub mod correlation_id;
+pub mod deprecation;
+pub mod impersonation;
+pub mod ownership;
+pub mod rate_limit;
 pub mod rbac_middleware;
+pub mod validated_json;
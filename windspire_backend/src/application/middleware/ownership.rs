@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::repositories::boat_owner_repository::BoatOwnerRepository;
+
+/// Decides whether the caller owns the resource a request targets, for
+/// `*_own` permissions. The resource is identified by the route's path
+/// parameters; a missing or malformed id is never owned.
+#[async_trait]
+pub trait OwnershipResolver: Send + Sync {
+    async fn is_owner(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        path_params: &HashMap<String, String>,
+    ) -> Result<bool, sqlx::Error>;
+}
+
+/// `users/{user_id}`: users own their own account
+pub struct SelfOwnership {
+    param: &'static str,
+}
+
+impl SelfOwnership {
+    pub fn new(param: &'static str) -> Self {
+        Self { param }
+    }
+}
+
+#[async_trait]
+impl OwnershipResolver for SelfOwnership {
+    async fn is_owner(
+        &self,
+        _pool: &PgPool,
+        user_id: Uuid,
+        path_params: &HashMap<String, String>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(path_uuid(path_params, self.param) == Some(user_id))
+    }
+}
+
+/// `boats/{boat_id}`: owners are listed in `boat_owners`
+pub struct BoatOwnership {
+    param: &'static str,
+}
+
+impl BoatOwnership {
+    pub fn new(param: &'static str) -> Self {
+        Self { param }
+    }
+}
+
+#[async_trait]
+impl OwnershipResolver for BoatOwnership {
+    async fn is_owner(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        path_params: &HashMap<String, String>,
+    ) -> Result<bool, sqlx::Error> {
+        let Some(boat_id) = path_uuid(path_params, self.param) else {
+            return Ok(false);
+        };
+
+        BoatOwnerRepository::new(pool)
+            .is_owner(boat_id, user_id)
+            .await
+    }
+}
+
+/// Chooses the resolver for `RequireOwnPermission<P, O>` at the type level
+pub trait OwnershipKind: Send + Sync + 'static {
+    type Resolver: OwnershipResolver;
+
+    fn resolver() -> Self::Resolver;
+}
+
+/// The `{user_id}` path parameter is the caller
+pub struct SelfOwned;
+
+impl OwnershipKind for SelfOwned {
+    type Resolver = SelfOwnership;
+
+    fn resolver() -> Self::Resolver {
+        SelfOwnership::new("user_id")
+    }
+}
+
+/// The caller owns the `{boat_id}` boat
+pub struct BoatOwned;
+
+impl OwnershipKind for BoatOwned {
+    type Resolver = BoatOwnership;
+
+    fn resolver() -> Self::Resolver {
+        BoatOwnership::new("boat_id")
+    }
+}
+
+fn path_uuid(path_params: &HashMap<String, String>, param: &str) -> Option<Uuid> {
+    path_params
+        .get(param)
+        .and_then(|value| Uuid::parse_str(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::PgPoolOptions;
+
+    fn lazy_pool() -> PgPool {
+        // Never connects; the resolvers under test don't touch the database
+        PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_self_ownership() {
+        let pool = lazy_pool();
+        let resolver = SelfOwnership::new("user_id");
+        let user_id = Uuid::new_v4();
+
+        let own = HashMap::from([("user_id".to_string(), user_id.to_string())]);
+        let other = HashMap::from([("user_id".to_string(), Uuid::new_v4().to_string())]);
+        let malformed = HashMap::from([("user_id".to_string(), "me".to_string())]);
+
+        assert!(resolver.is_owner(&pool, user_id, &own).await.unwrap());
+        assert!(!resolver.is_owner(&pool, user_id, &other).await.unwrap());
+        assert!(!resolver.is_owner(&pool, user_id, &malformed).await.unwrap());
+        assert!(!resolver
+            .is_owner(&pool, user_id, &HashMap::new())
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_boat_ownership_without_boat_id() {
+        let pool = lazy_pool();
+        let resolver = BoatOwnership::new("boat_id");
+        let params = HashMap::from([("user_id".to_string(), Uuid::new_v4().to_string())]);
+
+        assert!(!resolver
+            .is_owner(&pool, Uuid::new_v4(), &params)
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::application::error::AppError;
+use crate::application::middleware::client_info::client_ip;
+use crate::application::services::rate_limit_service::{
+    RateLimitBudget, RateLimitDecision, RateLimitService,
+};
+use crate::application::state::AppState;
+use crate::domain::models::auth::AuthContext;
+
+const X_RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const X_RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+
+/// State of `rate_limit_middleware`: the budget the routes it is layered on
+/// draw from. Behind the JWT middleware requests are counted per user,
+/// anywhere else per client IP.
+#[derive(Clone)]
+pub struct RateLimit {
+    service: Arc<RateLimitService>,
+    budget: RateLimitBudget,
+}
+
+impl RateLimit {
+    pub fn new(app_state: &AppState, budget: RateLimitBudget) -> Self {
+        Self {
+            service: app_state.rate_limit_service.clone(),
+            budget,
+        }
+    }
+}
+
+pub async fn rate_limit_middleware(
+    State(rate_limit): State<RateLimit>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !rate_limit.service.is_enabled() {
+        return next.run(request).await;
+    }
+
+    let client = client_key(&request, rate_limit.service.trusts_forwarded_for());
+    let decision = rate_limit
+        .service
+        .check(rate_limit.budget, &client, Utc::now())
+        .await;
+
+    if !decision.allowed {
+        tracing::warn!(
+            "Rate limit exceeded for {} on the {} budget",
+            client,
+            rate_limit.budget.as_str()
+        );
+        let mut response = AppError::TooManyRequests {
+            retry_after_seconds: decision.retry_after_seconds,
+        }
+        .into_response();
+        insert_limit_headers(response.headers_mut(), &decision);
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    insert_limit_headers(response.headers_mut(), &decision);
+    response
+}
+
+fn insert_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(X_RATE_LIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(
+        X_RATE_LIMIT_REMAINING,
+        HeaderValue::from(decision.remaining),
+    );
+}
+
+/// The authenticated user, or else the client IP
+fn client_key(request: &Request, trust_forwarded_for: bool) -> String {
+    if let Some(auth_context) = request.extensions().get::<AuthContext>() {
+        return format!("user:{}", auth_context.user.id);
+    }
+
+    let ip = client_ip(request.headers(), request.extensions(), trust_forwarded_for);
+    format!("ip:{}", ip.as_deref().unwrap_or("unknown"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::auth::AuthUser;
+    use axum::body::Body;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_client_key_prefers_user() {
+        let mut request = Request::builder().body(Body::empty()).unwrap();
+        assert_eq!(client_key(&request, false), "ip:unknown");
+
+        let user_id = Uuid::new_v4();
+        request.extensions_mut().insert(AuthContext {
+            user: AuthUser {
+                id: user_id,
+                email: "test@example.com".to_string(),
+                first_name: "Test".to_string(),
+                last_name: "User".to_string(),
+                provider_id: "123".to_string(),
+                provider_name: "google".to_string(),
+                avatar_url: None,
+                roles: vec![],
+                permissions: vec![],
+                permission_version: 0,
+            },
+            token: "token".to_string(),
+            api_key_id: None,
+            impersonator: None,
+        });
+        assert_eq!(client_key(&request, false), format!("user:{}", user_id));
+    }
+}
@@ -1,188 +1,126 @@
-use axum::{extract::Request, http::StatusCode, middleware::Next, response::Response};
-use std::collections::HashSet;
+use axum::{
+    extract::{FromRequestParts, RawPathParams, Request},
+    http::request::Parts,
+};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
 
+use crate::application::error::AppError;
 use crate::application::middleware::auth_middleware::extract_auth_context;
+use crate::application::middleware::ownership::{OwnershipKind, OwnershipResolver};
+use crate::application::state::AppState;
+use crate::domain::models::auth::{AuthContext, AuthUser};
+use crate::domain::models::permission_matcher;
 use crate::domain::models::rbac::{
-    PERMISSION_BOATS_DELETE, PERMISSION_BOATS_READ, PERMISSION_BOATS_WRITE,
-    PERMISSION_COUNTRIES_DELETE, PERMISSION_COUNTRIES_READ, PERMISSION_COUNTRIES_WRITE,
-    PERMISSION_USERS_DELETE, PERMISSION_USERS_READ, PERMISSION_USERS_WRITE, ROLE_ADMIN,
+    OwnablePermission, PermissionMarker, PermissionName, ROLE_ADMIN,
 };
-
-#[derive(Debug, Clone)]
-pub struct RequiredPermission {
-    pub permission: String,
-    pub allow_own: bool, // Allow if user owns the resource
-}
-
-impl RequiredPermission {
-    pub fn new(permission: &str) -> Self {
-        Self {
-            permission: permission.to_string(),
-            allow_own: false,
-        }
-    }
-
-    pub fn with_own_access(permission: &str) -> Self {
-        Self {
-            permission: permission.to_string(),
-            allow_own: true,
+use crate::domain::repositories::permission_repository::PermissionRepository;
+use crate::infrastructure::repositories::sqlx_permission_repository::SqlxPermissionRepository;
+
+/// Requires the caller to hold `P`, e.g. `RequirePermission<BoatsWrite>`.
+/// Works as a handler argument, or as a layer through
+/// `middleware::from_extractor::<RequirePermission<P>>()` behind the JWT middleware.
+pub struct RequirePermission<P>(PhantomData<P>);
+
+impl<P, S> FromRequestParts<S> for RequirePermission<P>
+where
+    P: PermissionMarker,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let auth_context = parts_auth_context(parts)?;
+
+        if user_has_permission(&auth_context.user, P::PERMISSION.as_str()) {
+            Ok(Self(PhantomData))
+        } else {
+            Err(insufficient_permissions())
         }
     }
 }
 
-#[allow(clippy::type_complexity)]
-pub fn require_permission(
-    permission: RequiredPermission,
-) -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    move |request: Request, next: Next| {
-        let permission = permission.clone();
-        Box::pin(async move { check_permission(request, next, permission).await })
-    }
-}
-
-async fn check_permission(
-    request: Request,
-    next: Next,
-    required_permission: RequiredPermission,
-) -> Result<Response, (StatusCode, &'static str)> {
-    // Extract auth context
-    let auth_context = extract_auth_context(&request)
-        .ok_or((StatusCode::UNAUTHORIZED, "Authentication required"))?;
-
-    // Admin role bypasses all permission checks
-    if auth_context.user.roles.contains(&ROLE_ADMIN.to_string()) {
-        return Ok(next.run(request).await);
-    }
-
-    // Check if user has the required permission
-    let has_permission = auth_context
-        .user
-        .permissions
-        .contains(&required_permission.permission);
-
-    if has_permission {
-        return Ok(next.run(request).await);
-    }
+/// Like `RequirePermission<P>`, but holders of the `_own` variant of `P` also
+/// pass for resources they own, e.g. `RequireOwnPermission<UsersWrite, SelfOwned>`
+pub struct RequireOwnPermission<P, O>(PhantomData<(P, O)>);
+
+impl<P, O> FromRequestParts<AppState> for RequireOwnPermission<P, O>
+where
+    P: OwnablePermission,
+    O: OwnershipKind,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = &parts_auth_context(parts)?.user;
+
+        if user_has_permission(user, P::PERMISSION.as_str()) {
+            return Ok(Self(PhantomData));
+        }
+        if !permission_matcher::any_grants(&user.permissions, P::OWN_PERMISSION.as_str()) {
+            return Err(insufficient_permissions());
+        }
 
-    // If permission allows own access, check for own-specific permission
-    if required_permission.allow_own {
-        let _own_permission = format!(
-            "{}_own",
-            required_permission
-                .permission
-                .replace(":read", "")
-                .replace(":write", "")
-                .replace(":delete", "")
-        );
-        if auth_context
-            .user
-            .permissions
-            .iter()
-            .any(|p| p.contains("_own"))
-        {
-            // Additional logic would be needed here to verify the user owns the resource
-            // This would typically involve extracting the resource ID from the request path
-            // and checking ownership in the database
-            return Ok(next.run(request).await);
+        let user_id = user.id;
+        let path_params: HashMap<String, String> = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let is_owner = O::resolver()
+            .is_owner(&state.db_pool, user_id, &path_params)
+            .await
+            .map_err(|e| {
+                AppError::Internal(format!("Failed to check resource ownership: {}", e))
+            })?;
+
+        if is_owner {
+            Ok(Self(PhantomData))
+        } else {
+            Err(insufficient_permissions())
         }
     }
-
-    Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
 }
 
-#[allow(clippy::type_complexity)]
-pub fn require_users_read() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::with_own_access(PERMISSION_USERS_READ))
+fn parts_auth_context(parts: &Parts) -> Result<&AuthContext, AppError> {
+    parts
+        .extensions
+        .get::<AuthContext>()
+        .ok_or_else(|| AppError::Unauthorized("Authentication required".to_string()))
 }
 
-#[allow(clippy::type_complexity)]
-pub fn require_users_write() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::with_own_access(PERMISSION_USERS_WRITE))
+fn insufficient_permissions() -> AppError {
+    AppError::Forbidden("Insufficient permissions".to_string())
 }
 
-#[allow(clippy::type_complexity)]
-pub fn require_users_delete() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::new(PERMISSION_USERS_DELETE))
+/// The permissions routes can require that are missing from the database.
+/// Checked at startup, so a typo in a seed fails the deploy instead of every
+/// request to the route.
+pub async fn missing_permissions(pool: &PgPool) -> Result<Vec<PermissionName>, sqlx::Error> {
+    let existing: Vec<String> = SqlxPermissionRepository
+        .get_all_permissions(pool)
+        .await?
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+
+    Ok(missing_from(&existing))
 }
 
-#[allow(clippy::type_complexity)]
-pub fn require_countries_read() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::new(PERMISSION_COUNTRIES_READ))
-}
-
-#[allow(clippy::type_complexity)]
-pub fn require_countries_write() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::new(PERMISSION_COUNTRIES_WRITE))
-}
-
-#[allow(clippy::type_complexity)]
-pub fn require_countries_delete() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::new(PERMISSION_COUNTRIES_DELETE))
-}
-
-#[allow(clippy::type_complexity)]
-pub fn require_boats_read() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::new(PERMISSION_BOATS_READ))
-}
-
-#[allow(clippy::type_complexity)]
-pub fn require_boats_write() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::new(PERMISSION_BOATS_WRITE))
-}
-
-#[allow(clippy::type_complexity)]
-pub fn require_boats_delete() -> impl Fn(
-    Request,
-    Next,
-) -> std::pin::Pin<
-    Box<dyn std::future::Future<Output = Result<Response, (StatusCode, &'static str)>> + Send>,
-> + Clone {
-    require_permission(RequiredPermission::new(PERMISSION_BOATS_DELETE))
+fn missing_from(existing: &[String]) -> Vec<PermissionName> {
+    PermissionName::ALL
+        .into_iter()
+        .filter(|permission| !existing.iter().any(|name| name == permission.as_str()))
+        .collect()
 }
 
 // Helper function to check if user has any of the specified roles
@@ -199,18 +137,15 @@ pub fn has_any_role(request: &Request, roles: &[&str]) -> bool {
 
 // Helper function to check if user has specific permission
 pub fn has_permission(request: &Request, permission: &str) -> bool {
-    if let Some(auth_context) = extract_auth_context(request) {
-        // Admin role bypasses all permission checks
-        if auth_context.user.roles.contains(&ROLE_ADMIN.to_string()) {
-            return true;
-        }
-        auth_context
-            .user
-            .permissions
-            .contains(&permission.to_string())
-    } else {
-        false
-    }
+    extract_auth_context(request).is_some_and(|ctx| user_has_permission(&ctx.user, permission))
+}
+
+// Same check for handlers that already hold the authenticated user. `permission`
+// may be scoped, e.g. `club:{id}:boats:write`; grants may use wildcards.
+pub fn user_has_permission(user: &AuthUser, permission: &str) -> bool {
+    // Admin role bypasses all permission checks
+    user.roles.iter().any(|r| r == ROLE_ADMIN)
+        || permission_matcher::any_grants(&user.permissions, permission)
 }
 
 // Helper function to get user ID from auth context
@@ -221,8 +156,14 @@ pub fn get_user_id(request: &Request) -> Option<uuid::Uuid> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::models::auth::{AuthContext, AuthUser};
-    use axum::{body::Body, http::Request};
+    use crate::domain::models::rbac::{
+        BoatsWrite, PERMISSION_BOATS_DELETE, PERMISSION_BOATS_WRITE, PERMISSION_COUNTRIES_DELETE,
+        PERMISSION_USERS_DELETE, PERMISSION_USERS_READ, PERMISSION_USERS_WRITE,
+    };
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
     use uuid::Uuid;
 
     fn create_test_user_with_permissions(permissions: Vec<&str>, roles: Vec<&str>) -> AuthUser {
@@ -236,6 +177,7 @@ mod tests {
             avatar_url: Some("https://example.com/avatar.jpg".to_string()),
             roles: roles.iter().map(|r| r.to_string()).collect(),
             permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            permission_version: 0,
         }
     }
 
@@ -245,6 +187,8 @@ mod tests {
         let auth_context = AuthContext {
             user,
             token: "test-token".to_string(),
+            api_key_id: None,
+            impersonator: None,
         };
 
         let mut request = Request::builder().body(Body::empty()).unwrap();
@@ -254,12 +198,44 @@ mod tests {
         assert!(!has_permission(&request, PERMISSION_USERS_WRITE));
     }
 
+    #[test]
+    fn test_user_has_permission() {
+        let user = create_test_user_with_permissions(vec![PERMISSION_BOATS_WRITE], vec!["user"]);
+        let admin = create_test_user_with_permissions(vec![], vec![ROLE_ADMIN]);
+
+        assert!(user_has_permission(&user, PERMISSION_BOATS_WRITE));
+        assert!(!user_has_permission(&user, PERMISSION_BOATS_DELETE));
+        assert!(user_has_permission(&admin, PERMISSION_BOATS_DELETE));
+    }
+
+    #[test]
+    fn test_user_has_wildcard_and_scoped_permission() {
+        let club_id = Uuid::new_v4();
+        let official = create_test_user_with_permissions(
+            vec!["*:read", &format!("club:{}:boats:*", club_id)],
+            vec!["user"],
+        );
+
+        assert!(user_has_permission(&official, PERMISSION_USERS_READ));
+        assert!(!user_has_permission(&official, PERMISSION_BOATS_WRITE));
+        assert!(user_has_permission(
+            &official,
+            &permission_matcher::scoped("club", club_id, PERMISSION_BOATS_WRITE)
+        ));
+        assert!(!user_has_permission(
+            &official,
+            &permission_matcher::scoped("club", Uuid::new_v4(), PERMISSION_BOATS_WRITE)
+        ));
+    }
+
     #[test]
     fn test_admin_role_bypass() {
         let user = create_test_user_with_permissions(vec![], vec![ROLE_ADMIN]);
         let auth_context = AuthContext {
             user,
             token: "test-token".to_string(),
+            api_key_id: None,
+            impersonator: None,
         };
 
         let mut request = Request::builder().body(Body::empty()).unwrap();
@@ -277,6 +253,8 @@ mod tests {
         let auth_context = AuthContext {
             user,
             token: "test-token".to_string(),
+            api_key_id: None,
+            impersonator: None,
         };
 
         let mut request = Request::builder().body(Body::empty()).unwrap();
@@ -297,6 +275,8 @@ mod tests {
         let auth_context = AuthContext {
             user,
             token: "test-token".to_string(),
+            api_key_id: None,
+            impersonator: None,
         };
 
         let mut request = Request::builder().body(Body::empty()).unwrap();
@@ -304,4 +284,69 @@ mod tests {
 
         assert_eq!(get_user_id(&request), Some(user_id));
     }
+
+    async fn extract_boats_write(
+        auth_context: Option<AuthContext>,
+    ) -> Result<RequirePermission<BoatsWrite>, AppError> {
+        let mut request = Request::builder().body(Body::empty()).unwrap();
+        if let Some(auth_context) = auth_context {
+            request.extensions_mut().insert(auth_context);
+        }
+        let (mut parts, _) = request.into_parts();
+        RequirePermission::<BoatsWrite>::from_request_parts(&mut parts, &()).await
+    }
+
+    fn auth_context(user: AuthUser) -> AuthContext {
+        AuthContext {
+            user,
+            token: "test-token".to_string(),
+            api_key_id: None,
+            impersonator: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_require_permission_extractor() {
+        let writer = create_test_user_with_permissions(vec![PERMISSION_BOATS_WRITE], vec!["user"]);
+        let reader = create_test_user_with_permissions(vec![PERMISSION_USERS_READ], vec!["user"]);
+        let admin = create_test_user_with_permissions(vec![], vec![ROLE_ADMIN]);
+
+        assert!(extract_boats_write(Some(auth_context(writer)))
+            .await
+            .is_ok());
+        assert!(extract_boats_write(Some(auth_context(admin))).await.is_ok());
+        assert_eq!(
+            extract_boats_write(Some(auth_context(reader)))
+                .await
+                .err()
+                .map(|e| (e.status(), e.detail())),
+            Some((
+                StatusCode::FORBIDDEN,
+                "Insufficient permissions".to_string()
+            ))
+        );
+        assert_eq!(
+            extract_boats_write(None).await.err().map(|e| e.status()),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn test_missing_permissions() {
+        let all: Vec<String> = PermissionName::ALL
+            .iter()
+            .map(|p| p.as_str().to_string())
+            .collect();
+        assert!(missing_from(&all).is_empty());
+
+        let without_boats_delete: Vec<String> = all
+            .iter()
+            .filter(|name| *name != PERMISSION_BOATS_DELETE)
+            .cloned()
+            .collect();
+        assert_eq!(
+            missing_from(&without_boats_delete),
+            vec![PermissionName::BoatsDelete]
+        );
+    }
 }
@@ -0,0 +1,241 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, StatusCode},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use validator::Validate;
+
+use crate::application::error::{AppError, FieldError};
+
+/// A JSON body that was deserialised and validated. Use it instead of `Json`
+/// for request bodies: malformed JSON, wrong types, missing fields and failed
+/// validations are all answered with the same `validation_failed` problem,
+/// whose `errors` name the offending field as the client sent it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(request.headers()) {
+            return Err(AppError::UnsupportedMediaType);
+        }
+
+        let bytes =
+            Bytes::from_request(request, state)
+                .await
+                .map_err(|rejection| match rejection.status() {
+                    StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge,
+                    _ => AppError::BadRequest(rejection.body_text()),
+                })?;
+
+        let body: Value = serde_json::from_slice(&bytes).map_err(|e| {
+            let mut error = FieldError::new(None, "invalid_json", e.to_string());
+            error.params.insert("line".to_string(), e.line().into());
+            error.params.insert("column".to_string(), e.column().into());
+            AppError::Validation(vec![error])
+        })?;
+
+        let value: T = serde_path_to_error::deserialize(&body)
+            .map_err(|e| AppError::Validation(vec![deserialize_error(e)]))?;
+
+        value.validate().map_err(|e| {
+            AppError::Validation(FieldError::from_validation_errors(&e, Some(&body)))
+        })?;
+
+        Ok(Self(value))
+    }
+}
+
+/// `application/json` or any `application/*+json`, parameters allowed
+fn has_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+}
+
+/// Turns a serde failure into a field error. Serde only reports a message, so
+/// the code is derived from its standard wording; anything else (e.g. a
+/// malformed UUID) is an `invalid_value`.
+fn deserialize_error(error: serde_path_to_error::Error<serde_json::Error>) -> FieldError {
+    let path = error.path().to_string();
+    let path = (path != ".").then_some(path);
+    let message = error.inner().to_string();
+
+    if let Some(name) = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split('`').next())
+    {
+        let field = match &path {
+            Some(path) => format!("{}.{}", path, name),
+            None => name.to_string(),
+        };
+        return FieldError::new(Some(field), "required", format!("{} is required", name));
+    }
+
+    let code = if message.starts_with("invalid type") {
+        "invalid_type"
+    } else if message.starts_with("unknown field") {
+        "unknown_field"
+    } else {
+        "invalid_value"
+    };
+    FieldError::new(path, code, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::header::CONTENT_TYPE};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, Validate)]
+    #[serde(rename_all = "camelCase")]
+    struct BoatBody {
+        #[validate(length(min = 2, message = "Boat name must contain 2 at least characters"))]
+        name: String,
+        sail_number: Option<String>,
+        #[allow(dead_code)]
+        country_id: uuid::Uuid,
+        #[validate(nested)]
+        #[serde(default)]
+        crew: Vec<CrewBody>,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    #[serde(rename_all = "camelCase")]
+    struct CrewBody {
+        #[validate(length(min = 1, message = "First name is required"))]
+        first_name: String,
+    }
+
+    async fn extract(content_type: &str, body: &str) -> Result<BoatBody, AppError> {
+        let request = Request::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        ValidatedJson::<BoatBody>::from_request(request, &())
+            .await
+            .map(|ValidatedJson(body)| body)
+    }
+
+    fn field_errors(result: Result<BoatBody, AppError>) -> Vec<(Option<String>, String)> {
+        match result {
+            Err(AppError::Validation(errors)) => errors
+                .into_iter()
+                .map(|error| (error.field, error.code))
+                .collect(),
+            other => panic!("Expected a validation error, got {:?}", other),
+        }
+    }
+
+    const COUNTRY_ID: &str = "0196e2a4-6a2e-7a6b-8d2c-3f8e6b1c2d4e";
+
+    #[tokio::test]
+    async fn test_valid_body() {
+        let body = format!(
+            r#"{{"name": "Aurora", "sailNumber": "NOR 1", "countryId": "{}"}}"#,
+            COUNTRY_ID
+        );
+        let boat = extract("application/json; charset=utf-8", &body)
+            .await
+            .unwrap();
+
+        assert_eq!(boat.name, "Aurora");
+        assert_eq!(boat.sail_number.as_deref(), Some("NOR 1"));
+    }
+
+    #[tokio::test]
+    async fn test_deserialization_errors_name_the_field() {
+        let missing = extract("application/json", r#"{"name": "Aurora"}"#).await;
+        assert_eq!(
+            field_errors(missing),
+            [(Some("countryId".to_string()), "required".to_string())]
+        );
+
+        let wrong_type = format!(r#"{{"name": 42, "countryId": "{}"}}"#, COUNTRY_ID);
+        assert_eq!(
+            field_errors(extract("application/json", &wrong_type).await),
+            [(Some("name".to_string()), "invalid_type".to_string())]
+        );
+
+        let bad_uuid = r#"{"name": "Aurora", "countryId": "not-a-uuid"}"#;
+        assert_eq!(
+            field_errors(extract("application/json", bad_uuid).await),
+            [(Some("countryId".to_string()), "invalid_value".to_string())]
+        );
+
+        let nested = format!(
+            r#"{{"name": "Aurora", "countryId": "{}", "crew": [{{}}]}}"#,
+            COUNTRY_ID
+        );
+        assert_eq!(
+            field_errors(extract("application/json", &nested).await),
+            [(
+                Some("crew[0].firstName".to_string()),
+                "required".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json() {
+        let result = extract("application/json", r#"{"name": "#).await;
+        let Err(AppError::Validation(errors)) = result else {
+            panic!("Expected a validation error");
+        };
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, None);
+        assert_eq!(errors[0].code, "invalid_json");
+        assert_eq!(errors[0].params["line"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_validation_errors_use_client_field_names() {
+        let body = format!(
+            r#"{{"name": "A", "countryId": "{}", "crew": [{{"firstName": "Ola"}}, {{"firstName": ""}}]}}"#,
+            COUNTRY_ID
+        );
+
+        assert_eq!(
+            field_errors(extract("application/json", &body).await),
+            [
+                (Some("crew[1].firstName".to_string()), "length".to_string()),
+                (Some("name".to_string()), "length".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_type_is_required() {
+        let result = extract("text/plain", "{}").await;
+        assert!(matches!(result, Err(AppError::UnsupportedMediaType)));
+
+        let mut headers = HeaderMap::new();
+        assert!(!has_json_content_type(&headers));
+        headers.insert(
+            CONTENT_TYPE,
+            "application/merge-patch+json".parse().unwrap(),
+        );
+        assert!(has_json_content_type(&headers));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod approuter;
 pub mod commands;
 pub mod config;
+pub mod error;
 pub mod handlers;
 pub mod middleware;
 pub mod queries;
@@ -12,8 +13,29 @@ pub mod http_response {
         http::{header, StatusCode},
         response::{IntoResponse, Response},
     };
-    use serde::Serialize;
-    use serde_json::{json, to_string};
+    use serde::{Deserialize, Serialize};
+    use serde_json::to_string;
+    use utoipa::ToSchema;
+
+    /// The envelope of every successful JSON response
+    #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+    pub struct ApiResponse<T> {
+        pub success: bool,
+        pub data: T,
+    }
+
+    /// Body of the endpoints that confirm an action without sending `data`
+    #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+    pub struct Confirmation {
+        pub success: bool,
+        pub message: String,
+    }
+
+    /// `data` of responses that only confirm an action
+    #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+    pub struct Message {
+        pub message: String,
+    }
 
     /// Safer version that never panics
     fn safe_serialize<T: Serialize>(payload: T) -> String {
@@ -32,32 +54,12 @@ pub mod http_response {
     }
 
     pub fn ok_json_response<T: Serialize>(payload: T) -> Response {
-        json_response(StatusCode::OK, json!({ "success": true, "data": payload }))
-    }
-
-    pub fn internal_server_error_json_response(err: sqlx::Error) -> Response {
         json_response(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            json!({ "success": false, "message": err.to_string() }),
+            StatusCode::OK,
+            ApiResponse {
+                success: true,
+                data: payload,
+            },
         )
     }
-
-    pub fn row_not_found_error_json_response(message: &str) -> Response {
-        json_response(
-            StatusCode::NOT_FOUND,
-            json!({ "success": false, "message": message }),
-        )
-    }
-
-    pub fn bad_request_error_json_response(message: String) -> Response {
-        if message == INVALID_COUNTRY_CODE_MSG {
-            json_response(
-                StatusCode::BAD_REQUEST,
-                json!({ "success": false, "message": "Invalid country code format" }),
-            )
-        } else {
-            json_response(StatusCode::BAD_REQUEST, json!({ "success": false }))
-        }
-    }
-    const INVALID_COUNTRY_CODE_MSG: &str = "Invalid country code";
 }
@@ -1,29 +1,47 @@
 use axum::{
     extract::{Query, State},
-    response::IntoResponse,
+    response::Response,
 };
 use serde::Deserialize;
+use utoipa::IntoParams;
 
 use crate::{
     application::{
-        http_response::{internal_server_error_json_response, ok_json_response},
+        error::AppError,
+        http_response::{ok_json_response, ApiResponse},
         state::AppState,
     },
-    domain::interface::boat_repository::{BoatRepository, PaginationParams},
+    domain::{
+        interface::boat_repository::{BoatRepository, PaginatedResult, PaginationParams},
+        models::boat::Boat,
+    },
     infrastructure::repositories::sqlx_boat_repository::SqlxBoatRepository,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct BoatQueryParams {
+    /// Page to return, starting at 1
     page: Option<u32>,
+    /// Boats per page, 1 to 100 (default 20)
     limit: Option<u32>,
+    /// `owners` to return each boat with its owners
     include: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/boats",
+    tag = "boats",
+    params(BoatQueryParams),
+    responses(
+        (status = 200, description = "A page of boats. With `include=owners` each item is a `BoatWithOwners` instead.", body = ApiResponse<PaginatedResult<Boat>>),
+    ),
+)]
 pub async fn get_boats_query(
     State(app_state): State<AppState>,
     Query(params): Query<BoatQueryParams>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let pagination_params = PaginationParams {
         page: params.page.unwrap_or(1).max(1),
         limit: params.limit.unwrap_or(20).clamp(1, 100), // Limit between 1 and 100
@@ -35,20 +53,14 @@ pub async fn get_boats_query(
     let include_owners = params.include.as_deref() == Some("owners");
 
     if include_owners {
-        match repository
+        let result = repository
             .get_paginated_with_owners(&app_state.db_pool, pagination_params)
-            .await
-        {
-            Ok(result) => ok_json_response(result),
-            Err(err) => internal_server_error_json_response(err),
-        }
+            .await?;
+        Ok(ok_json_response(result))
     } else {
-        match repository
+        let result = repository
             .get_paginated(&app_state.db_pool, pagination_params)
-            .await
-        {
-            Ok(result) => ok_json_response(result),
-            Err(err) => internal_server_error_json_response(err),
-        }
+            .await?;
+        Ok(ok_json_response(result))
     }
 }
@@ -1,17 +1,26 @@
-use axum::{extract::State, response::IntoResponse};
+use axum::{extract::State, response::Response};
 
 use crate::{
-    application::http_response::{internal_server_error_json_response, ok_json_response},
-    domain::interface::country_repository::CountryRepository,
+    application::{
+        error::AppError,
+        http_response::{ok_json_response, ApiResponse},
+    },
+    domain::{interface::country_repository::CountryRepository, models::country::Country},
     infrastructure::repositories::sqlx_country_repository::SqlxCountryRepository,
 };
 
+#[utoipa::path(
+    get,
+    path = "/countries",
+    tag = "countries",
+    responses(
+        (status = 200, description = "All countries", body = ApiResponse<Vec<Country>>),
+    ),
+)]
 pub async fn get_countries_query(
     State(app_state): State<crate::application::state::AppState>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let repository = SqlxCountryRepository;
-    match repository.get_countries(app_state.pool()).await {
-        Ok(countries) => ok_json_response(countries),
-        Err(e) => internal_server_error_json_response(e),
-    }
+    let countries = repository.get_countries(app_state.pool()).await?;
+    Ok(ok_json_response(countries))
 }
@@ -1,34 +1,49 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
 };
 use serde_json::json;
 
 use crate::{
     application::{
-        http_response::{
-            bad_request_error_json_response, internal_server_error_json_response, json_response,
-            row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
         state::AppState,
     },
-    domain::interface::country_repository::CountryRepository,
+    domain::{interface::country_repository::CountryRepository, models::country::Country},
     infrastructure::repositories::sqlx_country_repository::SqlxCountryRepository,
 };
 
+#[utoipa::path(
+    get,
+    path = "/countries/code/{country_code}",
+    tag = "countries",
+    params(("country_code" = String, Path, description = "ISO 3166 alpha-2 or alpha-3 code")),
+    responses(
+        (status = 200, description = "The country", body = ApiResponse<Country>),
+        (status = 400, description = "The code is not two or three letters"),
+        (status = 404, description = "Country not found"),
+    ),
+)]
 pub async fn get_country_by_code_query(
     State(app_state): State<AppState>,
     Path(country_code): Path<String>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let repository = SqlxCountryRepository;
     match repository
         .get_country_by_code(&app_state.db_pool, country_code)
         .await
     {
-        Ok(country) => json_response(StatusCode::OK, json!({ "success": true, "data": country })),
-        Err(sqlx::Error::ColumnNotFound(msg)) => bad_request_error_json_response(msg),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("Country not found"),
-        Err(e) => internal_server_error_json_response(e),
+        Ok(country) => Ok(json_response(
+            StatusCode::OK,
+            json!({ "success": true, "data": country }),
+        )),
+        // The repository refuses codes that are not two or three letters
+        Err(sqlx::Error::ColumnNotFound(_)) => Err(AppError::BadRequest(
+            "Invalid country code format".to_string(),
+        )),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Country not found".to_string())),
+        Err(e) => Err(e.into()),
     }
 }
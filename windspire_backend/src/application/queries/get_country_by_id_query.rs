@@ -1,32 +1,39 @@
 use axum::{
     extract::{Path, State},
-    response::IntoResponse,
+    response::Response,
 };
 use uuid::Uuid;
 
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, ok_json_response,
-            row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{ok_json_response, ApiResponse},
         state::AppState,
     },
-    domain::interface::country_repository::CountryRepository,
+    domain::{interface::country_repository::CountryRepository, models::country::Country},
     infrastructure::repositories::sqlx_country_repository::SqlxCountryRepository,
 };
 
+#[utoipa::path(
+    get,
+    path = "/countries/{country_id}",
+    tag = "countries",
+    responses(
+        (status = 200, description = "The country", body = ApiResponse<Country>),
+        (status = 404, description = "Country not found"),
+    ),
+)]
 pub async fn get_country_by_id_query(
     State(app_state): State<AppState>,
     Path(country_id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let repository = SqlxCountryRepository;
     match repository
         .get_country_by_id(&app_state.db_pool, country_id)
         .await
     {
-        Ok(country) => ok_json_response(country),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("Country not found"),
-        Err(e) => internal_server_error_json_response(e),
+        Ok(country) => Ok(ok_json_response(country)),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("Country not found".to_string())),
+        Err(e) => Err(e.into()),
     }
 }
@@ -1,24 +1,36 @@
 use crate::{
     application::{
-        http_response::{internal_server_error_json_response, json_response},
+        error::AppError,
+        http_response::{json_response, ApiResponse},
         state::AppState,
     },
-    domain::{models::auth::AuthContext, repositories::boat_owner_repository::BoatOwnerRepository},
+    domain::{
+        models::{auth::AuthContext, boat::Boat},
+        repositories::boat_owner_repository::BoatOwnerRepository,
+    },
 };
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension};
+use axum::{extract::State, http::StatusCode, response::Response, Extension};
 use serde_json::json;
 
+#[utoipa::path(
+    get,
+    path = "/boats/my",
+    tag = "boats",
+    responses(
+        (status = 200, description = "Boats owned by the caller", body = ApiResponse<Vec<Boat>>),
+    ),
+)]
 pub async fn get_my_boats_query(
     State(app_state): State<AppState>,
     Extension(auth_context): Extension<AuthContext>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let repo = BoatOwnerRepository::new(&app_state.db_pool);
 
-    match repo
+    let boats = repo
         .get_boats_with_details_for_user(auth_context.user.id)
-        .await
-    {
-        Ok(boats) => json_response(StatusCode::OK, json!({ "success": true, "data": boats })),
-        Err(e) => internal_server_error_json_response(e),
-    }
+        .await?;
+    Ok(json_response(
+        StatusCode::OK,
+        json!({ "success": true, "data": boats }),
+    ))
 }
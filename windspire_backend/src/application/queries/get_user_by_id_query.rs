@@ -1,30 +1,46 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
 };
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
         state::AppState,
     },
-    domain::repositories::user_repository::UserRepository,
+    domain::{models::user::User, repositories::user_repository::UserRepository},
     infrastructure::repositories::sqlx_user_repository::SqlxUserRepository,
 };
 
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}",
+    tag = "users",
+    responses(
+        (status = 200, description = "The user", body = ApiResponse<User>),
+        (status = 403, description = "Another user's account, without `users:read`"),
+        (status = 404, description = "User not found"),
+    ),
+    security(
+        ("bearer_auth" = ["users:read"]), ("bearer_auth" = ["users:read_own"]),
+        ("api_key" = ["users:read"]), ("api_key" = ["users:read_own"]),
+    ),
+)]
 pub async fn get_user_by_id_query(
     State(app_state): State<AppState>,
     Path(user_id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let repository = SqlxUserRepository;
     match repository.get_user_by_id(&app_state.db_pool, user_id).await {
-        Ok(users) => json_response(StatusCode::OK, json!({ "success": true, "data": users })),
-        Err(sqlx::Error::RowNotFound) => row_not_found_error_json_response("User not found"),
-        Err(err) => internal_server_error_json_response(err),
+        Ok(users) => Ok(json_response(
+            StatusCode::OK,
+            json!({ "success": true, "data": users }),
+        )),
+        Err(sqlx::Error::RowNotFound) => Err(AppError::NotFound("User not found".to_string())),
+        Err(err) => Err(err.into()),
     }
 }
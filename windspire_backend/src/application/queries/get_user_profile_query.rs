@@ -1,28 +1,39 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::Response,
 };
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
     application::{
-        http_response::{
-            internal_server_error_json_response, json_response, row_not_found_error_json_response,
-        },
+        error::AppError,
+        http_response::{json_response, ApiResponse},
         state::AppState,
     },
-    domain::repositories::{
-        boat_owner_repository::BoatOwnerRepository, user_repository::UserRepository,
+    domain::{
+        models::boat_owner::UserProfile,
+        repositories::{
+            boat_owner_repository::BoatOwnerRepository, user_repository::UserRepository,
+        },
     },
     infrastructure::repositories::sqlx_user_repository::SqlxUserRepository,
 };
 
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}/profile",
+    tag = "users",
+    responses(
+        (status = 200, description = "The user and the boats they own", body = ApiResponse<UserProfile>),
+        (status = 404, description = "User not found"),
+    ),
+)]
 pub async fn get_user_profile_query(
     State(app_state): State<AppState>,
     Path(user_id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     let user_repository = SqlxUserRepository;
     let boat_repository = BoatOwnerRepository::new(&app_state.db_pool);
 
@@ -33,9 +44,9 @@ pub async fn get_user_profile_query(
     {
         Ok(user) => user,
         Err(sqlx::Error::RowNotFound) => {
-            return row_not_found_error_json_response("User not found");
+            return Err(AppError::NotFound("User not found".to_string()));
         }
-        Err(err) => return internal_server_error_json_response(err),
+        Err(err) => return Err(err.into()),
     };
 
     // Get user's boats
@@ -51,14 +62,14 @@ pub async fn get_user_profile_query(
         }
     };
 
-    let user_profile = json!({
-        "user": user,
-        "boats": boats,
-        "boat_count": boats.len()
-    });
+    let user_profile = UserProfile {
+        user,
+        boat_count: boats.len(),
+        boats,
+    };
 
-    json_response(
+    Ok(json_response(
         StatusCode::OK,
         json!({ "success": true, "data": user_profile }),
-    )
+    ))
 }
@@ -1,18 +1,25 @@
-use axum::{extract::State, response::IntoResponse};
+use axum::{extract::State, response::Response};
 
 use crate::{
     application::{
-        http_response::{internal_server_error_json_response, ok_json_response},
+        error::AppError,
+        http_response::{ok_json_response, ApiResponse},
         state::AppState,
     },
-    domain::repositories::user_repository::UserRepository,
+    domain::{models::user::UserWithCountry, repositories::user_repository::UserRepository},
     infrastructure::repositories::sqlx_user_repository::SqlxUserRepository,
 };
 
-pub async fn get_users_query(State(app_state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/users",
+    tag = "users",
+    responses(
+        (status = 200, description = "All users", body = ApiResponse<Vec<UserWithCountry>>),
+    ),
+)]
+pub async fn get_users_query(State(app_state): State<AppState>) -> Result<Response, AppError> {
     let repository = SqlxUserRepository;
-    match repository.get_users(&app_state.db_pool).await {
-        Ok(users) => ok_json_response(users),
-        Err(err) => internal_server_error_json_response(err),
-    }
+    let users = repository.get_users(&app_state.db_pool).await?;
+    Ok(ok_json_response(users))
 }
@@ -0,0 +1,384 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::domain::models::api_key::{ApiKey, ApiKeyCreate, CreateApiKey};
+use crate::domain::models::auth::AuthUser;
+use crate::domain::models::permission_matcher;
+use crate::domain::repositories::api_key_repository::ApiKeyRepository;
+use crate::domain::repositories::permission_repository::PermissionRepository;
+use crate::domain::repositories::user_repository::UserRepository;
+use crate::infrastructure::repositories::sqlx_api_key_repository::SqlxApiKeyRepository;
+use crate::infrastructure::repositories::sqlx_permission_repository::SqlxPermissionRepository;
+use crate::infrastructure::repositories::sqlx_user_repository::SqlxUserRepository;
+
+/// Every API key starts with this, which is how the middleware tells keys and JWTs apart
+pub const API_KEY_PREFIX: &str = "wsk_";
+
+/// Random bytes in the public, stored part of a key
+const KEY_ID_BYTES: usize = 6;
+/// Random bytes in the secret part of a key (256 bits)
+const KEY_SECRET_BYTES: usize = 32;
+
+#[derive(Debug)]
+pub enum ApiKeyError {
+    InvalidKey,
+    ExpiredKey,
+    RevokedKey,
+    NotFound,
+    UnknownPermission(String),
+    /// The creator tried to grant a permission they don't hold themselves
+    PermissionNotHeld(String),
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiKeyError::InvalidKey => write!(f, "Invalid API key"),
+            ApiKeyError::ExpiredKey => write!(f, "API key has expired"),
+            ApiKeyError::RevokedKey => write!(f, "API key has been revoked"),
+            ApiKeyError::NotFound => write!(f, "API key not found"),
+            ApiKeyError::UnknownPermission(p) => write!(f, "Unknown permission: {}", p),
+            ApiKeyError::PermissionNotHeld(p) => {
+                write!(f, "Cannot grant a permission you don't have: {}", p)
+            }
+            ApiKeyError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiKeyError {}
+
+impl From<sqlx::Error> for ApiKeyError {
+    fn from(err: sqlx::Error) -> Self {
+        ApiKeyError::Database(err)
+    }
+}
+
+/// A freshly created API key. `key` is the only copy of the secret and must be
+/// handed to the caller; the database only keeps its hash.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IssuedApiKey {
+    pub key: String,
+    pub api_key: ApiKey,
+}
+
+/// An authenticated API key and the user it acts as
+#[derive(Debug, Clone)]
+pub struct ApiKeyIdentity {
+    pub api_key_id: Uuid,
+    pub user: AuthUser,
+}
+
+/// Long-lived credentials for scripts, either owned by a person or by a
+/// service account.
+///
+/// A key is limited to the permissions it was created with. Keys of a person
+/// are further limited to what that person currently holds, so losing a role
+/// also narrows their keys; service accounts have no roles and get exactly the
+/// permissions of the key. Roles are never carried by a key, so the admin role
+/// cannot widen a key's scope.
+pub struct ApiKeyService {
+    repository: SqlxApiKeyRepository,
+    permission_repository: SqlxPermissionRepository,
+    user_repository: SqlxUserRepository,
+}
+
+impl Default for ApiKeyService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiKeyService {
+    pub fn new() -> Self {
+        Self {
+            repository: SqlxApiKeyRepository,
+            permission_repository: SqlxPermissionRepository,
+            user_repository: SqlxUserRepository,
+        }
+    }
+
+    pub fn is_api_key(token: &str) -> bool {
+        token.starts_with(API_KEY_PREFIX)
+    }
+
+    /// Create a key for `user_id`. `grantable` holds the permissions of the
+    /// creator; the key can only be scoped to those.
+    pub async fn create(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        created_by: Uuid,
+        request: &CreateApiKey,
+        grantable: &[String],
+    ) -> Result<IssuedApiKey, ApiKeyError> {
+        let known: HashMap<String, Uuid> = self
+            .permission_repository
+            .get_all_permissions(pool)
+            .await?
+            .into_iter()
+            .map(|p| (p.name, p.id))
+            .collect();
+        let permission_ids = resolve_scope(&request.permissions, grantable, &known)?;
+
+        let (key, prefix) = Self::generate_key();
+        let create = ApiKeyCreate {
+            id: Uuid::new_v7(Timestamp::now(NoContext)),
+            user_id,
+            name: request.name.clone(),
+            prefix,
+            key_hash: Self::hash_key(&key),
+            created_by,
+            expires_at: request.expires_at,
+            permission_ids,
+        };
+
+        let api_key = self.repository.create_api_key(pool, &create).await?;
+
+        Ok(IssuedApiKey { key, api_key })
+    }
+
+    /// Resolve a presented key to the user it acts as, with the key's scope applied
+    pub async fn authenticate(
+        &self,
+        pool: &PgPool,
+        presented: &str,
+    ) -> Result<ApiKeyIdentity, ApiKeyError> {
+        let api_key = self
+            .repository
+            .get_api_key_by_hash(pool, &Self::hash_key(presented))
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => ApiKeyError::InvalidKey,
+                e => ApiKeyError::Database(e),
+            })?;
+
+        check_usable(&api_key, Utc::now())?;
+
+        let user = self
+            .user_repository
+            .get_user_by_id(pool, api_key.user_id)
+            .await?;
+
+        let permissions = if self
+            .repository
+            .is_service_account(pool, api_key.user_id)
+            .await?
+        {
+            api_key.permissions.clone()
+        } else {
+            let held = self
+                .user_repository
+                .get_user_with_roles(pool, api_key.user_id)
+                .await?
+                .permissions
+                .into_iter()
+                .map(|p| p.name)
+                .collect::<Vec<_>>();
+            api_key
+                .permissions
+                .iter()
+                .filter(|p| permission_matcher::any_grants(&held, p))
+                .cloned()
+                .collect()
+        };
+
+        if let Err(e) = self.repository.touch_api_key(pool, api_key.id).await {
+            tracing::warn!("Failed to record API key use: {}", e);
+        }
+
+        Ok(ApiKeyIdentity {
+            api_key_id: api_key.id,
+            user: AuthUser {
+                id: user.id,
+                email: user.email,
+                first_name: user.first_name,
+                last_name: user.last_name,
+                provider_id: user.provider_id.unwrap_or_default(),
+                provider_name: user.provider_name.unwrap_or_default(),
+                avatar_url: user.avatar_url,
+                roles: vec![],
+                permissions,
+                permission_version: 0,
+            },
+        })
+    }
+
+    pub async fn list(&self, pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiKey>, ApiKeyError> {
+        Ok(self.repository.get_api_keys_for_user(pool, user_id).await?)
+    }
+
+    pub async fn revoke(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        key_id: Uuid,
+    ) -> Result<(), ApiKeyError> {
+        match self
+            .repository
+            .revoke_api_key(pool, user_id, key_id)
+            .await?
+        {
+            0 => Err(ApiKeyError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn hash_key(key: &str) -> String {
+        format!("{:x}", Sha256::digest(key.as_bytes()))
+    }
+
+    /// A new key and its prefix: `wsk_<key id>_<secret>`, where `wsk_<key id>`
+    /// is stored in the clear
+    fn generate_key() -> (String, String) {
+        let mut key_id = [0u8; KEY_ID_BYTES];
+        let mut secret = [0u8; KEY_SECRET_BYTES];
+        rand::rng().fill_bytes(&mut key_id);
+        rand::rng().fill_bytes(&mut secret);
+
+        let key_id = key_id
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        let prefix = format!("{}{}", API_KEY_PREFIX, key_id);
+        let key = format!("{}_{}", prefix, URL_SAFE_NO_PAD.encode(secret));
+        (key, prefix)
+    }
+}
+
+/// Map the requested permission names to ids, refusing unknown permissions and
+/// permissions the creator doesn't hold
+fn resolve_scope(
+    requested: &[String],
+    grantable: &[String],
+    known: &HashMap<String, Uuid>,
+) -> Result<Vec<Uuid>, ApiKeyError> {
+    let mut ids = Vec::with_capacity(requested.len());
+    for permission in requested {
+        let id = known
+            .get(permission)
+            .ok_or_else(|| ApiKeyError::UnknownPermission(permission.clone()))?;
+        if !permission_matcher::any_grants(grantable, permission) {
+            return Err(ApiKeyError::PermissionNotHeld(permission.clone()));
+        }
+        if !ids.contains(id) {
+            ids.push(*id);
+        }
+    }
+    Ok(ids)
+}
+
+/// Decide whether a stored key may be used at `now`
+fn check_usable(key: &ApiKey, now: DateTime<Utc>) -> Result<(), ApiKeyError> {
+    if key.revoked_at.is_some() {
+        return Err(ApiKeyError::RevokedKey);
+    }
+    if key.expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err(ApiKeyError::ExpiredKey);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn create_test_key() -> ApiKey {
+        ApiKey {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            name: "nightly".to_string(),
+            prefix: "wsk_000000000000".to_string(),
+            key_hash: ApiKeyService::hash_key("key"),
+            created_by: None,
+            created_at: Utc::now(),
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+            permissions: vec!["boats:read".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_generated_keys_are_prefixed_and_unique() {
+        let (first, prefix) = ApiKeyService::generate_key();
+        let (second, _) = ApiKeyService::generate_key();
+
+        assert_ne!(first, second);
+        assert!(ApiKeyService::is_api_key(&first));
+        assert!(first.starts_with(&format!("{}_", prefix)));
+        assert_eq!(prefix.len(), API_KEY_PREFIX.len() + 2 * KEY_ID_BYTES);
+        assert_ne!(ApiKeyService::hash_key(&first), first);
+        assert!(!ApiKeyService::is_api_key("eyJhbGciOiJIUzI1NiJ9.e30.sig"));
+    }
+
+    #[test]
+    fn test_check_usable() {
+        let now = Utc::now();
+        assert!(check_usable(&create_test_key(), now).is_ok());
+
+        let mut expiring = create_test_key();
+        expiring.expires_at = Some(now + Duration::days(1));
+        assert!(check_usable(&expiring, now).is_ok());
+
+        let mut expired = create_test_key();
+        expired.expires_at = Some(now - Duration::seconds(1));
+        assert!(matches!(
+            check_usable(&expired, now),
+            Err(ApiKeyError::ExpiredKey)
+        ));
+
+        let mut revoked = create_test_key();
+        revoked.revoked_at = Some(now);
+        assert!(matches!(
+            check_usable(&revoked, now),
+            Err(ApiKeyError::RevokedKey)
+        ));
+    }
+
+    #[test]
+    fn test_resolve_scope() {
+        let boats_read = Uuid::new_v4();
+        let boats_write = Uuid::new_v4();
+        let known = HashMap::from([
+            ("boats:read".to_string(), boats_read),
+            ("boats:write".to_string(), boats_write),
+        ]);
+        let grantable = vec!["boats:read".to_string()];
+
+        let ids = resolve_scope(
+            &["boats:read".to_string(), "boats:read".to_string()],
+            &grantable,
+            &known,
+        )
+        .unwrap();
+        assert_eq!(ids, vec![boats_read]);
+
+        assert!(matches!(
+            resolve_scope(&["boats:write".to_string()], &grantable, &known),
+            Err(ApiKeyError::PermissionNotHeld(_))
+        ));
+        assert!(matches!(
+            resolve_scope(&["admin:write".to_string()], &grantable, &known),
+            Err(ApiKeyError::UnknownPermission(_))
+        ));
+
+        // Wildcard grants let the creator hand out what they cover
+        let ids = resolve_scope(
+            &["boats:write".to_string()],
+            &["boats:*".to_string()],
+            &known,
+        )
+        .unwrap();
+        assert_eq!(ids, vec![boats_write]);
+    }
+}
@@ -0,0 +1,373 @@
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use reqwest::{header::CACHE_CONTROL, Client};
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::application::services::identity_provider::IdentityProviderError;
+
+/// Lifetime used when the response carries no usable `Cache-Control: max-age`
+pub const DEFAULT_MAX_AGE_SECONDS: u64 = 300;
+/// How long stale certificates keep being served before the next fetch attempt
+pub const RETRY_INTERVAL_SECONDS: u64 = 30;
+/// How long before expiry the background task fetches the next set
+const REFRESH_AHEAD_SECONDS: u64 = 60;
+/// Minimum age of the cached set before an unknown `kid` triggers a fetch
+const UNKNOWN_KID_REFETCH_SECONDS: u64 = 10;
+
+/// Turns a response body into kid -> key mappings
+pub type ParseKeys<K> = fn(&[u8]) -> Result<HashMap<String, K>, String>;
+
+#[derive(Debug)]
+struct CachedCertificates<K> {
+    certificates: Arc<HashMap<String, K>>,
+    fetched_at: Instant,
+    expires_at: Instant,
+}
+
+impl<K> Clone for CachedCertificates<K> {
+    fn clone(&self) -> Self {
+        Self {
+            certificates: Arc::clone(&self.certificates),
+            fetched_at: self.fetched_at,
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+/// Public keys by kid (PEM certificates or JWKs) fetched from `url` and kept
+/// for as long as the response's `Cache-Control: max-age` allows.
+///
+/// When a fetch fails the previous keys keep being served, so an outage of
+/// the key endpoint does not lock users out.
+pub struct CertificateCache<K = String> {
+    url: String,
+    client: Client,
+    parse: ParseKeys<K>,
+    cached: RwLock<Option<CachedCertificates<K>>>,
+    fetch_lock: Mutex<()>,
+    unknown_kid_refetch: Duration,
+}
+
+impl CertificateCache<String> {
+    /// Cache for a JSON object of kid -> PEM certificate (Google's x509 format)
+    pub fn x509(url: String, client: Client) -> Self {
+        Self::new(url, client, parse_x509_certificates)
+    }
+}
+
+impl CertificateCache<Jwk> {
+    /// Cache for a JSON Web Key Set
+    pub fn jwks(url: String, client: Client) -> Self {
+        Self::new(url, client, parse_jwks)
+    }
+}
+
+impl<K: Clone + Send + Sync + 'static> CertificateCache<K> {
+    pub fn new(url: String, client: Client, parse: ParseKeys<K>) -> Self {
+        Self {
+            url,
+            client,
+            parse,
+            cached: RwLock::new(None),
+            fetch_lock: Mutex::new(()),
+            unknown_kid_refetch: Duration::from_secs(UNKNOWN_KID_REFETCH_SECONDS),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Look up the certificate for `kid`, fetching the set when it is missing
+    /// or expired. An unknown `kid` causes one extra fetch in case the keys
+    /// were rotated since the set was cached.
+    pub async fn get(&self, kid: &str) -> Result<Option<K>, IdentityProviderError> {
+        let (cached, fetched) = match self.current() {
+            Some(cached) if cached.expires_at > Instant::now() => (cached, false),
+            stale => (self.refresh_or_stale(stale).await?, true),
+        };
+
+        if let Some(certificate) = cached.certificates.get(kid) {
+            return Ok(Some(certificate.clone()));
+        }
+
+        if fetched || cached.fetched_at.elapsed() < self.unknown_kid_refetch {
+            return Ok(None);
+        }
+
+        let refreshed = self.refresh_or_stale(Some(cached)).await?;
+        Ok(refreshed.certificates.get(kid).cloned())
+    }
+
+    /// Keep the certificates fresh so requests never wait on the fetch
+    pub fn spawn_refresh_task(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let wait = match cache.refresh_or_stale(cache.current()).await {
+                    Ok(cached) => cached
+                        .expires_at
+                        .saturating_duration_since(Instant::now())
+                        .saturating_sub(Duration::from_secs(REFRESH_AHEAD_SECONDS))
+                        .max(Duration::from_secs(RETRY_INTERVAL_SECONDS)),
+                    Err(e) => {
+                        tracing::warn!("Failed to fetch certificates from {}: {}", cache.url, e);
+                        Duration::from_secs(RETRY_INTERVAL_SECONDS)
+                    }
+                };
+                tokio::time::sleep(wait).await;
+            }
+        })
+    }
+
+    fn current(&self) -> Option<CachedCertificates<K>> {
+        self.cached
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Fetch a new set, falling back to `stale` (and postponing the next
+    /// attempt) when the endpoint cannot be reached
+    async fn refresh_or_stale(
+        &self,
+        stale: Option<CachedCertificates<K>>,
+    ) -> Result<CachedCertificates<K>, IdentityProviderError> {
+        let _guard = self.fetch_lock.lock().await;
+
+        // Another caller may have refreshed the set while we were waiting
+        if let Some(current) = self.current() {
+            if stale
+                .as_ref()
+                .is_none_or(|stale| current.fetched_at != stale.fetched_at)
+            {
+                return Ok(current);
+            }
+        }
+
+        match self.fetch().await {
+            Ok(fetched) => {
+                self.store(fetched.clone());
+                Ok(fetched)
+            }
+            Err(e) => match stale {
+                Some(mut stale) => {
+                    tracing::warn!(
+                        "Failed to refresh certificates from {}, serving cached ones: {}",
+                        self.url,
+                        e
+                    );
+                    stale.expires_at = Instant::now() + Duration::from_secs(RETRY_INTERVAL_SECONDS);
+                    self.store(stale.clone());
+                    Ok(stale)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn fetch(&self) -> Result<CachedCertificates<K>, IdentityProviderError> {
+        let response = self.client.get(&self.url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(IdentityProviderError::TokenValidation(format!(
+                "Failed to fetch certificates: HTTP {}",
+                response.status()
+            )));
+        }
+
+        let max_age = parse_max_age(
+            response
+                .headers()
+                .get(CACHE_CONTROL)
+                .and_then(|value| value.to_str().ok()),
+        )
+        .unwrap_or(Duration::from_secs(DEFAULT_MAX_AGE_SECONDS));
+
+        let body = response.bytes().await?;
+        let certificates = (self.parse)(&body).map_err(|e| {
+            IdentityProviderError::TokenValidation(format!("Failed to parse certificates: {}", e))
+        })?;
+
+        let fetched_at = Instant::now();
+        Ok(CachedCertificates {
+            certificates: Arc::new(certificates),
+            fetched_at,
+            expires_at: fetched_at + max_age,
+        })
+    }
+
+    fn store(&self, certificates: CachedCertificates<K>) {
+        *self.cached.write().unwrap_or_else(PoisonError::into_inner) = Some(certificates);
+    }
+}
+
+/// The response is a JSON object with kid -> PEM certificate mappings
+pub fn parse_x509_certificates(body: &[u8]) -> Result<HashMap<String, String>, String> {
+    serde_json::from_slice(body).map_err(|e| e.to_string())
+}
+
+/// Keys without a kid cannot be selected by a token header and are skipped
+pub fn parse_jwks(body: &[u8]) -> Result<HashMap<String, Jwk>, String> {
+    let jwks: JwkSet = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+    Ok(jwks
+        .keys
+        .into_iter()
+        .filter_map(|jwk| Some((jwk.common.key_id.clone()?, jwk)))
+        .collect())
+}
+
+/// Extract the lifetime from a `Cache-Control` header value
+fn parse_max_age(cache_control: Option<&str>) -> Option<Duration> {
+    let cache_control = cache_control?.to_ascii_lowercase();
+    let mut directives = cache_control.split(',').map(str::trim);
+
+    if directives
+        .clone()
+        .any(|directive| directive == "no-cache" || directive == "no-store")
+    {
+        return Some(Duration::ZERO);
+    }
+
+    directives.find_map(|directive| {
+        directive
+            .strip_prefix("max-age=")?
+            .trim_matches('"')
+            .parse()
+            .ok()
+            .map(Duration::from_secs)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Local stand-in for the Google certificate endpoint
+    #[derive(Default)]
+    struct StubServer {
+        hits: AtomicUsize,
+        failing: AtomicBool,
+        max_age: AtomicUsize,
+        certificates: RwLock<HashMap<String, String>>,
+    }
+
+    impl StubServer {
+        fn set_certificates(&self, certificates: &[(&str, &str)]) {
+            *self.certificates.write().unwrap() = certificates
+                .iter()
+                .map(|(kid, pem)| (kid.to_string(), pem.to_string()))
+                .collect();
+        }
+
+        fn hits(&self) -> usize {
+            self.hits.load(Ordering::SeqCst)
+        }
+    }
+
+    async fn serve_certificates(State(stub): State<Arc<StubServer>>) -> impl IntoResponse {
+        stub.hits.fetch_add(1, Ordering::SeqCst);
+        if stub.failing.load(Ordering::SeqCst) {
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+        let cache_control = format!(
+            "public, max-age={}, must-revalidate",
+            stub.max_age.load(Ordering::SeqCst)
+        );
+        let certificates = stub.certificates.read().unwrap().clone();
+        ([(CACHE_CONTROL, cache_control)], Json(certificates)).into_response()
+    }
+
+    async fn start_stub_server(max_age: usize) -> (Arc<StubServer>, CertificateCache) {
+        let stub = Arc::new(StubServer::default());
+        stub.max_age.store(max_age, Ordering::SeqCst);
+        stub.set_certificates(&[("kid-1", "cert-1")]);
+
+        let app = axum::Router::new()
+            .route("/certs", get(serve_certificates))
+            .with_state(Arc::clone(&stub));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut cache = CertificateCache::x509(format!("http://{}/certs", address), Client::new());
+        cache.unknown_kid_refetch = Duration::ZERO;
+        (stub, cache)
+    }
+
+    #[test]
+    fn test_parse_max_age() {
+        assert_eq!(
+            parse_max_age(Some("public, max-age=19766, must-revalidate, no-transform")),
+            Some(Duration::from_secs(19766))
+        );
+        assert_eq!(
+            parse_max_age(Some("Max-Age=60")),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_max_age(Some("no-store, max-age=60")),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_max_age(Some("public")), None);
+        assert_eq!(parse_max_age(None), None);
+    }
+
+    #[tokio::test]
+    async fn test_certificates_are_cached_for_max_age() {
+        let (stub, cache) = start_stub_server(3600).await;
+
+        assert_eq!(cache.get("kid-1").await.unwrap().as_deref(), Some("cert-1"));
+        assert_eq!(cache.get("kid-1").await.unwrap().as_deref(), Some("cert-1"));
+        assert_eq!(stub.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_certificates_are_fetched_again() {
+        let (stub, cache) = start_stub_server(0).await;
+
+        cache.get("kid-1").await.unwrap();
+        cache.get("kid-1").await.unwrap();
+        assert_eq!(stub.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_kid_is_fetched_once() {
+        let (stub, cache) = start_stub_server(3600).await;
+        cache.get("kid-1").await.unwrap();
+
+        // Keys rotated after the set was cached
+        stub.set_certificates(&[("kid-1", "cert-1"), ("kid-2", "cert-2")]);
+        assert_eq!(cache.get("kid-2").await.unwrap().as_deref(), Some("cert-2"));
+        assert_eq!(stub.hits(), 2);
+
+        assert_eq!(cache.get("kid-3").await.unwrap(), None);
+        assert_eq!(stub.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stale_certificates_are_served_during_outage() {
+        let (stub, cache) = start_stub_server(0).await;
+        cache.get("kid-1").await.unwrap();
+
+        stub.failing.store(true, Ordering::SeqCst);
+        assert_eq!(cache.get("kid-1").await.unwrap().as_deref(), Some("cert-1"));
+        assert_eq!(stub.hits(), 2);
+
+        // The next attempt is postponed instead of hitting the endpoint on every request
+        assert_eq!(cache.get("kid-1").await.unwrap().as_deref(), Some("cert-1"));
+        assert_eq!(stub.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error_without_cached_certificates() {
+        let (stub, cache) = start_stub_server(3600).await;
+        stub.failing.store(true, Ordering::SeqCst);
+
+        assert!(cache.get("kid-1").await.is_err());
+    }
+}
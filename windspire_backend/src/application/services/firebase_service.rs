@@ -1,40 +1,23 @@
-use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode_header, Algorithm, DecodingKey};
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
-#[derive(Debug)]
-pub enum FirebaseError {
-    TokenValidation(String),
-    HttpError(reqwest::Error),
-    InvalidToken,
-    ProjectIdMismatch,
-    TokenExpired,
-    InvalidIssuer,
-    InvalidAudience,
-}
+use crate::application::services::certificate_cache::CertificateCache;
+use crate::application::services::identity_provider::{
+    decode_id_token, ExternalIdentity, IdentityProvider, IdentityProviderError,
+};
+use crate::application::services::test_issuer::TestIssuer;
 
-impl std::fmt::Display for FirebaseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FirebaseError::TokenValidation(msg) => write!(f, "Token validation error: {}", msg),
-            FirebaseError::HttpError(e) => write!(f, "HTTP error: {}", e),
-            FirebaseError::InvalidToken => write!(f, "Invalid token"),
-            FirebaseError::ProjectIdMismatch => write!(f, "Project ID mismatch"),
-            FirebaseError::TokenExpired => write!(f, "Token has expired"),
-            FirebaseError::InvalidIssuer => write!(f, "Invalid token issuer"),
-            FirebaseError::InvalidAudience => write!(f, "Invalid token audience"),
-        }
-    }
-}
+/// Provider name stored on users that log in through Firebase
+pub const FIREBASE_PROVIDER_NAME: &str = "firebase";
 
-impl std::error::Error for FirebaseError {}
-
-impl From<reqwest::Error> for FirebaseError {
-    fn from(err: reqwest::Error) -> Self {
-        FirebaseError::HttpError(err)
-    }
-}
+/// Where Google publishes the certificates that sign Firebase ID tokens
+pub const FIREBASE_CERTIFICATES_URL: &str =
+    "https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FirebaseTokenClaims {
@@ -58,148 +41,318 @@ pub struct FirebaseInfo {
     pub sign_in_provider: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct FirebaseUserInfo {
-    pub uid: String,
-    pub email: Option<String>,
-    pub name: Option<String>,
-    pub picture: Option<String>,
-    pub email_verified: bool,
-    pub provider: Option<String>,
+/// How ID tokens are checked, selected by `AUTH_MODE`
+enum TokenVerification {
+    /// Signed by Google; the only mode that may be used in production
+    Certificates,
+    /// Unsigned tokens from the Firebase Auth Emulator
+    Emulator,
+    /// Signed by the in-process test issuer
+    TestIssuer(Arc<TestIssuer>),
 }
 
 pub struct FirebaseService {
     project_id: String,
-    client: Client,
+    certificates: Arc<CertificateCache>,
+    verification: TokenVerification,
 }
 
 impl FirebaseService {
     pub fn new(project_id: String) -> Self {
         Self {
             project_id,
-            client: Client::new(),
+            certificates: Arc::new(CertificateCache::x509(
+                FIREBASE_CERTIFICATES_URL.to_string(),
+                Client::new(),
+            )),
+            verification: TokenVerification::Certificates,
         }
     }
 
+    /// Accept the unsigned tokens issued by the Firebase Auth Emulator
+    pub fn with_emulator(mut self) -> Self {
+        self.verification = TokenVerification::Emulator;
+        self
+    }
+
+    /// Accept tokens minted by the test issuer instead of Google's
+    pub fn with_test_issuer(mut self, test_issuer: Arc<TestIssuer>) -> Self {
+        self.verification = TokenVerification::TestIssuer(test_issuer);
+        self
+    }
+
+    /// Fetch certificates from `url` instead of Google (e.g. a local stub server)
+    pub fn with_certificates_url(mut self, url: String) -> Self {
+        self.certificates = Arc::new(CertificateCache::x509(url, Client::new()));
+        self
+    }
+
+    /// Refresh the certificates in the background before they expire
+    pub fn spawn_certificate_refresh(&self) -> tokio::task::JoinHandle<()> {
+        self.certificates.spawn_refresh_task()
+    }
+
     /// Verify Firebase ID token and extract user information
-    pub async fn verify_id_token(&self, id_token: &str) -> Result<FirebaseUserInfo, FirebaseError> {
+    async fn verify_with_certificates(
+        &self,
+        id_token: &str,
+    ) -> Result<ExternalIdentity, IdentityProviderError> {
         // Decode token header to get key ID (kid)
         let header = decode_header(id_token).map_err(|e| {
-            FirebaseError::TokenValidation(format!("Failed to decode header: {}", e))
+            IdentityProviderError::TokenValidation(format!("Failed to decode header: {}", e))
         })?;
 
-        let kid = header.kid.ok_or(FirebaseError::InvalidToken)?;
+        let kid = header.kid.ok_or(IdentityProviderError::InvalidToken)?;
 
-        // Get public key certificates from Google
-        let certs = self.get_firebase_certificates().await?;
-        let cert_pem = certs.get(&kid).ok_or(FirebaseError::TokenValidation(
-            "Key ID not found in certificates".to_string(),
-        ))?;
+        // Get the public key certificate for this kid (cached per Cache-Control)
+        let cert_pem =
+            self.certificates
+                .get(&kid)
+                .await?
+                .ok_or(IdentityProviderError::TokenValidation(
+                    "Key ID not found in certificates".to_string(),
+                ))?;
 
         // Create RSA decoding key from the PEM certificate
         let decoding_key = DecodingKey::from_rsa_pem(cert_pem.as_bytes()).map_err(|e| {
-            FirebaseError::TokenValidation(format!(
+            IdentityProviderError::TokenValidation(format!(
                 "Failed to create decoding key from certificate: {}",
                 e
             ))
         })?;
 
-        // Set up validation
-        let mut validation = Validation::new(Algorithm::RS256);
-        validation.set_issuer(&[format!(
-            "https://securetoken.google.com/{}",
-            self.project_id
-        )]);
-        validation.set_audience(std::slice::from_ref(&self.project_id));
-
         // Decode and validate token
-        let token_data = decode::<FirebaseTokenClaims>(id_token, &decoding_key, &validation)
-            .map_err(|e| match e.kind() {
-                jsonwebtoken::errors::ErrorKind::ExpiredSignature => FirebaseError::TokenExpired,
-                jsonwebtoken::errors::ErrorKind::InvalidIssuer => FirebaseError::InvalidIssuer,
-                jsonwebtoken::errors::ErrorKind::InvalidAudience => FirebaseError::InvalidAudience,
-                _ => FirebaseError::TokenValidation(format!("Token validation failed: {}", e)),
-            })?;
-
-        // Convert to our FirebaseUserInfo struct
-        let user_info = FirebaseUserInfo {
-            uid: token_data.claims.user_id,
-            email: token_data.claims.email,
-            name: token_data.claims.name,
-            picture: token_data.claims.picture,
-            email_verified: token_data.claims.email_verified.unwrap_or(false),
-            provider: token_data.claims.firebase.and_then(|f| f.sign_in_provider),
-        };
+        let claims: FirebaseTokenClaims = decode_id_token(
+            id_token,
+            &decoding_key,
+            Algorithm::RS256,
+            &self.issuer(),
+            &self.project_id,
+        )?;
 
-        Ok(user_info)
+        Ok(claims.into())
     }
 
-    /// Fetch Firebase public key certificates from Google (PEM format)
-    async fn get_firebase_certificates(&self) -> Result<HashMap<String, String>, FirebaseError> {
-        let url = "https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com";
-
-        let response = self.client.get(url).send().await?;
+    /// The emulator signs nothing (`"alg": "none"`), so only the claims can be
+    /// checked. jsonwebtoken refuses unsigned tokens, hence the manual parsing.
+    fn verify_emulator_token(
+        &self,
+        id_token: &str,
+    ) -> Result<ExternalIdentity, IdentityProviderError> {
+        let mut segments = id_token.split('.');
+        let (Some(header), Some(payload), Some(""), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return Err(IdentityProviderError::InvalidToken);
+        };
 
-        if !response.status().is_success() {
-            return Err(FirebaseError::TokenValidation(format!(
-                "Failed to fetch certificates: HTTP {}",
-                response.status()
-            )));
+        let header: serde_json::Value = decode_segment(header)?;
+        if header["alg"] != "none" {
+            return Err(IdentityProviderError::TokenValidation(
+                "Expected an unsigned emulator token".to_string(),
+            ));
         }
 
-        // The response is a JSON object with kid -> PEM certificate mappings
-        let certs: HashMap<String, String> = response.json().await.map_err(|e| {
-            FirebaseError::TokenValidation(format!("Failed to parse certificates: {}", e))
-        })?;
+        let claims: FirebaseTokenClaims = decode_segment(payload)?;
+        if claims.iss != self.issuer() {
+            return Err(IdentityProviderError::InvalidIssuer);
+        }
+        if claims.aud != self.project_id {
+            return Err(IdentityProviderError::InvalidAudience);
+        }
+        if claims.exp <= chrono::Utc::now().timestamp() {
+            return Err(IdentityProviderError::TokenExpired);
+        }
 
-        Ok(certs)
+        Ok(claims.into())
     }
 
-    /// Simple token validation without cryptographic verification (for development/testing)
-    /// WARNING: This should NOT be used in production!
-    #[cfg(debug_assertions)]
-    pub async fn verify_id_token_unsafe(
+    fn verify_test_issuer_token(
         &self,
+        test_issuer: &TestIssuer,
         id_token: &str,
-    ) -> Result<FirebaseUserInfo, FirebaseError> {
-        // Decode without verification (UNSAFE - only for development)
-        let mut validation = Validation::new(Algorithm::RS256);
-        validation.insecure_disable_signature_validation();
-        validation.set_issuer(&[format!(
-            "https://securetoken.google.com/{}",
-            self.project_id
-        )]);
-        validation.set_audience(std::slice::from_ref(&self.project_id));
-
-        let token_data = decode::<FirebaseTokenClaims>(
+    ) -> Result<ExternalIdentity, IdentityProviderError> {
+        let key = test_issuer.key();
+        let claims: FirebaseTokenClaims = decode_id_token(
             id_token,
-            &DecodingKey::from_secret(&[]), // Empty key since we're not validating
-            &validation,
-        )
-        .map_err(|e| FirebaseError::TokenValidation(format!("Token parsing failed: {}", e)))?;
-
-        let user_info = FirebaseUserInfo {
-            uid: token_data.claims.user_id,
-            email: token_data.claims.email,
-            name: token_data.claims.name,
-            picture: token_data.claims.picture,
-            email_verified: token_data.claims.email_verified.unwrap_or(false),
-            provider: token_data.claims.firebase.and_then(|f| f.sign_in_provider),
-        };
+            key.decoding_key(),
+            key.algorithm,
+            &self.issuer(),
+            &self.project_id,
+        )?;
+
+        Ok(claims.into())
+    }
 
-        Ok(user_info)
+    fn issuer(&self) -> String {
+        format!("https://securetoken.google.com/{}", self.project_id)
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for FirebaseService {
+    fn name(&self) -> &str {
+        FIREBASE_PROVIDER_NAME
+    }
+
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+    ) -> Result<ExternalIdentity, IdentityProviderError> {
+        match &self.verification {
+            TokenVerification::Certificates => self.verify_with_certificates(id_token).await,
+            TokenVerification::Emulator => self.verify_emulator_token(id_token),
+            TokenVerification::TestIssuer(test_issuer) => {
+                self.verify_test_issuer_token(test_issuer, id_token)
+            }
+        }
+    }
+}
+
+fn decode_segment<T: DeserializeOwned>(segment: &str) -> Result<T, IdentityProviderError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|_| IdentityProviderError::InvalidToken)?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        IdentityProviderError::TokenValidation(format!("Failed to parse token: {}", e))
+    })
+}
+
+impl From<FirebaseTokenClaims> for ExternalIdentity {
+    fn from(claims: FirebaseTokenClaims) -> Self {
+        ExternalIdentity {
+            subject: claims.user_id,
+            email: claims.email,
+            email_verified: claims.email_verified.unwrap_or(false),
+            name: claims.name,
+            picture: claims.picture,
+            sign_in_method: claims.firebase.and_then(|f| f.sign_in_provider),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::application::services::test_issuer::TestIdTokenRequest;
 
     #[test]
     fn test_firebase_service_creation() {
         let service = FirebaseService::new("test-project".to_string());
         assert_eq!(service.project_id, "test-project");
+        assert_eq!(service.certificates.url(), FIREBASE_CERTIFICATES_URL);
+    }
+
+    #[test]
+    fn test_certificates_url_override() {
+        let service = FirebaseService::new("test-project".to_string())
+            .with_certificates_url("http://127.0.0.1:9099/certs".to_string());
+        assert_eq!(service.certificates.url(), "http://127.0.0.1:9099/certs");
+    }
+
+    fn encode_segment(value: &serde_json::Value) -> String {
+        URL_SAFE_NO_PAD.encode(value.to_string())
+    }
+
+    fn create_emulator_token(audience: &str, exp_offset: i64) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = serde_json::json!({
+            "iss": "https://securetoken.google.com/test-project",
+            "aud": audience,
+            "auth_time": now,
+            "user_id": "emulator-user",
+            "sub": "emulator-user",
+            "iat": now,
+            "exp": now + exp_offset,
+            "email": "emulator@example.com",
+            "email_verified": true,
+        });
+        format!(
+            "{}.{}.",
+            encode_segment(&serde_json::json!({ "alg": "none", "typ": "JWT" })),
+            encode_segment(&claims)
+        )
+    }
+
+    #[tokio::test]
+    async fn test_emulator_token_accepted_in_emulator_mode() {
+        let service = FirebaseService::new("test-project".to_string()).with_emulator();
+
+        let identity = service
+            .verify_id_token(&create_emulator_token("test-project", 3600))
+            .await
+            .unwrap();
+        assert_eq!(identity.subject, "emulator-user");
+        assert!(identity.email_verified);
+    }
+
+    #[tokio::test]
+    async fn test_emulator_token_claims_checked() {
+        let service = FirebaseService::new("test-project".to_string()).with_emulator();
+
+        assert!(matches!(
+            service
+                .verify_id_token(&create_emulator_token("other-project", 3600))
+                .await,
+            Err(IdentityProviderError::InvalidAudience)
+        ));
+        assert!(matches!(
+            service
+                .verify_id_token(&create_emulator_token("test-project", -60))
+                .await,
+            Err(IdentityProviderError::TokenExpired)
+        ));
     }
 
-    // Add more tests here for token validation logic
+    #[tokio::test]
+    async fn test_unsigned_token_rejected_in_production_mode() {
+        let service = FirebaseService::new("test-project".to_string())
+            .with_certificates_url("http://127.0.0.1:9/certs".to_string());
+
+        assert!(service
+            .verify_id_token(&create_emulator_token("test-project", 3600))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_test_issuer_tokens() {
+        let test_issuer = Arc::new(TestIssuer::new("test-project".to_string()));
+        let service =
+            FirebaseService::new("test-project".to_string()).with_test_issuer(test_issuer.clone());
+
+        let id_token = test_issuer
+            .issue_id_token(&TestIdTokenRequest {
+                uid: "test-user".to_string(),
+                email: Some("test@example.com".to_string()),
+                name: Some("Test User".to_string()),
+                email_verified: None,
+            })
+            .unwrap();
+
+        let identity = service.verify_id_token(&id_token).await.unwrap();
+        assert_eq!(identity.subject, "test-user");
+        assert_eq!(identity.name.as_deref(), Some("Test User"));
+
+        // Tokens from another test issuer instance are not trusted
+        let other_issuer = TestIssuer::new("test-project".to_string());
+        let forged = other_issuer
+            .issue_id_token(&TestIdTokenRequest {
+                uid: "test-user".to_string(),
+                email: None,
+                name: None,
+                email_verified: None,
+            })
+            .unwrap();
+        assert!(service.verify_id_token(&forged).await.is_err());
+
+        // Emulator tokens are not accepted by the test issuer either
+        assert!(service
+            .verify_id_token(&create_emulator_token("test-project", 3600))
+            .await
+            .is_err());
+    }
 }
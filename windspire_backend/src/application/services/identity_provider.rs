@@ -0,0 +1,202 @@
+use async_trait::async_trait;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum IdentityProviderError {
+    TokenValidation(String),
+    HttpError(reqwest::Error),
+    InvalidToken,
+    TokenExpired,
+    InvalidIssuer,
+    InvalidAudience,
+}
+
+impl std::fmt::Display for IdentityProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentityProviderError::TokenValidation(msg) => {
+                write!(f, "Token validation error: {}", msg)
+            }
+            IdentityProviderError::HttpError(e) => write!(f, "HTTP error: {}", e),
+            IdentityProviderError::InvalidToken => write!(f, "Invalid token"),
+            IdentityProviderError::TokenExpired => write!(f, "Token has expired"),
+            IdentityProviderError::InvalidIssuer => write!(f, "Invalid token issuer"),
+            IdentityProviderError::InvalidAudience => write!(f, "Invalid token audience"),
+        }
+    }
+}
+
+impl std::error::Error for IdentityProviderError {}
+
+impl From<reqwest::Error> for IdentityProviderError {
+    fn from(err: reqwest::Error) -> Self {
+        IdentityProviderError::HttpError(err)
+    }
+}
+
+/// A user as asserted by a verified ID token from an external identity provider
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    /// Stable user id at the provider (the token's subject)
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    /// How the user signed in at the provider (e.g. "google.com"), when known
+    pub sign_in_method: Option<String>,
+}
+
+impl ExternalIdentity {
+    /// The email, only if the provider has verified the user controls it. Only
+    /// a verified email may be used to match the identity to an existing user.
+    pub fn verified_email(&self) -> Option<&str> {
+        self.email.as_deref().filter(|_| self.email_verified)
+    }
+
+    /// Whether the provider has verified that the user controls `email`
+    pub fn verifies_email(&self, email: &str) -> bool {
+        self.verified_email()
+            .is_some_and(|verified| verified.eq_ignore_ascii_case(email))
+    }
+}
+
+/// Something that can vouch for a user by verifying an ID token it issued
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Name stored as the user's `provider_name`; also used in the login route
+    fn name(&self) -> &str;
+
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+    ) -> Result<ExternalIdentity, IdentityProviderError>;
+}
+
+/// The identity providers users can log in with, keyed by name
+#[derive(Default)]
+pub struct IdentityProviders {
+    providers: HashMap<String, Arc<dyn IdentityProvider>>,
+}
+
+impl IdentityProviders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, provider: Arc<dyn IdentityProvider>) -> Self {
+        self.providers.insert(provider.name().to_string(), provider);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn IdentityProvider>> {
+        self.providers.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.providers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Signature algorithms accepted for ID tokens. Symmetric algorithms are never
+/// accepted since a public key set cannot vouch for them.
+pub fn is_asymmetric(algorithm: Algorithm) -> bool {
+    !matches!(
+        algorithm,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    )
+}
+
+/// Verify the signature, issuer, audience and expiry of an ID token
+pub fn decode_id_token<C: DeserializeOwned>(
+    id_token: &str,
+    key: &DecodingKey,
+    algorithm: Algorithm,
+    issuer: &str,
+    audience: &str,
+) -> Result<C, IdentityProviderError> {
+    let mut validation = Validation::new(algorithm);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[audience]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    decode::<C>(id_token, key, &validation)
+        .map(|token_data| token_data.claims)
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
+                IdentityProviderError::TokenExpired
+            }
+            jsonwebtoken::errors::ErrorKind::InvalidIssuer => IdentityProviderError::InvalidIssuer,
+            jsonwebtoken::errors::ErrorKind::InvalidAudience => {
+                IdentityProviderError::InvalidAudience
+            }
+            _ => IdentityProviderError::TokenValidation(format!("Token validation failed: {}", e)),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NamedProvider(&'static str);
+
+    #[async_trait]
+    impl IdentityProvider for NamedProvider {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        async fn verify_id_token(
+            &self,
+            _id_token: &str,
+        ) -> Result<ExternalIdentity, IdentityProviderError> {
+            Err(IdentityProviderError::InvalidToken)
+        }
+    }
+
+    #[test]
+    fn test_identity_providers_lookup() {
+        let providers = IdentityProviders::new()
+            .register(Arc::new(NamedProvider("keycloak")))
+            .register(Arc::new(NamedProvider("firebase")));
+
+        assert_eq!(providers.names(), vec!["firebase", "keycloak"]);
+        assert_eq!(providers.get("keycloak").unwrap().name(), "keycloak");
+        assert!(providers.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_verified_email() {
+        let mut identity = ExternalIdentity {
+            subject: "subject".to_string(),
+            email: Some("sailor@example.com".to_string()),
+            email_verified: false,
+            name: None,
+            picture: None,
+            sign_in_method: None,
+        };
+        assert_eq!(identity.verified_email(), None);
+
+        assert!(!identity.verifies_email("sailor@example.com"));
+
+        identity.email_verified = true;
+        assert_eq!(identity.verified_email(), Some("sailor@example.com"));
+        assert!(identity.verifies_email("Sailor@Example.com"));
+        assert!(!identity.verifies_email("skipper@example.com"));
+
+        identity.email = None;
+        assert_eq!(identity.verified_email(), None);
+    }
+
+    #[test]
+    fn test_symmetric_algorithms_rejected() {
+        assert!(is_asymmetric(Algorithm::RS256));
+        assert!(is_asymmetric(Algorithm::EdDSA));
+        assert!(!is_asymmetric(Algorithm::HS256));
+    }
+}
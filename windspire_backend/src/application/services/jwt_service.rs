@@ -1,13 +1,20 @@
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
 
 use crate::application::config::JwtConfig;
-use crate::domain::models::auth::{AuthUser, Claims};
+use crate::application::services::signing_keys::{RetiredKeySpec, SigningKey};
+use crate::domain::models::auth::{Actor, AuthUser, Claims};
+use crate::domain::models::permission_matcher;
 
 #[derive(Debug)]
 pub enum JwtError {
     TokenCreation(jsonwebtoken::errors::Error),
     TokenValidation(jsonwebtoken::errors::Error),
+    KeyConfiguration(String),
     InvalidToken,
     ExpiredToken,
 }
@@ -17,6 +24,7 @@ impl std::fmt::Display for JwtError {
         match self {
             JwtError::TokenCreation(e) => write!(f, "Token creation error: {}", e),
             JwtError::TokenValidation(e) => write!(f, "Token validation error: {}", e),
+            JwtError::KeyConfiguration(msg) => write!(f, "Key configuration error: {}", msg),
             JwtError::InvalidToken => write!(f, "Invalid token"),
             JwtError::ExpiredToken => write!(f, "Token has expired"),
         }
@@ -25,93 +33,238 @@ impl std::fmt::Display for JwtError {
 
 impl std::error::Error for JwtError {}
 
+enum Keys {
+    /// HS256 with `JWT_SECRET`; only this service can verify tokens
+    Secret {
+        encoding_key: EncodingKey,
+        decoding_key: DecodingKey,
+    },
+    /// RS256/EdDSA; anyone can verify tokens with the keys published as JWKS
+    Asymmetric {
+        active: SigningKey,
+        retired: Vec<SigningKey>,
+    },
+}
+
 pub struct JwtService {
     config: JwtConfig,
-    encoding_key: EncodingKey,
-    decoding_key: DecodingKey,
+    keys: Keys,
 }
 
 impl JwtService {
-    pub fn new(config: JwtConfig) -> Self {
-        let encoding_key = EncodingKey::from_secret(config.secret.as_ref());
-        let decoding_key = DecodingKey::from_secret(config.secret.as_ref());
-
-        Self {
-            config,
-            encoding_key,
-            decoding_key,
-        }
+    pub fn new(config: JwtConfig) -> Result<Self, JwtError> {
+        let algorithm: Algorithm = config.algorithm.parse().map_err(|_| {
+            JwtError::KeyConfiguration(format!("Unknown algorithm {}", config.algorithm))
+        })?;
+
+        let keys = match algorithm {
+            Algorithm::HS256 => Keys::Secret {
+                encoding_key: EncodingKey::from_secret(config.secret.as_ref()),
+                decoding_key: DecodingKey::from_secret(config.secret.as_ref()),
+            },
+            Algorithm::RS256 | Algorithm::EdDSA => {
+                let key_file = config.signing_key_file.as_deref().ok_or_else(|| {
+                    JwtError::KeyConfiguration(format!(
+                        "JWT_SIGNING_KEY_FILE is required for {:?}",
+                        algorithm
+                    ))
+                })?;
+                let active = load_signing_key(&config.signing_key_id, algorithm, key_file)?;
+
+                let mut retired = Vec::new();
+                for spec in &config.retired_keys {
+                    let spec = RetiredKeySpec::parse(spec).map_err(JwtError::KeyConfiguration)?;
+                    retired.push(
+                        load_signing_key(&spec.kid, algorithm, &spec.key_file)?
+                            .retired(spec.retired_at),
+                    );
+                }
+
+                Keys::Asymmetric { active, retired }
+            }
+            other => {
+                return Err(JwtError::KeyConfiguration(format!(
+                    "Unsupported algorithm {:?}",
+                    other
+                )))
+            }
+        };
+
+        Ok(Self { config, keys })
     }
 
     pub fn generate_token(&self, user: &AuthUser) -> Result<String, JwtError> {
+        let claims = self.claims_for(user, Duration::hours(self.config.expiration_hours), None);
+        self.sign(&claims)
+    }
+
+    /// A token for a login, tied to its session so ending the session revokes it
+    pub fn generate_session_token(
+        &self,
+        user: &AuthUser,
+        session_id: Uuid,
+    ) -> Result<String, JwtError> {
+        let mut claims = self.claims_for(user, Duration::hours(self.config.expiration_hours), None);
+        claims.sid = Some(session_id.to_string());
+        self.sign(&claims)
+    }
+
+    /// A short-lived token for `actor` to act as `user`. Its `act` claim keeps
+    /// the two apart, so requests made with it can be told and audited.
+    pub fn generate_impersonation_token(
+        &self,
+        user: &AuthUser,
+        actor: Actor,
+    ) -> Result<(String, Claims), JwtError> {
+        let claims = self.claims_for(
+            user,
+            Duration::minutes(self.config.impersonation_expiration_minutes),
+            Some(actor),
+        );
+        Ok((self.sign(&claims)?, claims))
+    }
+
+    fn claims_for(&self, user: &AuthUser, lifetime: Duration, act: Option<Actor>) -> Claims {
         let now = Utc::now();
-        let expiration = now + Duration::hours(self.config.expiration_hours);
+        let expiration = now + lifetime;
 
-        let claims = Claims {
+        Claims {
             sub: user.id.to_string(),
             email: user.email.clone(),
             name: format!("{} {}", user.first_name, user.last_name),
             picture: user.avatar_url.clone(),
             roles: user.roles.clone(),
-            permissions: user.permissions.clone(),
+            // Wildcard grants make the permissions they cover redundant
+            permissions: permission_matcher::compact(&user.permissions),
+            pv: user.permission_version,
+            act,
+            sid: None,
+            iss: self.config.issuer.clone(),
+            aud: self.config.audience.clone(),
+            jti: Uuid::new_v4().to_string(),
             iat: now.timestamp(),
+            nbf: now.timestamp(),
             exp: expiration.timestamp(),
-        };
+        }
+    }
 
-        let header = Header::new(Algorithm::HS256);
+    fn sign(&self, claims: &Claims) -> Result<String, JwtError> {
+        let (header, encoding_key) = match &self.keys {
+            Keys::Secret { encoding_key, .. } => (Header::new(Algorithm::HS256), encoding_key),
+            Keys::Asymmetric { active, .. } => {
+                let mut header = Header::new(active.algorithm);
+                header.kid = Some(active.kid.clone());
+                (header, active.encoding_key())
+            }
+        };
 
-        encode(&header, &claims, &self.encoding_key).map_err(JwtError::TokenCreation)
+        encode(&header, claims, encoding_key).map_err(JwtError::TokenCreation)
     }
 
     pub fn validate_token(&self, token: &str) -> Result<Claims, JwtError> {
-        let mut validation = Validation::new(Algorithm::HS256);
+        let (algorithm, decoding_key) = match &self.keys {
+            Keys::Secret { decoding_key, .. } => (Algorithm::HS256, decoding_key),
+            Keys::Asymmetric { .. } => {
+                let header = decode_header(token).map_err(JwtError::TokenValidation)?;
+                let kid = header.kid.ok_or(JwtError::InvalidToken)?;
+                let key = self
+                    .verification_keys(Utc::now())
+                    .find(|key| key.kid == kid)
+                    .ok_or(JwtError::InvalidToken)?;
+                (key.algorithm, key.decoding_key())
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
         validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[&self.config.audience]);
+        validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud", "sub"]);
+        validation.validate_nbf = true;
 
-        let token_data = decode::<Claims>(token, &self.decoding_key, &validation).map_err(|e| {
-            match e.kind() {
+        let token_data =
+            decode::<Claims>(token, decoding_key, &validation).map_err(|e| match e.kind() {
                 jsonwebtoken::errors::ErrorKind::ExpiredSignature => JwtError::ExpiredToken,
                 _ => JwtError::TokenValidation(e),
-            }
-        })?;
+            })?;
 
         Ok(token_data.claims)
     }
 
-    pub fn extract_bearer_token(auth_header: &str) -> Option<&str> {
-        auth_header.strip_prefix("Bearer ")
+    /// Public keys for `/.well-known/jwks.json`. Empty with HS256, since the
+    /// secret must never be published.
+    pub fn jwks(&self) -> Value {
+        let keys: Vec<&Value> = self
+            .verification_keys(Utc::now())
+            .map(SigningKey::jwk)
+            .collect();
+        json!({ "keys": keys })
     }
 
-    pub fn refresh_token(&self, claims: &Claims) -> Result<String, JwtError> {
-        let now = Utc::now();
-        let expiration = now + Duration::hours(self.config.expiration_hours);
-
-        let new_claims = Claims {
-            sub: claims.sub.clone(),
-            email: claims.email.clone(),
-            name: claims.name.clone(),
-            picture: claims.picture.clone(),
-            roles: claims.roles.clone(),
-            permissions: claims.permissions.clone(),
-            iat: now.timestamp(),
-            exp: expiration.timestamp(),
+    /// The active key plus retired keys still inside their grace period
+    fn verification_keys(&self, now: DateTime<Utc>) -> impl Iterator<Item = &SigningKey> {
+        let grace_period = Duration::hours(self.config.retired_key_grace_hours);
+        let (active, retired) = match &self.keys {
+            Keys::Secret { .. } => (None, &[][..]),
+            Keys::Asymmetric { active, retired } => (Some(active), &retired[..]),
         };
 
-        let header = Header::new(Algorithm::HS256);
+        active.into_iter().chain(
+            retired
+                .iter()
+                .filter(move |key| key.retired_at.is_some_and(|at| now < at + grace_period)),
+        )
+    }
 
-        encode(&header, &new_claims, &self.encoding_key).map_err(JwtError::TokenCreation)
+    pub fn extract_bearer_token(auth_header: &str) -> Option<&str> {
+        auth_header.strip_prefix("Bearer ")
     }
 }
 
+fn load_signing_key(
+    kid: &str,
+    algorithm: Algorithm,
+    key_file: &str,
+) -> Result<SigningKey, JwtError> {
+    let pem = std::fs::read(key_file).map_err(|e| {
+        JwtError::KeyConfiguration(format!("Failed to read key file {}: {}", key_file, e))
+    })?;
+    SigningKey::from_pem(kid, algorithm, &pem)
+        .map_err(|e| JwtError::KeyConfiguration(format!("Key {}: {}", kid, e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use uuid::Uuid;
+    use crate::application::services::signing_keys::generate_ed25519_pem;
 
     fn create_test_config() -> JwtConfig {
         JwtConfig {
             secret: "test-secret-key".to_string(),
             expiration_hours: 1,
+            impersonation_expiration_minutes: 15,
             issuer: "test".to_string(),
+            audience: "test-api".to_string(),
+            refresh_token_expiration_days: 30,
+            algorithm: "HS256".to_string(),
+            signing_key_id: "default".to_string(),
+            signing_key_file: None,
+            retired_keys: vec![],
+            retired_key_grace_hours: 1,
+        }
+    }
+
+    fn write_test_key() -> String {
+        let path = std::env::temp_dir().join(format!("windspire-test-{}.pem", Uuid::new_v4()));
+        std::fs::write(&path, generate_ed25519_pem()).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn create_eddsa_config(kid: &str, key_file: &str) -> JwtConfig {
+        JwtConfig {
+            algorithm: "EdDSA".to_string(),
+            signing_key_id: kid.to_string(),
+            signing_key_file: Some(key_file.to_string()),
+            ..create_test_config()
         }
     }
 
@@ -126,13 +279,14 @@ mod tests {
             avatar_url: Some("https://example.com/avatar.jpg".to_string()),
             roles: vec!["user".to_string()],
             permissions: vec!["users:read_own".to_string()],
+            permission_version: 0,
         }
     }
 
     #[test]
     fn test_generate_and_validate_token() {
         let config = create_test_config();
-        let jwt_service = JwtService::new(config);
+        let jwt_service = JwtService::new(config).unwrap();
         let user = create_test_user();
 
         let token = jwt_service.generate_token(&user).unwrap();
@@ -141,6 +295,145 @@ mod tests {
         assert_eq!(claims.email, user.email);
         assert_eq!(claims.roles, user.roles);
         assert_eq!(claims.permissions, user.permissions);
+        assert_eq!(claims.iss, "test");
+        assert_eq!(claims.aud, "test-api");
+    }
+
+    #[test]
+    fn test_impersonation_token_carries_actor() {
+        let jwt_service = JwtService::new(create_test_config()).unwrap();
+        let user = create_test_user();
+        let actor = Actor {
+            sub: Uuid::new_v4().to_string(),
+            email: "support@example.com".to_string(),
+        };
+
+        let (token, issued) = jwt_service
+            .generate_impersonation_token(&user, actor.clone())
+            .unwrap();
+        let claims = jwt_service.validate_token(&token).unwrap();
+
+        assert_eq!(claims.sub, user.id.to_string());
+        assert_eq!(claims.act, Some(actor));
+        assert_eq!(claims.jti, issued.jti);
+        assert_eq!(claims.exp - claims.iat, 15 * 60);
+
+        // Regular tokens carry no actor at all
+        let token = jwt_service.generate_token(&user).unwrap();
+        assert!(!token_payload(&token).contains("\"act\""));
+        assert_eq!(jwt_service.validate_token(&token).unwrap().act, None);
+    }
+
+    #[test]
+    fn test_session_token_carries_session_id() {
+        let jwt_service = JwtService::new(create_test_config()).unwrap();
+        let session_id = Uuid::new_v4();
+
+        let token = jwt_service
+            .generate_session_token(&create_test_user(), session_id)
+            .unwrap();
+
+        assert_eq!(
+            jwt_service.validate_token(&token).unwrap().sid,
+            Some(session_id.to_string())
+        );
+    }
+
+    fn token_payload(token: &str) -> String {
+        use base64::Engine;
+        let payload = token.split('.').nth(1).unwrap();
+        String::from_utf8(
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(payload)
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_claims_carry_compacted_permissions() {
+        let jwt_service = JwtService::new(create_test_config()).unwrap();
+        let mut user = create_test_user();
+        user.permissions = vec![
+            "boats:read".to_string(),
+            "boats:*".to_string(),
+            "users:read_own".to_string(),
+        ];
+
+        let token = jwt_service.generate_token(&user).unwrap();
+        let claims = jwt_service.validate_token(&token).unwrap();
+
+        assert_eq!(claims.permissions, vec!["boats:*", "users:read_own"]);
+    }
+
+    #[test]
+    fn test_wrong_audience_rejected() {
+        let jwt_service = JwtService::new(create_test_config()).unwrap();
+        let other_service = JwtService::new(JwtConfig {
+            audience: "other-api".to_string(),
+            ..create_test_config()
+        })
+        .unwrap();
+
+        let token = other_service.generate_token(&create_test_user()).unwrap();
+        assert!(jwt_service.validate_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_eddsa_token_carries_kid() {
+        let key_file = write_test_key();
+        let jwt_service = JwtService::new(create_eddsa_config("key-1", &key_file)).unwrap();
+
+        let token = jwt_service.generate_token(&create_test_user()).unwrap();
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::EdDSA);
+        assert_eq!(header.kid.as_deref(), Some("key-1"));
+        assert!(jwt_service.validate_token(&token).is_ok());
+
+        let jwks = jwt_service.jwks();
+        assert_eq!(jwks["keys"].as_array().unwrap().len(), 1);
+        assert_eq!(jwks["keys"][0]["kid"], "key-1");
+    }
+
+    #[test]
+    fn test_retired_key_grace_period() {
+        let old_key_file = write_test_key();
+        let new_key_file = write_test_key();
+        let old_service = JwtService::new(create_eddsa_config("key-1", &old_key_file)).unwrap();
+        let token = old_service.generate_token(&create_test_user()).unwrap();
+
+        // Rotated just now: tokens signed with the old key are still accepted
+        let rotated = JwtService::new(JwtConfig {
+            retired_keys: vec![format!(
+                "key-1={}@{}",
+                old_key_file,
+                Utc::now().to_rfc3339()
+            )],
+            ..create_eddsa_config("key-2", &new_key_file)
+        })
+        .unwrap();
+        assert!(rotated.validate_token(&token).is_ok());
+        assert_eq!(rotated.jwks()["keys"].as_array().unwrap().len(), 2);
+
+        // Rotated longer ago than the grace period: the old key is gone
+        let retired_at = Utc::now() - Duration::hours(2);
+        let expired = JwtService::new(JwtConfig {
+            retired_keys: vec![format!(
+                "key-1={}@{}",
+                old_key_file,
+                retired_at.to_rfc3339()
+            )],
+            ..create_eddsa_config("key-2", &new_key_file)
+        })
+        .unwrap();
+        assert!(expired.validate_token(&token).is_err());
+        assert_eq!(expired.jwks()["keys"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_hs256_publishes_no_keys() {
+        let jwt_service = JwtService::new(create_test_config()).unwrap();
+        assert!(jwt_service.jwks()["keys"].as_array().unwrap().is_empty());
     }
 
     #[test]
@@ -1,2 +1,13 @@
+pub mod api_key_service;
+pub mod certificate_cache;
 pub mod firebase_service;
+pub mod identity_provider;
 pub mod jwt_service;
+pub mod oidc_provider;
+pub mod permission_version_service;
+pub mod rate_limit_service;
+pub mod refresh_token_service;
+pub mod session_service;
+pub mod signing_keys;
+pub mod test_issuer;
+pub mod token_revocation_service;
@@ -0,0 +1,354 @@
+use async_trait::async_trait;
+use jsonwebtoken::{decode_header, jwk::Jwk, DecodingKey};
+use reqwest::Client;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::application::config::OidcProviderConfig;
+use crate::application::services::certificate_cache::CertificateCache;
+use crate::application::services::identity_provider::{
+    decode_id_token, is_asymmetric, ExternalIdentity, IdentityProvider, IdentityProviderError,
+};
+
+/// The parts of `.well-known/openid-configuration` needed to verify ID tokens
+#[derive(Debug, Deserialize)]
+struct OpenIdConfiguration {
+    issuer: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub picture: Option<String>,
+}
+
+/// Any OpenID Connect provider (Keycloak, a federation SSO, ...). The JWKS URL
+/// is discovered on first use, so the provider may be unavailable at startup.
+pub struct OidcProvider {
+    name: String,
+    issuer: String,
+    audience: String,
+    discovery_url: String,
+    client: Client,
+    keys: OnceCell<Arc<CertificateCache<Jwk>>>,
+}
+
+impl OidcProvider {
+    pub fn new(config: &OidcProviderConfig) -> Self {
+        let discovery_url = config.discovery_url.clone().unwrap_or_else(|| {
+            format!(
+                "{}/.well-known/openid-configuration",
+                config.issuer.trim_end_matches('/')
+            )
+        });
+
+        Self {
+            name: config.name.clone(),
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            discovery_url,
+            client: Client::new(),
+            keys: OnceCell::new(),
+        }
+    }
+
+    async fn keys(&self) -> Result<&Arc<CertificateCache<Jwk>>, IdentityProviderError> {
+        self.keys
+            .get_or_try_init(|| async {
+                let response = self.client.get(&self.discovery_url).send().await?;
+
+                if !response.status().is_success() {
+                    return Err(IdentityProviderError::TokenValidation(format!(
+                        "Failed to fetch OpenID configuration: HTTP {}",
+                        response.status()
+                    )));
+                }
+
+                let configuration: OpenIdConfiguration = response.json().await.map_err(|e| {
+                    IdentityProviderError::TokenValidation(format!(
+                        "Failed to parse OpenID configuration: {}",
+                        e
+                    ))
+                })?;
+
+                // A document for another issuer would let that issuer's keys in
+                if configuration.issuer != self.issuer {
+                    return Err(IdentityProviderError::InvalidIssuer);
+                }
+
+                Ok(Arc::new(CertificateCache::jwks(
+                    configuration.jwks_uri,
+                    self.client.clone(),
+                )))
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+    ) -> Result<ExternalIdentity, IdentityProviderError> {
+        let header = decode_header(id_token).map_err(|e| {
+            IdentityProviderError::TokenValidation(format!("Failed to decode header: {}", e))
+        })?;
+
+        if !is_asymmetric(header.alg) {
+            return Err(IdentityProviderError::TokenValidation(format!(
+                "Unsupported signing algorithm: {:?}",
+                header.alg
+            )));
+        }
+
+        let kid = header.kid.ok_or(IdentityProviderError::InvalidToken)?;
+
+        let keys = self.keys().await?;
+        let jwk = keys
+            .get(&kid)
+            .await?
+            .ok_or(IdentityProviderError::TokenValidation(
+                "Key ID not found in key set".to_string(),
+            ))?;
+
+        let decoding_key = DecodingKey::from_jwk(&jwk).map_err(|e| {
+            IdentityProviderError::TokenValidation(format!(
+                "Failed to create decoding key from JWK: {}",
+                e
+            ))
+        })?;
+
+        let claims: OidcTokenClaims = decode_id_token(
+            id_token,
+            &decoding_key,
+            header.alg,
+            &self.issuer,
+            &self.audience,
+        )?;
+
+        Ok(claims.into())
+    }
+}
+
+impl From<OidcTokenClaims> for ExternalIdentity {
+    fn from(claims: OidcTokenClaims) -> Self {
+        // Fall back to the structured name parts when no display name is given
+        let name = claims.name.or_else(|| {
+            let parts: Vec<String> = [claims.given_name, claims.family_name]
+                .into_iter()
+                .flatten()
+                .collect();
+            (!parts.is_empty()).then(|| parts.join(" "))
+        });
+
+        ExternalIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified.unwrap_or(false),
+            name,
+            picture: claims.picture,
+            sign_in_method: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::get, Json};
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use serde_json::{json, Value};
+
+    const AUDIENCE: &str = "windspire";
+
+    struct SigningKey {
+        kid: String,
+        encoding_key: EncodingKey,
+        jwk: Value,
+    }
+
+    impl SigningKey {
+        fn generate(kid: &str) -> Self {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+            let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+            Self {
+                kid: kid.to_string(),
+                encoding_key: EncodingKey::from_ed_der(pkcs8.as_ref()),
+                jwk: json!({
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "use": "sig",
+                    "alg": "EdDSA",
+                    "kid": kid,
+                    "x": URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+                }),
+            }
+        }
+
+        fn sign(&self, claims: &Value) -> String {
+            let mut header = Header::new(Algorithm::EdDSA);
+            header.kid = Some(self.kid.clone());
+            encode(&header, claims, &self.encoding_key).unwrap()
+        }
+    }
+
+    /// Local stand-in for an OpenID provider's discovery document and JWKS
+    #[derive(Clone)]
+    struct StubIssuer {
+        issuer: String,
+        jwks: Value,
+    }
+
+    async fn openid_configuration(State(stub): State<StubIssuer>) -> Json<Value> {
+        Json(json!({
+            "issuer": stub.issuer,
+            "jwks_uri": format!("{}/jwks", stub.issuer),
+        }))
+    }
+
+    async fn jwks(State(stub): State<StubIssuer>) -> Json<Value> {
+        Json(stub.jwks)
+    }
+
+    /// Start the stub and return its issuer URL. `advertised_issuer` overrides
+    /// the issuer named in the discovery document.
+    async fn start_stub_issuer(keys: &[&SigningKey], advertised_issuer: Option<&str>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let stub = StubIssuer {
+            issuer: advertised_issuer.unwrap_or(&issuer).to_string(),
+            jwks: json!({ "keys": keys.iter().map(|k| k.jwk.clone()).collect::<Vec<_>>() }),
+        };
+
+        let app = axum::Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(openid_configuration),
+            )
+            .route("/jwks", get(jwks))
+            .with_state(stub);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        issuer
+    }
+
+    fn create_provider(issuer: &str) -> OidcProvider {
+        OidcProvider::new(&OidcProviderConfig {
+            name: "keycloak".to_string(),
+            issuer: issuer.to_string(),
+            audience: AUDIENCE.to_string(),
+            discovery_url: None,
+        })
+    }
+
+    fn create_claims(issuer: &str, audience: &str) -> Value {
+        let now = chrono::Utc::now().timestamp();
+        json!({
+            "iss": issuer,
+            "aud": audience,
+            "sub": "user-123",
+            "iat": now,
+            "exp": now + 300,
+            "email": "ada@example.com",
+            "email_verified": true,
+            "given_name": "Ada",
+            "family_name": "Lovelace",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_verify_id_token() {
+        let key = SigningKey::generate("key-1");
+        let issuer = start_stub_issuer(&[&key], None).await;
+        let provider = create_provider(&issuer);
+
+        let identity = provider
+            .verify_id_token(&key.sign(&create_claims(&issuer, AUDIENCE)))
+            .await
+            .unwrap();
+
+        assert_eq!(provider.name(), "keycloak");
+        assert_eq!(identity.subject, "user-123");
+        assert_eq!(identity.email.as_deref(), Some("ada@example.com"));
+        assert!(identity.email_verified);
+        assert_eq!(identity.name.as_deref(), Some("Ada Lovelace"));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_audience_rejected() {
+        let key = SigningKey::generate("key-1");
+        let issuer = start_stub_issuer(&[&key], None).await;
+        let provider = create_provider(&issuer);
+
+        let result = provider
+            .verify_id_token(&key.sign(&create_claims(&issuer, "another-app")))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(IdentityProviderError::InvalidAudience)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_key_outside_key_set_rejected() {
+        let published = SigningKey::generate("key-1");
+        let forged = SigningKey::generate("key-1");
+        let issuer = start_stub_issuer(&[&published], None).await;
+        let provider = create_provider(&issuer);
+
+        let result = provider
+            .verify_id_token(&forged.sign(&create_claims(&issuer, AUDIENCE)))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(IdentityProviderError::TokenValidation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_symmetric_token_rejected() {
+        let key = SigningKey::generate("key-1");
+        let issuer = start_stub_issuer(&[&key], None).await;
+        let provider = create_provider(&issuer);
+
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("key-1".to_string());
+        let token = encode(
+            &header,
+            &create_claims(&issuer, AUDIENCE),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        assert!(provider.verify_id_token(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discovery_issuer_mismatch_rejected() {
+        let key = SigningKey::generate("key-1");
+        let issuer = start_stub_issuer(&[&key], Some("https://evil.example.com")).await;
+        let provider = create_provider(&issuer);
+
+        let result = provider
+            .verify_id_token(&key.sign(&create_claims(&issuer, AUDIENCE)))
+            .await;
+
+        assert!(matches!(result, Err(IdentityProviderError::InvalidIssuer)));
+    }
+}
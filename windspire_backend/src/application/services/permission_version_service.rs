@@ -0,0 +1,247 @@
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::domain::models::auth::Claims;
+use crate::domain::repositories::role_repository::RoleRepository;
+use crate::domain::repositories::user_repository::UserRepository;
+use crate::infrastructure::repositories::sqlx_role_repository::SqlxRoleRepository;
+use crate::infrastructure::repositories::sqlx_user_repository::SqlxUserRepository;
+
+/// How long a user's permission version is trusted before it is read again, so
+/// role changes made by other instances are picked up
+pub const VERSION_CACHE_SECONDS: u64 = 30;
+
+/// How often role assignments that started or ended are swept
+pub const ASSIGNMENT_SWEEP_SECONDS: u64 = 60;
+
+/// Above this many cached users, entries that are due for a re-read are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Roles and permissions of a user as currently stored in the database
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPermissions {
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+struct CachedVersion {
+    version: i64,
+    checked_at: Instant,
+    /// Roles and permissions resolved at `version`, once a stale token needed them
+    resolved: Option<ResolvedPermissions>,
+}
+
+/// Detects access tokens whose embedded roles and permissions are out of date.
+///
+/// Every access token carries the user's `permission_version` (`pv`) from when
+/// it was issued. The database bumps the version whenever the user's roles or
+/// the permissions of those roles change; `jwt_auth_middleware` then uses the
+/// permissions resolved here instead of the ones in the token.
+pub struct PermissionVersionService {
+    ttl: Duration,
+    versions: RwLock<HashMap<Uuid, CachedVersion>>,
+    repository: SqlxUserRepository,
+}
+
+impl PermissionVersionService {
+    pub fn new(ttl_seconds: u64) -> Self {
+        Self {
+            ttl: Duration::from_secs(ttl_seconds),
+            versions: RwLock::new(HashMap::new()),
+            repository: SqlxUserRepository,
+        }
+    }
+
+    /// The user's current roles and permissions when they changed after the
+    /// token was issued, `None` when the token is up to date
+    pub async fn resolve_stale(
+        &self,
+        pool: &PgPool,
+        claims: &Claims,
+    ) -> Result<Option<ResolvedPermissions>, sqlx::Error> {
+        let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+            return Ok(None);
+        };
+
+        let version = match self.cached_version(user_id) {
+            Some(version) => version,
+            None => {
+                let version = self
+                    .repository
+                    .get_permission_version(pool, user_id)
+                    .await?;
+                self.remember_version(user_id, version);
+                version
+            }
+        };
+
+        if claims.pv >= version {
+            return Ok(None);
+        }
+
+        if let Some(resolved) = self.cached_permissions(user_id, version) {
+            return Ok(Some(resolved));
+        }
+
+        let user_with_roles = self.repository.get_user_with_roles(pool, user_id).await?;
+        let resolved = ResolvedPermissions {
+            roles: user_with_roles.roles.into_iter().map(|r| r.name).collect(),
+            permissions: user_with_roles
+                .permissions
+                .into_iter()
+                .map(|p| p.name)
+                .collect(),
+        };
+        self.remember_permissions(
+            user_id,
+            user_with_roles.permission_version,
+            resolved.clone(),
+        );
+
+        Ok(Some(resolved))
+    }
+
+    /// Periodically bump the permission version of users whose time-bound role
+    /// assignments started or expired, so their tokens are re-resolved
+    pub fn spawn_assignment_sweep(self: &Arc<Self>, pool: PgPool) -> tokio::task::JoinHandle<()> {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(ASSIGNMENT_SWEEP_SECONDS));
+            loop {
+                interval.tick().await;
+                match SqlxRoleRepository.sweep_role_assignments(&pool).await {
+                    Ok(user_ids) => {
+                        for user_id in user_ids {
+                            service.invalidate(user_id);
+                        }
+                    }
+                    Err(e) => tracing::error!("Failed to sweep role assignments: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Forget the cached version so the next request re-reads it. Used after
+    /// changing a user's roles on this instance.
+    pub fn invalidate(&self, user_id: Uuid) {
+        self.versions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&user_id);
+    }
+
+    fn cached_version(&self, user_id: Uuid) -> Option<i64> {
+        self.versions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&user_id)
+            .filter(|cached| cached.checked_at.elapsed() < self.ttl)
+            .map(|cached| cached.version)
+    }
+
+    fn cached_permissions(&self, user_id: Uuid, version: i64) -> Option<ResolvedPermissions> {
+        self.versions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&user_id)
+            .filter(|cached| cached.version == version)
+            .and_then(|cached| cached.resolved.clone())
+    }
+
+    fn remember_version(&self, user_id: Uuid, version: i64) {
+        let mut versions = self
+            .versions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if versions.len() >= PRUNE_THRESHOLD {
+            versions.retain(|_, cached| cached.checked_at.elapsed() < self.ttl);
+        }
+
+        match versions.get_mut(&user_id) {
+            // Keep the resolved permissions while the version is unchanged
+            Some(cached) if cached.version == version => cached.checked_at = Instant::now(),
+            _ => {
+                versions.insert(
+                    user_id,
+                    CachedVersion {
+                        version,
+                        checked_at: Instant::now(),
+                        resolved: None,
+                    },
+                );
+            }
+        }
+    }
+
+    fn remember_permissions(&self, user_id: Uuid, version: i64, resolved: ResolvedPermissions) {
+        self.versions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                user_id,
+                CachedVersion {
+                    version,
+                    checked_at: Instant::now(),
+                    resolved: Some(resolved),
+                },
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(permissions: &[&str]) -> ResolvedPermissions {
+        ResolvedPermissions {
+            roles: vec!["user".to_string()],
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_cached_version_expires() {
+        let service = PermissionVersionService::new(0);
+        let user_id = Uuid::new_v4();
+
+        service.remember_version(user_id, 3);
+
+        assert_eq!(service.cached_version(user_id), None);
+    }
+
+    #[test]
+    fn test_resolved_permissions_follow_version() {
+        let service = PermissionVersionService::new(60);
+        let user_id = Uuid::new_v4();
+
+        service.remember_permissions(user_id, 2, resolved(&["boats:read"]));
+        // Re-reading the same version keeps the resolved permissions
+        service.remember_version(user_id, 2);
+
+        assert_eq!(service.cached_version(user_id), Some(2));
+        assert_eq!(
+            service.cached_permissions(user_id, 2),
+            Some(resolved(&["boats:read"]))
+        );
+
+        // A newer version means the resolved permissions are stale as well
+        service.remember_version(user_id, 3);
+
+        assert_eq!(service.cached_version(user_id), Some(3));
+        assert_eq!(service.cached_permissions(user_id, 3), None);
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let service = PermissionVersionService::new(60);
+        let user_id = Uuid::new_v4();
+
+        service.remember_version(user_id, 1);
+        service.invalidate(user_id);
+
+        assert_eq!(service.cached_version(user_id), None);
+    }
+}
@@ -0,0 +1,319 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::application::config::{RateLimitConfig, RateLimitRule, RateLimitStoreKind};
+use crate::domain::repositories::rate_limit_repository::RateLimitRepository;
+use crate::infrastructure::repositories::sqlx_rate_limit_repository::SqlxRateLimitRepository;
+
+/// How often counters of past windows are pruned
+pub const PRUNE_INTERVAL_SECONDS: u64 = 60;
+
+/// The budget a route draws from. Routes sharing a budget share its counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitBudget {
+    Login,
+    Refresh,
+    Public,
+    Api,
+}
+
+impl RateLimitBudget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitBudget::Login => "login",
+            RateLimitBudget::Refresh => "refresh",
+            RateLimitBudget::Public => "public",
+            RateLimitBudget::Api => "api",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Until the current window ends and the budget is refilled
+    pub retry_after_seconds: u64,
+}
+
+/// Counts requests per key in fixed windows
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Count a request, returning the number of requests in the window so far
+    async fn increment(
+        &self,
+        key: &str,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Result<u32, sqlx::Error>;
+
+    /// Drop counters of windows that ended at or before `now`
+    async fn prune(&self, now: DateTime<Utc>) -> Result<(), sqlx::Error>;
+}
+
+/// Counters in process memory; each instance enforces the budgets on its own
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    counters: Mutex<HashMap<String, Counter>>,
+}
+
+struct Counter {
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    count: u32,
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn increment(
+        &self,
+        key: &str,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Result<u32, sqlx::Error> {
+        let mut counters = self.counters.lock().unwrap_or_else(PoisonError::into_inner);
+        let counter = counters.entry(key.to_string()).or_insert(Counter {
+            window_start,
+            window_end,
+            count: 0,
+        });
+        if counter.window_start != window_start {
+            *counter = Counter {
+                window_start,
+                window_end,
+                count: 0,
+            };
+        }
+        counter.count += 1;
+        Ok(counter.count)
+    }
+
+    async fn prune(&self, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        self.counters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|_, counter| counter.window_end > now);
+        Ok(())
+    }
+}
+
+/// Counters in Postgres, shared by every instance
+pub struct PostgresRateLimitStore {
+    pool: PgPool,
+    repository: SqlxRateLimitRepository,
+}
+
+impl PostgresRateLimitStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            repository: SqlxRateLimitRepository,
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresRateLimitStore {
+    async fn increment(
+        &self,
+        key: &str,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Result<u32, sqlx::Error> {
+        let count = self
+            .repository
+            .increment_counter(&self.pool, key, window_start, window_end)
+            .await?;
+        Ok(count.max(0) as u32)
+    }
+
+    async fn prune(&self, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        self.repository
+            .delete_expired_counters(&self.pool, now)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Fixed-window rate limiting over the configured budgets
+pub struct RateLimitService {
+    config: RateLimitConfig,
+    store: Box<dyn RateLimitStore>,
+}
+
+impl RateLimitService {
+    pub fn new(config: RateLimitConfig, store: Box<dyn RateLimitStore>) -> Self {
+        Self { config, store }
+    }
+
+    /// With the store `RATE_LIMIT_STORE` asks for
+    pub fn from_config(config: RateLimitConfig, pool: &PgPool) -> Self {
+        let store: Box<dyn RateLimitStore> = match config.store {
+            RateLimitStoreKind::Memory => Box::new(MemoryRateLimitStore::default()),
+            RateLimitStoreKind::Postgres => Box::new(PostgresRateLimitStore::new(pool.clone())),
+        };
+        Self::new(config, store)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn trusts_forwarded_for(&self) -> bool {
+        self.config.trust_forwarded_for
+    }
+
+    pub fn rule(&self, budget: RateLimitBudget) -> RateLimitRule {
+        match budget {
+            RateLimitBudget::Login => self.config.login,
+            RateLimitBudget::Refresh => self.config.refresh,
+            RateLimitBudget::Public => self.config.public,
+            RateLimitBudget::Api => self.config.api,
+        }
+    }
+
+    /// Count a request by `client` against `budget`. If the store fails the
+    /// request is allowed, so an outage of the store cannot lock everybody out.
+    pub async fn check(
+        &self,
+        budget: RateLimitBudget,
+        client: &str,
+        now: DateTime<Utc>,
+    ) -> RateLimitDecision {
+        let rule = self.rule(budget);
+        let window = i64::from(rule.window_seconds);
+        let window_start_seconds = now.timestamp() - now.timestamp().rem_euclid(window);
+        let window_start = DateTime::from_timestamp(window_start_seconds, 0).unwrap_or(now);
+        let window_end = window_start + chrono::Duration::seconds(window);
+
+        let key = format!("{}:{}", budget.as_str(), client);
+        let count = match self.store.increment(&key, window_start, window_end).await {
+            Ok(count) => count,
+            Err(e) => {
+                tracing::error!("Rate limit store failed, allowing request: {}", e);
+                0
+            }
+        };
+
+        RateLimitDecision {
+            allowed: count <= rule.requests,
+            limit: rule.requests,
+            remaining: rule.requests.saturating_sub(count),
+            retry_after_seconds: (window_end - now).num_seconds().max(1) as u64,
+        }
+    }
+
+    /// Periodically prune counters of past windows for as long as the process runs
+    pub fn spawn_prune_task(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(PRUNE_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.store.prune(Utc::now()).await {
+                    tracing::error!("Failed to prune rate limit counters: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_service() -> RateLimitService {
+        let config = RateLimitConfig {
+            enabled: true,
+            store: RateLimitStoreKind::Memory,
+            trust_forwarded_for: false,
+            login: RateLimitRule::new(2, 60),
+            refresh: RateLimitRule::new(5, 60),
+            public: RateLimitRule::new(5, 60),
+            api: RateLimitRule::new(5, 60),
+        };
+        RateLimitService::new(config, Box::new(MemoryRateLimitStore::default()))
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_800_000_000 + seconds, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_budget_runs_out_within_window() {
+        let service = create_test_service();
+
+        let first = service
+            .check(RateLimitBudget::Login, "ip:10.0.0.1", at(0))
+            .await;
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 1);
+        assert!(
+            service
+                .check(RateLimitBudget::Login, "ip:10.0.0.1", at(1))
+                .await
+                .allowed
+        );
+
+        let limited = service
+            .check(RateLimitBudget::Login, "ip:10.0.0.1", at(20))
+            .await;
+        assert!(!limited.allowed);
+        assert_eq!(limited.remaining, 0);
+        assert_eq!(limited.retry_after_seconds, 40);
+
+        // A new window refills the budget
+        assert!(
+            service
+                .check(RateLimitBudget::Login, "ip:10.0.0.1", at(60))
+                .await
+                .allowed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_budgets_and_clients_are_counted_apart() {
+        let service = create_test_service();
+        for _ in 0..2 {
+            service
+                .check(RateLimitBudget::Login, "ip:10.0.0.1", at(0))
+                .await;
+        }
+
+        assert!(
+            !service
+                .check(RateLimitBudget::Login, "ip:10.0.0.1", at(0))
+                .await
+                .allowed
+        );
+        assert!(
+            service
+                .check(RateLimitBudget::Login, "ip:10.0.0.2", at(0))
+                .await
+                .allowed
+        );
+        assert!(
+            service
+                .check(RateLimitBudget::Public, "ip:10.0.0.1", at(0))
+                .await
+                .allowed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_prunes_past_windows() {
+        let store = MemoryRateLimitStore::default();
+        store.increment("login:a", at(0), at(60)).await.unwrap();
+        store.increment("login:b", at(60), at(120)).await.unwrap();
+
+        store.prune(at(60)).await.unwrap();
+
+        let counters = store.counters.lock().unwrap();
+        assert_eq!(counters.keys().collect::<Vec<_>>(), vec!["login:b"]);
+    }
+}
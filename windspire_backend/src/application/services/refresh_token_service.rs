@@ -0,0 +1,300 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::domain::models::refresh_token::{RefreshToken, RefreshTokenCreate};
+use crate::domain::repositories::refresh_token_repository::RefreshTokenRepository;
+use crate::infrastructure::repositories::sqlx_refresh_token_repository::SqlxRefreshTokenRepository;
+
+/// Number of random bytes in an opaque refresh token (256 bits)
+const REFRESH_TOKEN_BYTES: usize = 32;
+
+#[derive(Debug)]
+pub enum RefreshTokenError {
+    InvalidToken,
+    ExpiredToken,
+    /// An already rotated token was presented again; its family has been revoked
+    TokenReused,
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for RefreshTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefreshTokenError::InvalidToken => write!(f, "Invalid refresh token"),
+            RefreshTokenError::ExpiredToken => write!(f, "Refresh token has expired"),
+            RefreshTokenError::TokenReused => write!(f, "Refresh token reuse detected"),
+            RefreshTokenError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RefreshTokenError {}
+
+impl From<sqlx::Error> for RefreshTokenError {
+    fn from(err: sqlx::Error) -> Self {
+        RefreshTokenError::Database(err)
+    }
+}
+
+/// A freshly minted refresh token. `token` is the only copy of the secret and
+/// must be handed to the client; the database only keeps its hash.
+#[derive(Debug, Clone)]
+pub struct IssuedRefreshToken {
+    pub token: String,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct RefreshTokenService {
+    expiration_days: i64,
+    repository: SqlxRefreshTokenRepository,
+}
+
+impl RefreshTokenService {
+    pub fn new(expiration_days: i64) -> Self {
+        Self {
+            expiration_days,
+            repository: SqlxRefreshTokenRepository,
+        }
+    }
+
+    /// Issue the first refresh token of a new family (i.e. a new login). The
+    /// family shares its id with the login's session.
+    pub async fn issue(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<IssuedRefreshToken, RefreshTokenError> {
+        let (token, create) = self.new_token(user_id, session_id);
+
+        let created = self.repository.create_refresh_token(pool, &create).await?;
+
+        Ok(IssuedRefreshToken {
+            token,
+            user_id: created.user_id,
+            family_id: created.family_id,
+            expires_at: created.expires_at,
+        })
+    }
+
+    /// Look up a presented refresh token that may be exchanged. Nothing is
+    /// spent yet, so the caller can prepare the new access token first and
+    /// only then [`rotate`](Self::rotate).
+    ///
+    /// Presenting a token that has already been exchanged means that either the
+    /// client or an attacker holds a stale copy, so every token in the family is
+    /// revoked and the caller has to log in again.
+    pub async fn redeemable(
+        &self,
+        pool: &PgPool,
+        presented: &str,
+    ) -> Result<RefreshToken, RefreshTokenError> {
+        let stored = self.find(pool, presented).await?;
+
+        if let Err(e) = check_usable(&stored, Utc::now()) {
+            if matches!(e, RefreshTokenError::TokenReused) {
+                self.revoke_family(pool, &stored).await?;
+            }
+            return Err(e);
+        }
+
+        Ok(stored)
+    }
+
+    /// Exchange a token found by [`redeemable`](Self::redeemable) for its
+    /// successor in the same family
+    pub async fn rotate(
+        &self,
+        pool: &PgPool,
+        stored: &RefreshToken,
+    ) -> Result<IssuedRefreshToken, RefreshTokenError> {
+        let (token, replacement) = self.new_token(stored.user_id, stored.family_id);
+
+        match self
+            .repository
+            .rotate_refresh_token(pool, stored.id, &replacement)
+            .await?
+        {
+            Some(created) => Ok(IssuedRefreshToken {
+                token,
+                user_id: created.user_id,
+                family_id: created.family_id,
+                expires_at: created.expires_at,
+            }),
+            None => {
+                // Lost the race against a concurrent exchange of the same token
+                self.revoke_family(pool, stored).await?;
+                Err(RefreshTokenError::TokenReused)
+            }
+        }
+    }
+
+    /// Revoke the family the presented token belongs to (client-side logout),
+    /// returning the presented token
+    pub async fn revoke(
+        &self,
+        pool: &PgPool,
+        presented: &str,
+    ) -> Result<RefreshToken, RefreshTokenError> {
+        let stored = self.find(pool, presented).await?;
+        self.repository
+            .revoke_refresh_token_family(pool, stored.family_id)
+            .await?;
+        Ok(stored)
+    }
+
+    /// Revoke the refresh tokens of an ended session
+    pub async fn revoke_session(&self, pool: &PgPool, session_id: Uuid) -> Result<(), sqlx::Error> {
+        self.repository
+            .revoke_refresh_token_family(pool, session_id)
+            .await?;
+        Ok(())
+    }
+
+    /// Revoke every refresh token the user holds
+    pub async fn revoke_all_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<u64, RefreshTokenError> {
+        Ok(self
+            .repository
+            .revoke_user_refresh_tokens(pool, user_id)
+            .await?)
+    }
+
+    pub fn hash_token(token: &str) -> String {
+        format!("{:x}", Sha256::digest(token.as_bytes()))
+    }
+
+    fn generate_token() -> String {
+        let mut bytes = [0u8; REFRESH_TOKEN_BYTES];
+        rand::rng().fill_bytes(&mut bytes);
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn new_token(&self, user_id: Uuid, family_id: Uuid) -> (String, RefreshTokenCreate) {
+        let token = Self::generate_token();
+        let create = RefreshTokenCreate {
+            id: Uuid::new_v7(Timestamp::now(NoContext)),
+            user_id,
+            family_id,
+            token_hash: Self::hash_token(&token),
+            expires_at: Utc::now() + Duration::days(self.expiration_days),
+        };
+        (token, create)
+    }
+
+    async fn find(
+        &self,
+        pool: &PgPool,
+        presented: &str,
+    ) -> Result<RefreshToken, RefreshTokenError> {
+        self.repository
+            .get_refresh_token_by_hash(pool, &Self::hash_token(presented))
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => RefreshTokenError::InvalidToken,
+                e => RefreshTokenError::Database(e),
+            })
+    }
+
+    async fn revoke_family(
+        &self,
+        pool: &PgPool,
+        stored: &RefreshToken,
+    ) -> Result<(), RefreshTokenError> {
+        tracing::warn!(
+            "Refresh token reuse detected for user {}, revoking family {}",
+            stored.user_id,
+            stored.family_id
+        );
+        self.repository
+            .revoke_refresh_token_family(pool, stored.family_id)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Decide whether a stored token may be exchanged at `now`
+fn check_usable(token: &RefreshToken, now: DateTime<Utc>) -> Result<(), RefreshTokenError> {
+    if token.revoked_at.is_some() {
+        return Err(RefreshTokenError::InvalidToken);
+    }
+    if token.used_at.is_some() {
+        return Err(RefreshTokenError::TokenReused);
+    }
+    if token.expires_at <= now {
+        return Err(RefreshTokenError::ExpiredToken);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_token() -> RefreshToken {
+        let now = Utc::now();
+        RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            family_id: Uuid::new_v4(),
+            token_hash: RefreshTokenService::hash_token("token"),
+            issued_at: now,
+            expires_at: now + Duration::days(1),
+            used_at: None,
+            revoked_at: None,
+            replaced_by: None,
+        }
+    }
+
+    #[test]
+    fn test_generated_tokens_are_unique_and_hashed() {
+        let first = RefreshTokenService::generate_token();
+        let second = RefreshTokenService::generate_token();
+
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 43); // 32 bytes, base64url without padding
+        assert_eq!(
+            RefreshTokenService::hash_token(&first),
+            RefreshTokenService::hash_token(&first)
+        );
+        assert_ne!(RefreshTokenService::hash_token(&first), first);
+    }
+
+    #[test]
+    fn test_check_usable() {
+        let now = Utc::now();
+        let token = create_test_token();
+        assert!(check_usable(&token, now).is_ok());
+
+        let mut used = create_test_token();
+        used.used_at = Some(now);
+        assert!(matches!(
+            check_usable(&used, now),
+            Err(RefreshTokenError::TokenReused)
+        ));
+
+        let mut revoked = create_test_token();
+        revoked.used_at = Some(now);
+        revoked.revoked_at = Some(now);
+        assert!(matches!(
+            check_usable(&revoked, now),
+            Err(RefreshTokenError::InvalidToken)
+        ));
+
+        let mut expired = create_test_token();
+        expired.expires_at = now - Duration::seconds(1);
+        assert!(matches!(
+            check_usable(&expired, now),
+            Err(RefreshTokenError::ExpiredToken)
+        ));
+    }
+}
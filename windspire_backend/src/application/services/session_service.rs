@@ -0,0 +1,112 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+use uuid::Uuid;
+
+use crate::domain::models::user_session::{UserSession, UserSessionCreate};
+use crate::domain::repositories::user_session_repository::UserSessionRepository;
+use crate::infrastructure::repositories::sqlx_user_session_repository::SqlxUserSessionRepository;
+
+/// How stale a session's last seen time may get before a request updates it
+pub const TOUCH_INTERVAL_SECONDS: i64 = 300;
+
+/// Records logins as sessions and when they were last seen. Ending sessions
+/// is up to `TokenRevocationService`, which rejects their access tokens.
+pub struct SessionService {
+    touch_interval: Duration,
+    last_touched: RwLock<HashMap<Uuid, DateTime<Utc>>>, // session id -> last recorded on this instance
+    repository: SqlxUserSessionRepository,
+}
+
+impl SessionService {
+    pub fn new(touch_interval_seconds: i64) -> Self {
+        Self {
+            touch_interval: Duration::seconds(touch_interval_seconds),
+            last_touched: RwLock::new(HashMap::new()),
+            repository: SqlxUserSessionRepository,
+        }
+    }
+
+    pub async fn start(
+        &self,
+        pool: &PgPool,
+        session: &UserSessionCreate,
+    ) -> Result<UserSession, sqlx::Error> {
+        let created = self.repository.create_session(pool, session).await?;
+        self.remember_touch(created.id, created.last_seen_at);
+        Ok(created)
+    }
+
+    /// Record a request made in the session, at most once per touch interval
+    pub async fn touch(&self, pool: &PgPool, session_id: Uuid) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        if !self.is_touch_due(session_id, now) {
+            return Ok(());
+        }
+
+        self.remember_touch(session_id, now);
+        self.repository.touch_session(pool, session_id, now).await
+    }
+
+    /// Record a token refresh, which keeps the session alive until `expires_at`
+    pub async fn extend(
+        &self,
+        pool: &PgPool,
+        session_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        self.remember_touch(session_id, now);
+        self.repository
+            .extend_session(pool, session_id, now, expires_at)
+            .await
+    }
+
+    pub async fn list_active(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<UserSession>, sqlx::Error> {
+        self.repository
+            .get_active_sessions_for_user(pool, user_id, Utc::now())
+            .await
+    }
+
+    fn is_touch_due(&self, session_id: Uuid, now: DateTime<Utc>) -> bool {
+        self.last_touched
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&session_id)
+            .is_none_or(|touched| now - *touched >= self.touch_interval)
+    }
+
+    fn remember_touch(&self, session_id: Uuid, now: DateTime<Utc>) {
+        let mut last_touched = self
+            .last_touched
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        // Entries of sessions idle for longer than the interval serve no purpose
+        last_touched.retain(|_, touched| now - *touched < self.touch_interval);
+        last_touched.insert(session_id, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touch_is_throttled_per_session() {
+        let service = SessionService::new(300);
+        let session_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        assert!(service.is_touch_due(session_id, now));
+        service.remember_touch(session_id, now);
+
+        assert!(!service.is_touch_due(session_id, now + Duration::seconds(299)));
+        assert!(service.is_touch_due(session_id, now + Duration::seconds(300)));
+        assert!(service.is_touch_due(Uuid::new_v4(), now));
+    }
+}
@@ -0,0 +1,168 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use ring::rsa::PublicKeyComponents;
+use ring::signature::{Ed25519KeyPair, KeyPair, RsaKeyPair};
+use serde_json::{json, Value};
+
+/// An asymmetric key pair used to sign access tokens, identified by `kid`
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    /// When the key stopped being used for signing; `None` for the active key
+    pub retired_at: Option<DateTime<Utc>>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    jwk: Value,
+}
+
+impl SigningKey {
+    /// Load a PEM encoded private key (PKCS#8, or PKCS#1 for RSA)
+    pub fn from_pem(kid: &str, algorithm: Algorithm, pem: &[u8]) -> Result<Self, String> {
+        let parsed = pem::parse(pem).map_err(|e| format!("Invalid PEM: {}", e))?;
+
+        let (encoding_key, decoding_key, jwk) = match algorithm {
+            Algorithm::RS256 => {
+                let key_pair = match parsed.tag() {
+                    "PRIVATE KEY" => RsaKeyPair::from_pkcs8(parsed.contents()),
+                    "RSA PRIVATE KEY" => RsaKeyPair::from_der(parsed.contents()),
+                    tag => return Err(format!("Expected an RSA private key, found {}", tag)),
+                }
+                .map_err(|e| format!("Invalid RSA key: {}", e))?;
+
+                let public_key = PublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+                let n = URL_SAFE_NO_PAD.encode(&public_key.n);
+                let e = URL_SAFE_NO_PAD.encode(&public_key.e);
+
+                (
+                    EncodingKey::from_rsa_pem(pem).map_err(|e| e.to_string())?,
+                    DecodingKey::from_rsa_components(&n, &e).map_err(|e| e.to_string())?,
+                    json!({ "kty": "RSA", "n": n, "e": e }),
+                )
+            }
+            Algorithm::EdDSA => {
+                let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(parsed.contents())
+                    .map_err(|e| format!("Invalid Ed25519 key: {}", e))?;
+                let x = URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref());
+
+                (
+                    EncodingKey::from_ed_der(parsed.contents()),
+                    DecodingKey::from_ed_components(&x).map_err(|e| e.to_string())?,
+                    json!({ "kty": "OKP", "crv": "Ed25519", "x": x }),
+                )
+            }
+            other => return Err(format!("Unsupported signing algorithm: {:?}", other)),
+        };
+
+        let mut jwk = jwk;
+        jwk["kid"] = json!(kid);
+        jwk["alg"] = json!(algorithm);
+        jwk["use"] = json!("sig");
+
+        Ok(Self {
+            kid: kid.to_string(),
+            algorithm,
+            retired_at: None,
+            encoding_key,
+            decoding_key,
+            jwk,
+        })
+    }
+
+    pub fn retired(mut self, retired_at: DateTime<Utc>) -> Self {
+        self.retired_at = Some(retired_at);
+        self
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding_key
+    }
+
+    pub fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding_key
+    }
+
+    /// The public half of the key as a JWK
+    pub fn jwk(&self) -> &Value {
+        &self.jwk
+    }
+}
+
+/// A key that no longer signs tokens but still verifies them, configured as
+/// `kid=/path/to/private.pem@2026-01-01T00:00:00Z` (retirement time)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetiredKeySpec {
+    pub kid: String,
+    pub key_file: String,
+    pub retired_at: DateTime<Utc>,
+}
+
+impl RetiredKeySpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "Invalid retired key '{}', expected kid=file@retired_at",
+                spec
+            )
+        };
+
+        let (kid, rest) = spec.split_once('=').ok_or_else(invalid)?;
+        let (key_file, retired_at) = rest.rsplit_once('@').ok_or_else(invalid)?;
+        let retired_at = DateTime::parse_from_rfc3339(retired_at.trim())
+            .map_err(|_| invalid())?
+            .with_timezone(&Utc);
+
+        if kid.trim().is_empty() || key_file.trim().is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            kid: kid.trim().to_string(),
+            key_file: key_file.trim().to_string(),
+            retired_at,
+        })
+    }
+}
+
+/// A fresh Ed25519 private key as PKCS#8 PEM
+pub fn generate_ed25519_pem() -> String {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+        .expect("System random number generator failed");
+    pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_key_from_pem() {
+        let pem = generate_ed25519_pem();
+        let key = SigningKey::from_pem("key-1", Algorithm::EdDSA, pem.as_bytes()).unwrap();
+
+        assert_eq!(key.jwk()["kid"], "key-1");
+        assert_eq!(key.jwk()["alg"], "EdDSA");
+        assert_eq!(key.jwk()["kty"], "OKP");
+        assert!(key.jwk().get("d").is_none());
+        assert!(key.retired_at.is_none());
+    }
+
+    #[test]
+    fn test_algorithm_must_match_key() {
+        let pem = generate_ed25519_pem();
+        assert!(SigningKey::from_pem("key-1", Algorithm::RS256, pem.as_bytes()).is_err());
+        assert!(SigningKey::from_pem("key-1", Algorithm::HS256, pem.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_retired_key_spec() {
+        let spec =
+            RetiredKeySpec::parse("2026-09=/secrets/jwt-2026-09.pem@2026-10-01T00:00:00Z").unwrap();
+        assert_eq!(spec.kid, "2026-09");
+        assert_eq!(spec.key_file, "/secrets/jwt-2026-09.pem");
+        assert_eq!(spec.retired_at.to_rfc3339(), "2026-10-01T00:00:00+00:00");
+
+        assert!(RetiredKeySpec::parse("2026-09=/secrets/jwt.pem").is_err());
+        assert!(RetiredKeySpec::parse("/secrets/jwt.pem@2026-10-01T00:00:00Z").is_err());
+    }
+}
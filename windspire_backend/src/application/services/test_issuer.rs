@@ -0,0 +1,71 @@
+use chrono::{Duration, Utc};
+use jsonwebtoken::{encode, Algorithm, Header};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::application::services::signing_keys::{generate_ed25519_pem, SigningKey};
+
+/// Key ID of the test issuer's signing key
+pub const TEST_ISSUER_KID: &str = "test-issuer";
+
+/// Lifetime of the ID tokens minted by the test issuer
+const ID_TOKEN_EXPIRATION_MINUTES: i64 = 60;
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct TestIdTokenRequest {
+    pub uid: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub email_verified: Option<bool>,
+}
+
+/// Stands in for Firebase in `AUTH_MODE=test-issuer`: mints Firebase-shaped ID
+/// tokens signed with a key generated at startup, which `FirebaseService` then
+/// verifies like real ones. The key never leaves the process.
+pub struct TestIssuer {
+    project_id: String,
+    key: SigningKey,
+}
+
+impl TestIssuer {
+    pub fn new(project_id: String) -> Self {
+        let key = SigningKey::from_pem(
+            TEST_ISSUER_KID,
+            Algorithm::EdDSA,
+            generate_ed25519_pem().as_bytes(),
+        )
+        .expect("Generated key is valid");
+
+        Self { project_id, key }
+    }
+
+    pub fn key(&self) -> &SigningKey {
+        &self.key
+    }
+
+    pub fn issue_id_token(
+        &self,
+        request: &TestIdTokenRequest,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = Utc::now();
+        let claims = json!({
+            "iss": format!("https://securetoken.google.com/{}", self.project_id),
+            "aud": self.project_id,
+            "auth_time": now.timestamp(),
+            "user_id": request.uid,
+            "sub": request.uid,
+            "iat": now.timestamp(),
+            "exp": (now + Duration::minutes(ID_TOKEN_EXPIRATION_MINUTES)).timestamp(),
+            "email": request.email,
+            "email_verified": request.email_verified.unwrap_or(true),
+            "name": request.name,
+            "firebase": { "sign_in_provider": "password" },
+        });
+
+        let mut header = Header::new(self.key.algorithm);
+        header.kid = Some(self.key.kid.clone());
+        encode(&header, &claims, self.key.encoding_key())
+    }
+}
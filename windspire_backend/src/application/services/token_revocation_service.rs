@@ -0,0 +1,288 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+use uuid::Uuid;
+
+use crate::domain::models::auth::Claims;
+use crate::domain::repositories::token_revocation_repository::TokenRevocationRepository;
+use crate::domain::repositories::user_session_repository::UserSessionRepository;
+use crate::infrastructure::repositories::sqlx_token_revocation_repository::SqlxTokenRevocationRepository;
+use crate::infrastructure::repositories::sqlx_user_session_repository::SqlxUserSessionRepository;
+
+/// How often the cache is reloaded so revocations made by other instances are picked up
+pub const RELOAD_INTERVAL_SECONDS: u64 = 30;
+
+/// Access token denylist backed by Postgres with an in-process cache.
+///
+/// `jwt_auth_middleware` only ever consults the cache; revocations made on this
+/// instance are applied to it immediately, revocations made elsewhere show up
+/// after the next reload.
+pub struct TokenRevocationService {
+    token_lifetime: Duration,
+    revoked_tokens: RwLock<HashMap<String, i64>>, // jti -> exp
+    revoked_before: RwLock<HashMap<Uuid, i64>>,   // user id -> cutoff (unix seconds)
+    revoked_sessions: RwLock<HashSet<Uuid>>,
+    repository: SqlxTokenRevocationRepository,
+    session_repository: SqlxUserSessionRepository,
+}
+
+impl TokenRevocationService {
+    pub fn new(expiration_hours: i64) -> Self {
+        Self {
+            token_lifetime: Duration::hours(expiration_hours),
+            revoked_tokens: RwLock::new(HashMap::new()),
+            revoked_before: RwLock::new(HashMap::new()),
+            revoked_sessions: RwLock::new(HashSet::new()),
+            repository: SqlxTokenRevocationRepository,
+            session_repository: SqlxUserSessionRepository,
+        }
+    }
+
+    pub fn is_revoked(&self, claims: &Claims) -> bool {
+        if self
+            .revoked_tokens
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(&claims.jti)
+        {
+            return true;
+        }
+
+        if claims
+            .sid
+            .as_deref()
+            .and_then(|sid| Uuid::parse_str(sid).ok())
+            .is_some_and(|session_id| {
+                self.revoked_sessions
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .contains(&session_id)
+            })
+        {
+            return true;
+        }
+
+        let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+            return false;
+        };
+
+        self.revoked_before
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&user_id)
+            .is_some_and(|cutoff| claims.iat <= *cutoff)
+    }
+
+    /// Revoke a single access token ("log out this session")
+    pub async fn revoke_token(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(), sqlx::Error> {
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+
+        self.repository
+            .revoke_token(pool, &claims.jti, user_id, expires_at)
+            .await?;
+        self.remember_token(&claims.jti, claims.exp);
+
+        Ok(())
+    }
+
+    /// End one of the user's sessions, revoking every access token issued to it.
+    /// `false` if the user has no such active session.
+    pub async fn revoke_session(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let revoked = self
+            .session_repository
+            .revoke_session(pool, user_id, session_id)
+            .await?;
+        if revoked {
+            self.remember_session(session_id);
+        }
+
+        Ok(revoked)
+    }
+
+    /// Revoke every access token issued to the user up to now ("log out everywhere")
+    pub async fn revoke_all_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        self.repository
+            .revoke_user_tokens_before(pool, user_id, now)
+            .await?;
+        self.remember_cutoff(user_id, now.timestamp());
+        // The cutoff already rejects their tokens; this ends the sessions as listed
+        self.session_repository
+            .revoke_user_sessions(pool, user_id)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Prune entries that can no longer match a valid token and reload the cache
+    pub async fn reload(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        self.repository
+            .delete_expired_revoked_tokens(pool, now)
+            .await?;
+        self.repository
+            .delete_user_token_revocations_before(pool, now - self.token_lifetime)
+            .await?;
+
+        let revoked_tokens = self
+            .repository
+            .get_revoked_tokens(pool)
+            .await?
+            .into_iter()
+            .map(|t| (t.jti, t.expires_at.timestamp()))
+            .collect();
+        let revoked_before = self
+            .repository
+            .get_user_token_revocations(pool)
+            .await?
+            .into_iter()
+            .map(|r| (r.user_id, r.revoked_before.timestamp()))
+            .collect();
+        // Tokens of sessions revoked earlier than a token lifetime ago have all expired
+        let revoked_sessions = self
+            .session_repository
+            .get_revoked_session_ids(pool, now - self.token_lifetime)
+            .await?
+            .into_iter()
+            .collect();
+
+        *self
+            .revoked_tokens
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = revoked_tokens;
+        *self
+            .revoked_before
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = revoked_before;
+        *self
+            .revoked_sessions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = revoked_sessions;
+
+        Ok(())
+    }
+
+    /// Periodically reload the cache for as long as the process runs
+    pub fn spawn_reload_task(self: &Arc<Self>, pool: PgPool) -> tokio::task::JoinHandle<()> {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(RELOAD_INTERVAL_SECONDS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.reload(&pool).await {
+                    tracing::error!("Failed to reload token revocations: {}", e);
+                }
+            }
+        })
+    }
+
+    fn remember_token(&self, jti: &str, exp: i64) {
+        self.revoked_tokens
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(jti.to_string(), exp);
+    }
+
+    fn remember_session(&self, session_id: Uuid) {
+        self.revoked_sessions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(session_id);
+    }
+
+    fn remember_cutoff(&self, user_id: Uuid, cutoff: i64) {
+        let mut revoked_before = self
+            .revoked_before
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let entry = revoked_before.entry(user_id).or_insert(cutoff);
+        *entry = (*entry).max(cutoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_claims(user_id: Uuid, iat: i64) -> Claims {
+        Claims {
+            sub: user_id.to_string(),
+            email: "test@example.com".to_string(),
+            name: "Test User".to_string(),
+            picture: None,
+            roles: vec!["user".to_string()],
+            permissions: vec![],
+            pv: 0,
+            act: None,
+            sid: None,
+            iss: "test".to_string(),
+            aud: "test-api".to_string(),
+            jti: Uuid::new_v4().to_string(),
+            iat,
+            nbf: iat,
+            exp: iat + 3600,
+        }
+    }
+
+    #[test]
+    fn test_revoked_jti() {
+        let service = TokenRevocationService::new(1);
+        let user_id = Uuid::new_v4();
+        let revoked = create_test_claims(user_id, 1_000);
+        let other = create_test_claims(user_id, 1_000);
+
+        service.remember_token(&revoked.jti, revoked.exp);
+
+        assert!(service.is_revoked(&revoked));
+        assert!(!service.is_revoked(&other));
+    }
+
+    #[test]
+    fn test_revoked_session() {
+        let service = TokenRevocationService::new(1);
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let mut in_session = create_test_claims(user_id, 1_000);
+        in_session.sid = Some(session_id.to_string());
+        let mut other_session = create_test_claims(user_id, 1_000);
+        other_session.sid = Some(Uuid::new_v4().to_string());
+
+        service.remember_session(session_id);
+
+        assert!(service.is_revoked(&in_session));
+        assert!(!service.is_revoked(&other_session));
+        assert!(!service.is_revoked(&create_test_claims(user_id, 1_000)));
+    }
+
+    #[test]
+    fn test_user_cutoff() {
+        let service = TokenRevocationService::new(1);
+        let user_id = Uuid::new_v4();
+
+        service.remember_cutoff(user_id, 2_000);
+        // An older cutoff never moves the boundary backwards
+        service.remember_cutoff(user_id, 1_500);
+
+        assert!(service.is_revoked(&create_test_claims(user_id, 1_999)));
+        assert!(service.is_revoked(&create_test_claims(user_id, 2_000)));
+        assert!(!service.is_revoked(&create_test_claims(user_id, 2_001)));
+        assert!(!service.is_revoked(&create_test_claims(Uuid::new_v4(), 1_000)));
+    }
+}
@@ -1,5 +1,10 @@
 use crate::application::config::AppConfig;
-use crate::application::services::{firebase_service::FirebaseService, jwt_service::JwtService};
+use crate::application::services::{
+    api_key_service::ApiKeyService, identity_provider::IdentityProviders, jwt_service::JwtService,
+    permission_version_service::PermissionVersionService, rate_limit_service::RateLimitService,
+    refresh_token_service::RefreshTokenService, session_service::SessionService,
+    test_issuer::TestIssuer, token_revocation_service::TokenRevocationService,
+};
 use sqlx::PgPool;
 use std::sync::Arc;
 
@@ -7,21 +12,44 @@ use std::sync::Arc;
 pub struct AppState {
     pub db_pool: PgPool,
     pub jwt_service: Arc<JwtService>,
-    pub firebase_service: Arc<FirebaseService>,
+    pub identity_providers: Arc<IdentityProviders>,
+    pub refresh_token_service: Arc<RefreshTokenService>,
+    pub session_service: Arc<SessionService>,
+    pub token_revocation_service: Arc<TokenRevocationService>,
+    pub permission_version_service: Arc<PermissionVersionService>,
+    pub api_key_service: Arc<ApiKeyService>,
+    pub rate_limit_service: Arc<RateLimitService>,
+    /// Only present in `AUTH_MODE=test-issuer`
+    pub test_issuer: Option<Arc<TestIssuer>>,
     pub config: AppConfig,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         db_pool: PgPool,
         jwt_service: Arc<JwtService>,
-        firebase_service: Arc<FirebaseService>,
+        identity_providers: Arc<IdentityProviders>,
+        refresh_token_service: Arc<RefreshTokenService>,
+        session_service: Arc<SessionService>,
+        token_revocation_service: Arc<TokenRevocationService>,
+        permission_version_service: Arc<PermissionVersionService>,
+        api_key_service: Arc<ApiKeyService>,
+        rate_limit_service: Arc<RateLimitService>,
+        test_issuer: Option<Arc<TestIssuer>>,
         config: AppConfig,
     ) -> Self {
         Self {
             db_pool,
             jwt_service,
-            firebase_service,
+            identity_providers,
+            refresh_token_service,
+            session_service,
+            token_revocation_service,
+            permission_version_service,
+            api_key_service,
+            rate_limit_service,
+            test_issuer,
             config,
         }
     }
@@ -0,0 +1,161 @@
+use anyhow::{bail, Context};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use std::path::Path;
+use uuid::{NoContext, Timestamp, Uuid};
+
+/// ISO 3166-1 countries
+const COUNTRIES: &str = include_str!("countries.json");
+
+/// Version of the export file format
+const EXPORT_VERSION: u32 = 1;
+
+/// Tables in an export, in an order that satisfies their foreign keys on
+/// import. Credentials, sessions and logs are left out on purpose.
+const EXPORTED_TABLES: [&str; 9] = [
+    "countries",
+    "users",
+    "user_identities",
+    "roles",
+    "permissions",
+    "role_permissions",
+    "user_roles",
+    "boats",
+    "boat_owners",
+];
+
+#[derive(Debug, Deserialize)]
+struct CountrySeed {
+    iso_name: String,
+    iso_alpha_2: String,
+    iso_alpha_3: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Export {
+    version: u32,
+    exported_at: String,
+    tables: Map<String, Value>,
+}
+
+/// Add the countries that are missing. Existing countries keep their ids and names.
+pub async fn seed(pool: &PgPool) -> anyhow::Result<()> {
+    let countries: Vec<CountrySeed> = serde_json::from_str(COUNTRIES)?;
+
+    let mut tx = pool.begin().await?;
+    let mut added = 0;
+    for country in &countries {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO countries (id, iso_name, iso_alpha_2, iso_alpha_3)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (iso_alpha_2) DO NOTHING
+            "#,
+            Uuid::new_v7(Timestamp::now(NoContext)),
+            country.iso_name,
+            country.iso_alpha_2,
+            country.iso_alpha_3
+        )
+        .execute(&mut *tx)
+        .await?;
+        added += result.rows_affected();
+    }
+    tx.commit().await?;
+
+    println!("Added {} of {} countries", added, countries.len());
+    Ok(())
+}
+
+pub async fn export(pool: &PgPool, output: &Path) -> anyhow::Result<()> {
+    let mut tables = Map::new();
+    for table in EXPORTED_TABLES {
+        let rows: String = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(json_agg(t), '[]'::json)::text FROM {} t",
+            table
+        ))
+        .fetch_one(pool)
+        .await
+        .with_context(|| format!("Failed to export {}", table))?;
+        let rows: Value = serde_json::from_str(&rows)?;
+        println!(
+            "Exported {} rows of {}",
+            rows.as_array().map_or(0, Vec::len),
+            table
+        );
+        tables.insert(table.to_string(), rows);
+    }
+
+    let export = Export {
+        version: EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        tables,
+    };
+    std::fs::write(output, serde_json::to_string_pretty(&export)?)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    Ok(())
+}
+
+/// Import everything in one transaction, so a failing table leaves the
+/// database untouched
+pub async fn import(pool: &PgPool, input: &Path) -> anyhow::Result<()> {
+    let file = std::fs::read_to_string(input)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+    let export: Export = serde_json::from_str(&file).context("Not a windspire-admin export")?;
+    if export.version != EXPORT_VERSION {
+        bail!("Unsupported export version {}", export.version);
+    }
+    if let Some(unknown) = export
+        .tables
+        .keys()
+        .find(|table| !EXPORTED_TABLES.contains(&table.as_str()))
+    {
+        bail!("Unknown table in export: {}", unknown);
+    }
+
+    let mut tx = pool.begin().await?;
+    for table in EXPORTED_TABLES {
+        let Some(rows) = export.tables.get(table) else {
+            continue;
+        };
+        let result = sqlx::query(&format!(
+            "INSERT INTO {table} SELECT * FROM json_populate_recordset(NULL::{table}, $1::text::json) ON CONFLICT DO NOTHING"
+        ))
+        .bind(rows.to_string())
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to import {}", table))?;
+        println!(
+            "Imported {} of {} rows of {}",
+            result.rows_affected(),
+            rows.as_array().map_or(0, Vec::len),
+            table
+        );
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_country_seed_is_well_formed() {
+        let countries: Vec<CountrySeed> = serde_json::from_str(COUNTRIES).unwrap();
+
+        let mut codes = HashSet::new();
+        for country in &countries {
+            assert_eq!(country.iso_alpha_2.len(), 2, "{}", country.iso_name);
+            assert_eq!(country.iso_alpha_3.len(), 3, "{}", country.iso_name);
+            assert!(
+                codes.insert(&country.iso_alpha_2),
+                "{}",
+                country.iso_alpha_2
+            );
+        }
+        assert!(countries.iter().any(|c| c.iso_alpha_3 == "NOR"));
+    }
+}
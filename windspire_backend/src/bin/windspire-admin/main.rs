@@ -0,0 +1,218 @@
+//! Administration of a Windspire deployment from the command line. Reads the
+//! same configuration as the server (`.env` / environment) and works directly
+//! against its database.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::time::Duration;
+use windspire_backend::application::config::AppConfig;
+
+mod data;
+mod migrations;
+mod roles;
+mod users;
+
+#[derive(Debug, Parser)]
+#[command(name = "windspire-admin", about = "Administer a Windspire deployment")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Issue an access token for a user, with the user's current roles and permissions
+    Token {
+        /// User id or email address
+        user: String,
+        /// Lifetime of the token (defaults to JWT_EXPIRATION_HOURS)
+        #[arg(long)]
+        hours: Option<i64>,
+    },
+    /// Manage users
+    #[command(subcommand)]
+    Users(UsersCommand),
+    /// Grant or revoke roles
+    #[command(subcommand)]
+    Roles(RolesCommand),
+    /// Apply or revert database migrations
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Add missing reference data (ISO 3166 countries)
+    Seed,
+    /// Write users, roles, boats and reference data to a JSON file
+    Export {
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Load a file written by `export`, skipping rows that already exist
+    Import {
+        #[arg(long, short)]
+        input: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum UsersCommand {
+    /// Create a user, who can then sign in with an identity for the same verified email
+    Create {
+        #[arg(long)]
+        email: String,
+        #[arg(long)]
+        first_name: String,
+        #[arg(long)]
+        last_name: String,
+        #[arg(long)]
+        phone: Option<String>,
+        /// ISO 3166 alpha-2 or alpha-3 country code
+        #[arg(long, default_value = "NO")]
+        country: String,
+        /// Roles to grant, e.g. `--role user --role moderator`
+        #[arg(long = "role")]
+        roles: Vec<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RolesCommand {
+    /// List the user's role assignments
+    List {
+        /// User id or email address
+        user: String,
+    },
+    /// Grant a role, or replace the window of an existing assignment
+    Grant {
+        /// User id or email address
+        user: String,
+        role: String,
+        /// Start of the assignment (RFC 3339), immediately when omitted
+        #[arg(long)]
+        valid_from: Option<DateTime<Utc>>,
+        /// End of the assignment (RFC 3339), never when omitted
+        #[arg(long)]
+        valid_until: Option<DateTime<Utc>>,
+    },
+    /// Revoke a role
+    Revoke {
+        /// User id or email address
+        user: String,
+        role: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum MigrateCommand {
+    /// Apply pending migrations
+    Run,
+    /// Revert the most recently applied migrations
+    Revert {
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// List migrations and whether they are applied
+    Status,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    let config = AppConfig::from_env().context("Failed to load configuration")?;
+    let pool = connect(&config).await?;
+
+    match cli.command {
+        Command::Token { user, hours } => users::issue_token(&pool, &config, &user, hours).await,
+        Command::Users(UsersCommand::Create {
+            email,
+            first_name,
+            last_name,
+            phone,
+            country,
+            roles,
+        }) => {
+            let new_user = users::NewUser {
+                email,
+                first_name,
+                last_name,
+                phone,
+                country,
+            };
+            users::create_user(&pool, new_user, &roles).await
+        }
+        Command::Roles(RolesCommand::List { user }) => roles::list(&pool, &user).await,
+        Command::Roles(RolesCommand::Grant {
+            user,
+            role,
+            valid_from,
+            valid_until,
+        }) => roles::grant(&pool, &user, &role, valid_from, valid_until).await,
+        Command::Roles(RolesCommand::Revoke { user, role }) => {
+            roles::revoke(&pool, &user, &role).await
+        }
+        Command::Migrate(MigrateCommand::Run) => migrations::run(&pool).await,
+        Command::Migrate(MigrateCommand::Revert { steps }) => {
+            migrations::revert(&pool, steps).await
+        }
+        Command::Migrate(MigrateCommand::Status) => migrations::status(&pool).await,
+        Command::Seed => data::seed(&pool).await,
+        Command::Export { output } => data::export(&pool, &output).await,
+        Command::Import { input } => data::import(&pool, &input).await,
+    }
+}
+
+async fn connect(config: &AppConfig) -> anyhow::Result<PgPool> {
+    PgPoolOptions::new()
+        .max_connections(2)
+        .acquire_timeout(Duration::from_secs(15))
+        .connect(&config.database_url)
+        .await
+        .context("Failed to connect to the database")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_role_grant() {
+        let cli = Cli::try_parse_from([
+            "windspire-admin",
+            "roles",
+            "grant",
+            "someone@example.com",
+            "moderator",
+            "--valid-until",
+            "2026-12-31T00:00:00Z",
+        ])
+        .unwrap();
+
+        match cli.command {
+            Command::Roles(RolesCommand::Grant {
+                user,
+                role,
+                valid_from,
+                valid_until,
+            }) => {
+                assert_eq!(user, "someone@example.com");
+                assert_eq!(role, "moderator");
+                assert_eq!(valid_from, None);
+                assert_eq!(
+                    valid_until.map(|t| t.to_rfc3339()),
+                    Some("2026-12-31T00:00:00+00:00".to_string())
+                );
+            }
+            command => panic!("Unexpected command: {:?}", command),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::Context;
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+pub async fn run(pool: &PgPool) -> anyhow::Result<()> {
+    MIGRATOR
+        .run(pool)
+        .await
+        .context("Failed to run migrations")?;
+    println!("Migrations are up to date");
+    Ok(())
+}
+
+/// Revert the last `steps` applied migrations. Migrations without a down
+/// script cannot be reverted and stop the revert.
+pub async fn revert(pool: &PgPool, steps: usize) -> anyhow::Result<()> {
+    let mut applied = applied_versions(pool).await?;
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    let reverted = &applied[..steps.min(applied.len())];
+    if let Some(irreversible) = reverted.iter().find(|version| {
+        !MIGRATOR
+            .iter()
+            .any(|m| m.version == **version && m.migration_type.is_down_migration())
+    }) {
+        anyhow::bail!("Migration {} cannot be reverted", irreversible);
+    }
+
+    // Everything applied after the target version is reverted
+    let target = applied.get(steps).copied().unwrap_or(0);
+    MIGRATOR
+        .undo(pool, target)
+        .await
+        .context("Failed to revert migrations")?;
+    for version in reverted {
+        println!("Reverted {}", version);
+    }
+    Ok(())
+}
+
+pub async fn status(pool: &PgPool) -> anyhow::Result<()> {
+    let applied: HashSet<i64> = applied_versions(pool).await?.into_iter().collect();
+    for migration in MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+    {
+        println!(
+            "{}\t{}\t{}",
+            migration.version,
+            if applied.contains(&migration.version) {
+                "applied"
+            } else {
+                "pending"
+            },
+            migration.description
+        );
+    }
+    Ok(())
+}
+
+async fn applied_versions(pool: &PgPool) -> anyhow::Result<Vec<i64>> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    Ok(conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect())
+}
@@ -0,0 +1,91 @@
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use windspire_backend::domain::models::rbac::{
+    AssignRoleOutcome, RemoveRoleOutcome, UserRoleCreate,
+};
+use windspire_backend::domain::repositories::role_repository::RoleRepository;
+use windspire_backend::infrastructure::repositories::sqlx_role_repository::SqlxRoleRepository;
+
+use crate::users::resolve_user;
+
+pub async fn list(pool: &PgPool, user: &str) -> anyhow::Result<()> {
+    let user_id = resolve_user(pool, user).await?;
+    let assignments = SqlxRoleRepository
+        .get_user_role_assignments(pool, user_id)
+        .await?;
+
+    if assignments.is_empty() {
+        println!("No roles");
+    }
+    for assignment in assignments {
+        println!(
+            "{}\tfrom {}\tuntil {}",
+            assignment.role.name,
+            assignment.valid_from.to_rfc3339(),
+            assignment
+                .valid_until
+                .map_or("-".to_string(), |until| until.to_rfc3339())
+        );
+    }
+    Ok(())
+}
+
+/// Unlike `POST /api/v1/admin/users/{user_id}/roles` this may hand out any role,
+/// including admin, as whoever runs it already has the database
+pub async fn grant(
+    pool: &PgPool,
+    user: &str,
+    role: &str,
+    valid_from: Option<DateTime<Utc>>,
+    valid_until: Option<DateTime<Utc>>,
+) -> anyhow::Result<()> {
+    if let (Some(from), Some(until)) = (valid_from, valid_until) {
+        if until <= from {
+            bail!("--valid-until must be after --valid-from");
+        }
+    }
+
+    let user_id = resolve_user(pool, user).await?;
+    let role = SqlxRoleRepository
+        .get_role_by_name(pool, role)
+        .await
+        .with_context(|| format!("Unknown role {}", role))?;
+
+    let assignment = UserRoleCreate {
+        user_id,
+        role_id: role.id,
+        valid_from,
+        valid_until,
+        assigned_by: None,
+    };
+    match SqlxRoleRepository
+        .assign_role_to_user(pool, &assignment)
+        .await?
+    {
+        AssignRoleOutcome::Assigned => println!("Granted {} to {}", role.name, user),
+        AssignRoleOutcome::Updated => println!("Updated {} of {}", role.name, user),
+        AssignRoleOutcome::LastAdmin => {
+            bail!("The admin role of the last admin must start now and never end")
+        }
+    }
+    Ok(())
+}
+
+pub async fn revoke(pool: &PgPool, user: &str, role: &str) -> anyhow::Result<()> {
+    let user_id = resolve_user(pool, user).await?;
+    let role = SqlxRoleRepository
+        .get_role_by_name(pool, role)
+        .await
+        .with_context(|| format!("Unknown role {}", role))?;
+
+    match SqlxRoleRepository
+        .remove_role_from_user(pool, user_id, role.id)
+        .await?
+    {
+        RemoveRoleOutcome::Removed => println!("Revoked {} from {}", role.name, user),
+        RemoveRoleOutcome::NotAssigned => bail!("{} does not have the role {}", user, role.name),
+        RemoveRoleOutcome::LastAdmin => bail!("Cannot remove the admin role from the last admin"),
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anyhow::{bail, Context};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+use windspire_backend::application::config::AppConfig;
+use windspire_backend::application::handlers::auth_handlers::load_auth_user;
+use windspire_backend::application::services::jwt_service::JwtService;
+use windspire_backend::domain::interface::country_repository::CountryRepository;
+use windspire_backend::domain::models::user::UserCreate;
+use windspire_backend::domain::repositories::user_repository::UserRepository;
+use windspire_backend::infrastructure::repositories::sqlx_country_repository::SqlxCountryRepository;
+use windspire_backend::infrastructure::repositories::sqlx_user_repository::SqlxUserRepository;
+
+use crate::roles;
+
+pub struct NewUser {
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub phone: Option<String>,
+    pub country: String,
+}
+
+/// The id of the user given by id or by the email of an active account
+pub async fn resolve_user(pool: &PgPool, user: &str) -> anyhow::Result<Uuid> {
+    if let Ok(user_id) = Uuid::parse_str(user) {
+        return match SqlxUserRepository.get_user_by_id(pool, user_id).await {
+            Ok(user) => Ok(user.id),
+            Err(sqlx::Error::RowNotFound) => bail!("No user with id {}", user_id),
+            Err(e) => Err(e.into()),
+        };
+    }
+
+    match SqlxUserRepository.get_user_by_email(pool, user).await {
+        Ok(user) => Ok(user.id),
+        Err(sqlx::Error::RowNotFound) => bail!("No active user with email {}", user),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Print an access token signed with the server's keys. It is not tied to a
+/// session; `POST /api/v1/auth/logout` or logging out everywhere revokes it.
+pub async fn issue_token(
+    pool: &PgPool,
+    config: &AppConfig,
+    user: &str,
+    hours: Option<i64>,
+) -> anyhow::Result<()> {
+    let user_id = resolve_user(pool, user).await?;
+    let auth_user = load_auth_user(pool, user_id)
+        .await
+        .context("Failed to load the user's roles")?;
+
+    let mut jwt_config = config.jwt.clone();
+    if let Some(hours) = hours {
+        jwt_config.expiration_hours = hours;
+    }
+    let jwt_service = JwtService::new(jwt_config).context("Failed to load JWT signing keys")?;
+    let token = jwt_service.generate_token(&auth_user)?;
+
+    eprintln!(
+        "Token for {} with roles [{}]",
+        auth_user.email,
+        auth_user.roles.join(", ")
+    );
+    println!("{}", token);
+    Ok(())
+}
+
+pub async fn create_user(pool: &PgPool, new_user: NewUser, roles: &[String]) -> anyhow::Result<()> {
+    let country = SqlxCountryRepository
+        .get_country_by_code(pool, new_user.country.to_uppercase())
+        .await
+        .with_context(|| format!("Unknown country {}", new_user.country))?;
+
+    let user_create = UserCreate {
+        first_name: new_user.first_name,
+        last_name: new_user.last_name,
+        email: new_user.email,
+        phone: new_user.phone,
+        country_id: country.id,
+    };
+    if let Err(e) = user_create.validate() {
+        bail!("Invalid user: {}", e);
+    }
+
+    let user = SqlxUserRepository
+        .insert_user(pool, user_create)
+        .await
+        .context("Failed to create user")?;
+    println!("Created user {} ({})", user.email, user.id);
+
+    for role in roles {
+        roles::grant(pool, &user.id.to_string(), role, None, None).await?;
+    }
+    Ok(())
+}
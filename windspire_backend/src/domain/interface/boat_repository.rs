@@ -1,6 +1,7 @@
 use anyhow::Result;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::{Error, PgPool};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::domain::models::boat::{Boat, BoatCreate, BoatUpdate};
@@ -18,7 +19,7 @@ impl Default for PaginationParams {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PaginatedResult<T> {
     pub data: Vec<T>,
@@ -4,7 +4,7 @@ use uuid::Uuid;
 
 use crate::domain::models::country::{Country, CountryCreate, CountryUpdate};
 
-pub(crate) trait CountryRepository {
+pub trait CountryRepository {
     fn get_country_by_id(
         &self,
         pool: &PgPool,
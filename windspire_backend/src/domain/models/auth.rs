@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +13,7 @@ pub struct AuthUser {
     pub avatar_url: Option<String>,
     pub roles: Vec<String>,
     pub permissions: Vec<String>,
+    pub permission_version: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,8 +24,25 @@ pub struct Claims {
     pub picture: Option<String>, // Avatar URL
     pub roles: Vec<String>,
     pub permissions: Vec<String>,
-    pub iat: i64, // Issued at
-    pub exp: i64, // Expiration time
+    #[serde(default)]
+    pub pv: i64, // Permission version the roles and permissions were resolved at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>, // Set on impersonation tokens: who is acting as `sub`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>, // Session the token was issued to, see user_sessions
+    pub iss: String, // Issuer
+    pub aud: String, // Audience
+    pub jti: String, // Token ID, used to revoke a single token
+    pub iat: i64,    // Issued at
+    pub nbf: i64,    // Not before
+    pub exp: i64,    // Expiration time
+}
+
+/// The user really making requests with an impersonation token (RFC 8693 `act` claim)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Actor {
+    pub sub: String, // The impersonating user's ID
+    pub email: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -55,4 +74,6 @@ pub struct AuthError {
 pub struct AuthContext {
     pub user: AuthUser,
     pub token: String,
+    pub api_key_id: Option<Uuid>, // Set when authenticated with an API key instead of a JWT
+    pub impersonator: Option<Actor>, // Set when authenticated with an impersonation token
 }
@@ -1,12 +1,13 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
 static REGEX_SAIL_NUMBER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Z]{3}\d{1,5}$").unwrap());
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Boat {
     pub id: Uuid,
@@ -17,7 +18,7 @@ pub struct Boat {
     pub country_id: Uuid,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct BoatCreate {
     #[validate(length(min = 2, message = "Boat name must contain 2 at least characters"))]
@@ -31,7 +32,7 @@ pub struct BoatCreate {
     pub country_id: Uuid,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct BoatUpdate {
     #[validate(length(min = 2, message = "Boat name must contain 2 at least characters"))]
@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::domain::models::boat::Boat;
-use crate::domain::models::user::UserWithCountry;
+use crate::domain::models::user::{User, UserWithCountry};
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct BoatOwner {
@@ -10,12 +11,28 @@ pub struct BoatOwner {
     pub user_id: Uuid,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Result of removing an owner; a boat always keeps at least one owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveOwnerOutcome {
+    Removed,
+    NotAnOwner,
+    LastOwner,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BoatWithOwners {
     pub boat: Boat,
     pub owners: Vec<UserWithCountry>,
 }
 
+/// A user together with the boats they own
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserProfile {
+    pub user: User,
+    pub boats: Vec<Boat>,
+    pub boat_count: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserWithBoats {
     pub user: UserWithCountry,
@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Deserialize, Serialize, Clone, sqlx::FromRow)]
+#[derive(Debug, Deserialize, Serialize, Clone, sqlx::FromRow, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Country {
     pub id: Uuid,
@@ -11,7 +12,7 @@ pub struct Country {
     pub iso_alpha_3: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CountryCreate {
     pub iso_name: String,
@@ -20,7 +21,7 @@ pub struct CountryCreate {
     #[validate(length(equal = 3, message = "ISO Alpha-3 code must be 3 characters"))]
     pub iso_alpha_3: String,
 }
-#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CountryUpdate {
     pub iso_name: String,
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Body of `POST /admin/users/{user_id}/impersonate`
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct ImpersonateUser {
+    #[validate(length(min = 1, max = 500, message = "Reason must be 1 to 500 characters"))]
+    pub reason: String, // Recorded in the audit log, e.g. a support ticket
+}
+
+/// What an impersonation audit entry records
+/// A token to act as another user, as handed out to the impersonator
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImpersonationToken {
+    pub token: String,
+    pub expires_in: i64, // Seconds
+    pub user: ImpersonatedUser,
+}
+
+/// The user an impersonation token acts as
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImpersonatedUser {
+    pub id: Uuid,
+    pub email: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImpersonationEvent {
+    TokenIssued,
+    Request,
+}
+
+impl ImpersonationEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImpersonationEvent::TokenIssued => "token_issued",
+            ImpersonationEvent::Request => "request",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImpersonationAuditCreate {
+    pub event: ImpersonationEvent,
+    pub actor_id: Uuid,
+    pub actor_email: String,
+    pub target_user_id: Uuid,
+    pub token_id: String, // The impersonation token's jti
+    pub method: String,
+    pub path: String,
+    pub status_code: Option<i32>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImpersonationAuditEntry {
+    pub id: Uuid,
+    pub event: String,
+    pub actor_id: Uuid,
+    pub actor_email: String,
+    pub target_user_id: Uuid,
+    pub token_id: String,
+    pub method: String,
+    pub path: String,
+    pub status_code: Option<i32>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filters for `GET /admin/impersonations`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImpersonationAuditFilter {
+    pub actor_id: Option<Uuid>,
+    pub target_user_id: Option<Uuid>,
+}
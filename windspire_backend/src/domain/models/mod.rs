@@ -1,6 +1,13 @@
+pub mod api_key;
 pub mod auth;
 pub mod boat;
 pub mod boat_owner;
 pub mod country;
+pub mod impersonation;
+pub mod permission_matcher;
 pub mod rbac;
+pub mod refresh_token;
+pub mod token_revocation;
 pub mod user;
+pub mod user_identity;
+pub mod user_session;
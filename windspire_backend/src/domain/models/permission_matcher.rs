@@ -0,0 +1,189 @@
+use std::fmt::Display;
+
+/// The wildcard segment of a grant, e.g. `boats:*` or `*:read`
+pub const WILDCARD: &str = "*";
+
+/// A granted or required permission: `resource:action`, or scoped to a single
+/// instance of a parent resource as `scope:{id}:resource:action`, e.g.
+/// `club:{id}:boats:write`. Any segment of a grant may be `*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionPattern<'a> {
+    pub scope: Option<(&'a str, &'a str)>,
+    pub resource: &'a str,
+    pub action: &'a str,
+}
+
+impl<'a> PermissionPattern<'a> {
+    /// `None` for anything that is not two or four non-empty segments
+    pub fn parse(permission: &'a str) -> Option<Self> {
+        let segments: Vec<&str> = permission.split(':').collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return None;
+        }
+
+        match segments.as_slice() {
+            [resource, action] => Some(Self {
+                scope: None,
+                resource,
+                action,
+            }),
+            [scope, scope_id, resource, action] => Some(Self {
+                scope: Some((scope, scope_id)),
+                resource,
+                action,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether this grant allows `required`. Unscoped grants apply in every
+    /// scope; scoped grants only apply within their own scope.
+    pub fn grants(&self, required: &PermissionPattern) -> bool {
+        let scope_matches = match (self.scope, required.scope) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some((scope, scope_id)), Some((required_scope, required_id))) => {
+                segment_matches(scope, required_scope) && segment_matches(scope_id, required_id)
+            }
+        };
+
+        scope_matches
+            && segment_matches(self.resource, required.resource)
+            && segment_matches(self.action, required.action)
+    }
+}
+
+fn segment_matches(granted: &str, required: &str) -> bool {
+    granted == WILDCARD || granted == required
+}
+
+/// Whether the `granted` permission allows `required`. Malformed permissions
+/// never match.
+pub fn grants(granted: &str, required: &str) -> bool {
+    match (
+        PermissionPattern::parse(granted),
+        PermissionPattern::parse(required),
+    ) {
+        (Some(granted), Some(required)) => granted.grants(&required),
+        _ => false,
+    }
+}
+
+/// Whether any of the `granted` permissions allows `required`
+pub fn any_grants<S: AsRef<str>>(granted: &[S], required: &str) -> bool {
+    let Some(required) = PermissionPattern::parse(required) else {
+        return false;
+    };
+
+    granted.iter().any(|granted| {
+        PermissionPattern::parse(granted.as_ref()).is_some_and(|g| g.grants(&required))
+    })
+}
+
+/// `permission` limited to one instance of `scope`, e.g.
+/// `scoped("club", club_id, "boats:write")` is `club:{club_id}:boats:write`
+pub fn scoped(scope: &str, scope_id: impl Display, permission: &str) -> String {
+    format!("{}:{}:{}", scope, scope_id, permission)
+}
+
+/// Drop grants that are duplicates or covered by another grant, so tokens only
+/// carry what the matcher needs, e.g. `boats:read` next to `boats:*`
+pub fn compact(granted: &[String]) -> Vec<String> {
+    let mut compacted: Vec<String> = Vec::with_capacity(granted.len());
+    for (index, permission) in granted.iter().enumerate() {
+        let covered = granted.iter().enumerate().any(|(other_index, other)| {
+            other_index != index
+                && grants(other, permission)
+                // Of two grants covering each other, keep the first
+                && !(grants(permission, other) && other_index > index)
+        });
+        if !covered {
+            compacted.push(permission.clone());
+        }
+    }
+    compacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_and_wildcard_grants() {
+        assert!(grants("boats:write", "boats:write"));
+        assert!(!grants("boats:write", "boats:read"));
+        assert!(grants("boats:*", "boats:delete"));
+        assert!(grants("*:read", "countries:read"));
+        assert!(!grants("*:read", "countries:write"));
+        assert!(grants("*:*", "users:delete"));
+        // `write` does not imply `write_own` or the other way around
+        assert!(!grants("users:write_own", "users:write"));
+    }
+
+    #[test]
+    fn test_scoped_grants() {
+        let club = "0198f7a8-8c1e-7b52-9d34-2f6c1a0b9e11";
+        let other_club = "0198f7a8-8c1e-7b52-9d34-2f6c1a0b9e12";
+        let club_boats_write = scoped("club", club, "boats:write");
+        assert_eq!(club_boats_write, format!("club:{}:boats:write", club));
+
+        assert!(grants(&club_boats_write, &club_boats_write));
+        assert!(!grants(
+            &club_boats_write,
+            &scoped("club", other_club, "boats:write")
+        ));
+        // A scoped grant gives no power outside its scope
+        assert!(!grants(&club_boats_write, "boats:write"));
+        assert!(!grants(
+            &club_boats_write,
+            &scoped("event", club, "boats:write")
+        ));
+
+        // Global and wildcard grants reach into every scope
+        assert!(grants("boats:write", &club_boats_write));
+        assert!(grants(
+            &scoped("club", club, "*:*"),
+            &scoped("club", club, "results:write")
+        ));
+        assert!(grants("club:*:boats:write", &club_boats_write));
+    }
+
+    #[test]
+    fn test_malformed_permissions_never_match() {
+        assert!(!grants("admin", "admin"));
+        assert!(!grants("boats:", "boats:"));
+        assert!(!grants("*", "boats:write"));
+        assert!(!grants("club:1:boats", "club:1:boats"));
+        assert!(!any_grants(&["*:*"], "not-a-permission"));
+    }
+
+    #[test]
+    fn test_any_grants() {
+        let granted = vec!["users:read_own".to_string(), "boats:*".to_string()];
+
+        assert!(any_grants(&granted, "boats:write"));
+        assert!(any_grants(&granted, "users:read_own"));
+        assert!(!any_grants(&granted, "users:read"));
+        assert!(!any_grants::<String>(&[], "boats:read"));
+    }
+
+    #[test]
+    fn test_compact() {
+        let granted: Vec<String> = [
+            "boats:read",
+            "boats:*",
+            "club:1:boats:write",
+            "users:read_own",
+            "boats:*",
+            "club:1:results:write",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+
+        assert_eq!(
+            compact(&granted),
+            vec!["boats:*", "users:read_own", "club:1:results:write"]
+        );
+    }
+}
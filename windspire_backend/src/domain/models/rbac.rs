@@ -1,8 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Role {
     pub id: Uuid,
     pub name: String,
@@ -10,7 +12,7 @@ pub struct Role {
     pub created_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Permission {
     pub id: Uuid,
     pub name: String,
@@ -20,11 +22,50 @@ pub struct Permission {
     pub created_at: Option<DateTime<Utc>>,
 }
 
+/// A role together with the permissions it grants
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleWithPermissions {
+    pub role: Role,
+    pub permissions: Vec<Permission>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserRole {
     pub user_id: Uuid,
     pub role_id: Uuid,
     pub assigned_at: DateTime<Utc>,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: Option<DateTime<Utc>>, // Never expires when None
+    pub assigned_by: Option<Uuid>,
+}
+
+/// A role held by a user, with the window in which the assignment applies
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleAssignment {
+    #[serde(flatten)]
+    pub role: Role,
+    pub assigned_at: Option<DateTime<Utc>>,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub assigned_by: Option<Uuid>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserRoleCreate {
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+    pub valid_from: Option<DateTime<Utc>>, // Starts immediately when None
+    pub valid_until: Option<DateTime<Utc>>,
+    pub assigned_by: Option<Uuid>, // None when assigned outside the API, e.g. by windspire-admin
+}
+
+impl UserRoleCreate {
+    /// Whether the assignment is in effect at `now` and never ends. Replacing
+    /// the last permanent admin assignment with any other window, a later
+    /// start included, would leave no admin.
+    pub fn is_permanent_at(&self, now: DateTime<Utc>) -> bool {
+        self.valid_until.is_none() && self.valid_from.is_none_or(|from| from <= now)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,29 +81,67 @@ pub struct UserWithRoles {
     pub email: String,
     pub first_name: String,
     pub last_name: String,
+    /// Bumped whenever the user's roles or role permissions change
+    pub permission_version: i64,
     pub roles: Vec<Role>,
     pub permissions: Vec<Permission>,
 }
 
 // DTOs for creating roles and permissions
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
 pub struct CreateRole {
+    #[validate(length(min = 1, max = 50, message = "Name must be 1 to 50 characters"))]
     pub name: String,
+    #[validate(length(max = 255, message = "Description must be at most 255 characters"))]
     pub description: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Resource and action are taken from the name, e.g. `boats:write` or
+/// `club:{id}:boats:write`
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
 pub struct CreatePermission {
+    #[validate(length(min = 1, max = 255, message = "Name must be 1 to 255 characters"))]
     pub name: String,
+    #[validate(length(max = 255, message = "Description must be at most 255 characters"))]
     pub description: String,
-    pub resource: String,
-    pub action: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Body of `POST /admin/users/{user_id}/roles`. Assigning a role the user
+/// already has replaces the window of the existing assignment.
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
 pub struct AssignRole {
-    pub user_id: Uuid,
     pub role_id: Uuid,
+    pub valid_from: Option<DateTime<Utc>>, // Starts immediately when omitted
+    pub valid_until: Option<DateTime<Utc>>, // Never expires when omitted
+}
+
+/// The result of giving a role to a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssignRoleOutcome {
+    Assigned,
+    /// The user already had the role; its window was replaced
+    Updated,
+    /// Refused: the window would end or postpone the last permanent admin
+    /// assignment
+    LastAdmin,
+}
+
+/// The result of taking a role away from a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveRoleOutcome {
+    Removed,
+    NotAssigned,
+    /// Refused: the user is the only permanent admin left
+    LastAdmin,
+}
+
+/// The result of deleting a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteUserOutcome {
+    Deleted,
+    NotFound,
+    /// Refused: the user is the only permanent admin left
+    LastAdmin,
 }
 
 // Common permission patterns
@@ -71,6 +150,7 @@ pub const PERMISSION_USERS_WRITE: &str = "users:write";
 pub const PERMISSION_USERS_DELETE: &str = "users:delete";
 pub const PERMISSION_USERS_READ_OWN: &str = "users:read_own";
 pub const PERMISSION_USERS_WRITE_OWN: &str = "users:write_own";
+pub const PERMISSION_USERS_IMPERSONATE: &str = "users:impersonate";
 
 pub const PERMISSION_COUNTRIES_READ: &str = "countries:read";
 pub const PERMISSION_COUNTRIES_WRITE: &str = "countries:write";
@@ -79,8 +159,201 @@ pub const PERMISSION_COUNTRIES_DELETE: &str = "countries:delete";
 pub const PERMISSION_BOATS_READ: &str = "boats:read";
 pub const PERMISSION_BOATS_WRITE: &str = "boats:write";
 pub const PERMISSION_BOATS_DELETE: &str = "boats:delete";
+pub const PERMISSION_BOATS_CREATE: &str = "boats:create";
+pub const PERMISSION_BOATS_WRITE_OWN: &str = "boats:write_own";
+
+pub const PERMISSION_ROLES_READ: &str = "roles:read";
+pub const PERMISSION_ROLES_WRITE: &str = "roles:write";
 
 // Default roles
 pub const ROLE_ADMIN: &str = "admin";
 pub const ROLE_MODERATOR: &str = "moderator";
 pub const ROLE_USER: &str = "user";
+/// Read-only role for accounts whose email is not verified yet
+pub const ROLE_RESTRICTED: &str = "restricted";
+
+/// Roles the code refers to by name, which therefore cannot be renamed or deleted
+pub const BUILT_IN_ROLES: [&str; 4] = [ROLE_ADMIN, ROLE_MODERATOR, ROLE_USER, ROLE_RESTRICTED];
+
+/// Every permission the code can check. Routes refer to these instead of raw
+/// strings, and startup verifies each of them is present in `permissions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermissionName {
+    UsersRead,
+    UsersWrite,
+    UsersDelete,
+    UsersReadOwn,
+    UsersWriteOwn,
+    UsersImpersonate,
+    CountriesRead,
+    CountriesWrite,
+    CountriesDelete,
+    BoatsRead,
+    BoatsWrite,
+    BoatsDelete,
+    BoatsCreate,
+    BoatsWriteOwn,
+    RolesRead,
+    RolesWrite,
+}
+
+impl PermissionName {
+    pub const ALL: [PermissionName; 16] = [
+        PermissionName::UsersRead,
+        PermissionName::UsersWrite,
+        PermissionName::UsersDelete,
+        PermissionName::UsersReadOwn,
+        PermissionName::UsersWriteOwn,
+        PermissionName::UsersImpersonate,
+        PermissionName::CountriesRead,
+        PermissionName::CountriesWrite,
+        PermissionName::CountriesDelete,
+        PermissionName::BoatsRead,
+        PermissionName::BoatsWrite,
+        PermissionName::BoatsDelete,
+        PermissionName::BoatsCreate,
+        PermissionName::BoatsWriteOwn,
+        PermissionName::RolesRead,
+        PermissionName::RolesWrite,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            PermissionName::UsersRead => PERMISSION_USERS_READ,
+            PermissionName::UsersWrite => PERMISSION_USERS_WRITE,
+            PermissionName::UsersDelete => PERMISSION_USERS_DELETE,
+            PermissionName::UsersReadOwn => PERMISSION_USERS_READ_OWN,
+            PermissionName::UsersWriteOwn => PERMISSION_USERS_WRITE_OWN,
+            PermissionName::UsersImpersonate => PERMISSION_USERS_IMPERSONATE,
+            PermissionName::CountriesRead => PERMISSION_COUNTRIES_READ,
+            PermissionName::CountriesWrite => PERMISSION_COUNTRIES_WRITE,
+            PermissionName::CountriesDelete => PERMISSION_COUNTRIES_DELETE,
+            PermissionName::BoatsRead => PERMISSION_BOATS_READ,
+            PermissionName::BoatsWrite => PERMISSION_BOATS_WRITE,
+            PermissionName::BoatsDelete => PERMISSION_BOATS_DELETE,
+            PermissionName::BoatsCreate => PERMISSION_BOATS_CREATE,
+            PermissionName::BoatsWriteOwn => PERMISSION_BOATS_WRITE_OWN,
+            PermissionName::RolesRead => PERMISSION_ROLES_READ,
+            PermissionName::RolesWrite => PERMISSION_ROLES_WRITE,
+        }
+    }
+}
+
+impl std::fmt::Display for PermissionName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for PermissionName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PermissionName::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == s)
+            .ok_or_else(|| format!("Unknown permission: {}", s))
+    }
+}
+
+/// A permission at the type level, for `RequirePermission<BoatsWrite>`
+pub trait PermissionMarker: Send + Sync + 'static {
+    const PERMISSION: PermissionName;
+}
+
+/// A permission with an `_own` variant that grants the same access to
+/// resources the caller owns
+pub trait OwnablePermission: PermissionMarker {
+    const OWN_PERMISSION: PermissionName;
+}
+
+macro_rules! permission_markers {
+    ($($marker:ident),* $(,)?) => {
+        $(
+            #[derive(Debug, Clone, Copy)]
+            pub struct $marker;
+
+            impl PermissionMarker for $marker {
+                const PERMISSION: PermissionName = PermissionName::$marker;
+            }
+        )*
+    };
+}
+
+permission_markers!(
+    UsersRead,
+    UsersWrite,
+    UsersDelete,
+    UsersImpersonate,
+    CountriesRead,
+    CountriesWrite,
+    CountriesDelete,
+    BoatsRead,
+    BoatsWrite,
+    BoatsDelete,
+    BoatsCreate,
+    RolesRead,
+    RolesWrite,
+);
+
+impl OwnablePermission for UsersRead {
+    const OWN_PERMISSION: PermissionName = PermissionName::UsersReadOwn;
+}
+
+impl OwnablePermission for UsersWrite {
+    const OWN_PERMISSION: PermissionName = PermissionName::UsersWriteOwn;
+}
+
+impl OwnablePermission for BoatsWrite {
+    const OWN_PERMISSION: PermissionName = PermissionName::BoatsWriteOwn;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_names_round_trip() {
+        for permission in PermissionName::ALL {
+            assert_eq!(
+                permission.as_str().parse::<PermissionName>(),
+                Ok(permission)
+            );
+        }
+        assert!("admin:write".parse::<PermissionName>().is_err());
+    }
+
+    #[test]
+    fn test_permanent_assignment() {
+        let now = Utc::now();
+        let assignment = |valid_from, valid_until| UserRoleCreate {
+            user_id: Uuid::new_v4(),
+            role_id: Uuid::new_v4(),
+            valid_from,
+            valid_until,
+            assigned_by: None,
+        };
+
+        assert!(assignment(None, None).is_permanent_at(now));
+        assert!(assignment(Some(now - chrono::Duration::days(1)), None).is_permanent_at(now));
+        assert!(!assignment(Some(now + chrono::Duration::days(1)), None).is_permanent_at(now));
+        assert!(!assignment(None, Some(now + chrono::Duration::days(1))).is_permanent_at(now));
+    }
+
+    #[test]
+    fn test_own_permissions() {
+        assert_eq!(UsersWrite::PERMISSION.as_str(), PERMISSION_USERS_WRITE);
+        assert_eq!(
+            UsersWrite::OWN_PERMISSION.as_str(),
+            PERMISSION_USERS_WRITE_OWN
+        );
+        assert_eq!(
+            UsersRead::OWN_PERMISSION.as_str(),
+            PERMISSION_USERS_READ_OWN
+        );
+        assert_eq!(
+            BoatsWrite::OWN_PERMISSION.as_str(),
+            PERMISSION_BOATS_WRITE_OWN
+        );
+    }
+}
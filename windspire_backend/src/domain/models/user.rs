@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -10,7 +11,7 @@ static REGEX_EMAIL: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^(?:[a-zA-Z0-9_'^&+/=?`{|}~.-]+)@(?:[a-zA-Z0-9-]+\.)+[a-zA-Z]{2,}$").unwrap()
 });
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct User {
     pub id: Uuid,
     pub first_name: String,
@@ -25,7 +26,7 @@ pub struct User {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserCreate {
     #[validate(length(min = 2, message = "First name must contain 2 at least characters"))]
@@ -40,7 +41,7 @@ pub struct UserCreate {
     pub country_id: Uuid,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Validate)]
+#[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserUpdate {
     #[validate(length(min = 2, message = "First name must contain 2 at least characters"))]
@@ -54,7 +55,7 @@ pub struct UserUpdate {
     pub country_id: Uuid,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserWithCountry {
     pub id: Uuid,
@@ -76,6 +77,9 @@ pub struct OAuthUserCreate {
     pub last_name: String,
     pub provider_id: String,
     pub provider_name: String,
+    pub provider_email: Option<String>, // As reported by the provider
+    pub email_verified: bool,
+    pub account_status: AccountStatus,
     pub avatar_url: Option<String>,
     pub country_id: Uuid, // Default country will be set
 }
@@ -90,3 +94,46 @@ pub struct UserByEmail {
     pub provider_name: Option<String>,
     pub country_id: Uuid,
 }
+
+/// Whether a user can use the app. Accounts signed up without a verified email
+/// are `Restricted` or `Pending` until the user signs in with a verified email.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountStatus {
+    Active,
+    /// Read-only access through the `restricted` role
+    Restricted,
+    /// No access at all; login is refused
+    Pending,
+}
+
+impl AccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Restricted => "restricted",
+            AccountStatus::Pending => "pending",
+        }
+    }
+}
+
+impl std::str::FromStr for AccountStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(AccountStatus::Active),
+            "restricted" => Ok(AccountStatus::Restricted),
+            "pending" => Ok(AccountStatus::Pending),
+            other => Err(format!("Unknown account status: {}", other)),
+        }
+    }
+}
+
+/// The account state reported by `/auth/me`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+pub struct AccountState {
+    pub status: AccountStatus,
+    /// Whether a linked identity has verified the user's email
+    pub email_verified: bool,
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// A login identity (provider + subject) linked to a user
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub provider_name: String,
+    pub provider_subject: String,
+    pub email: Option<String>, // As reported by the provider
+    pub email_verified: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserIdentityCreate {
+    pub user_id: Uuid,
+    pub provider_name: String,
+    pub provider_subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+}
+
+/// Body of `POST /auth/identities`: an ID token from the provider to link
+#[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
+pub struct LinkIdentity {
+    pub provider: String,
+    pub id_token: String,
+}
+
+/// The result of linking an identity to a user
+#[derive(Debug, Clone)]
+pub enum LinkIdentityOutcome {
+    Linked(UserIdentity),
+    AlreadyLinked(UserIdentity),
+    /// Refused: the identity belongs to another user
+    LinkedToOtherUser,
+}
+
+/// The result of unlinking an identity from a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnlinkIdentityOutcome {
+    Unlinked,
+    NotFound,
+    /// Refused: the user would have no way left to sign in
+    LastIdentity,
+}
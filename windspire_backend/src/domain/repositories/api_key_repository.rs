@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::models::api_key::{ApiKey, ApiKeyCreate, ServiceAccount};
+use crate::infrastructure::error::Error;
+
+#[async_trait]
+pub trait ApiKeyRepository {
+    /// Stores the key together with its permission scope in one transaction
+    async fn create_api_key(&self, pool: &PgPool, key: &ApiKeyCreate) -> Result<ApiKey, Error>;
+    async fn get_api_key_by_hash(&self, pool: &PgPool, key_hash: &str) -> Result<ApiKey, Error>;
+    async fn get_api_keys_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<ApiKey>, Error>;
+
+    /// Returns the number of keys revoked; 0 when the key does not belong to the user
+    async fn revoke_api_key(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        key_id: Uuid,
+    ) -> Result<u64, Error>;
+    async fn touch_api_key(&self, pool: &PgPool, key_id: Uuid) -> Result<(), Error>;
+
+    async fn create_service_account(
+        &self,
+        pool: &PgPool,
+        name: &str,
+        country_id: Uuid,
+    ) -> Result<ServiceAccount, Error>;
+    async fn get_service_account(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<ServiceAccount, Error>;
+    async fn get_service_accounts(&self, pool: &PgPool) -> Result<Vec<ServiceAccount>, Error>;
+    async fn is_service_account(&self, pool: &PgPool, user_id: Uuid) -> Result<bool, Error>;
+}
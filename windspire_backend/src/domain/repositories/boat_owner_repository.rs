@@ -2,7 +2,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::domain::models::boat::Boat;
-use crate::domain::models::boat_owner::{BoatWithOwners, UserWithBoats};
+use crate::domain::models::boat_owner::{BoatWithOwners, RemoveOwnerOutcome, UserWithBoats};
 use crate::domain::models::user::UserWithCountry;
 
 pub struct BoatOwnerRepository<'a> {
@@ -29,15 +29,69 @@ impl<'a> BoatOwnerRepository<'a> {
         &self,
         boat_id: Uuid,
         user_id: Uuid,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<RemoveOwnerOutcome, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the boat so concurrent removals cannot leave it without owners
+        sqlx::query!("SELECT id FROM boats WHERE id = $1 FOR UPDATE", boat_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let owners = sqlx::query_scalar!(
+            "SELECT user_id FROM boat_owners WHERE boat_id = $1",
+            boat_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !owners.contains(&user_id) {
+            return Ok(RemoveOwnerOutcome::NotAnOwner);
+        }
+        if owners.len() == 1 {
+            return Ok(RemoveOwnerOutcome::LastOwner);
+        }
+
         sqlx::query!(
             "DELETE FROM boat_owners WHERE boat_id = $1 AND user_id = $2",
             boat_id,
             user_id
         )
-        .execute(self.pool)
+        .execute(&mut *tx)
         .await?;
-        Ok(())
+
+        tx.commit().await?;
+        Ok(RemoveOwnerOutcome::Removed)
+    }
+
+    pub async fn boat_exists(&self, boat_id: Uuid) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM boats WHERE id = $1) AS "exists!""#,
+            boat_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+        Ok(exists)
+    }
+
+    pub async fn user_exists(&self, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+            user_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+        Ok(exists)
+    }
+
+    pub async fn is_owner(&self, boat_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        let is_owner = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM boat_owners WHERE boat_id = $1 AND user_id = $2) AS "exists!""#,
+            boat_id,
+            user_id
+        )
+        .fetch_one(self.pool)
+        .await?;
+        Ok(is_owner)
     }
 
     pub async fn get_boats_for_user(&self, user_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
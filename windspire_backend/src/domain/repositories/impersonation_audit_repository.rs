@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::models::impersonation::{
+    ImpersonationAuditCreate, ImpersonationAuditEntry, ImpersonationAuditFilter,
+};
+use crate::infrastructure::error::Error;
+
+#[async_trait]
+pub trait ImpersonationAuditRepository {
+    async fn record(&self, pool: &PgPool, entry: &ImpersonationAuditCreate) -> Result<Uuid, Error>;
+
+    /// Fill in the response status of a recorded request
+    async fn set_status_code(&self, pool: &PgPool, id: Uuid, status_code: i32)
+        -> Result<(), Error>;
+
+    /// Most recent first, at most `limit` entries
+    async fn list(
+        &self,
+        pool: &PgPool,
+        filter: &ImpersonationAuditFilter,
+        limit: i64,
+    ) -> Result<Vec<ImpersonationAuditEntry>, Error>;
+}
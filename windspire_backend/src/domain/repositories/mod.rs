@@ -1,4 +1,11 @@
+pub mod api_key_repository;
 pub mod boat_owner_repository;
+pub mod impersonation_audit_repository;
 pub mod permission_repository;
+pub mod rate_limit_repository;
+pub mod refresh_token_repository;
 pub mod role_repository;
+pub mod token_revocation_repository;
+pub mod user_identity_repository;
 pub mod user_repository;
+pub mod user_session_repository;
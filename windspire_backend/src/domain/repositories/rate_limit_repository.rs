@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::infrastructure::error::Error;
+
+#[async_trait]
+pub trait RateLimitRepository {
+    /// Count a request against `key` in the window starting at `window_start`,
+    /// returning the number of requests in the window so far
+    async fn increment_counter(
+        &self,
+        pool: &PgPool,
+        key: &str,
+        window_start: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<i32, Error>;
+
+    async fn delete_expired_counters(
+        &self,
+        pool: &PgPool,
+        now: DateTime<Utc>,
+    ) -> Result<u64, Error>;
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::models::refresh_token::{RefreshToken, RefreshTokenCreate};
+use crate::infrastructure::error::Error;
+
+#[async_trait]
+pub trait RefreshTokenRepository {
+    async fn create_refresh_token(
+        &self,
+        pool: &PgPool,
+        token: &RefreshTokenCreate,
+    ) -> Result<RefreshToken, Error>;
+    async fn get_refresh_token_by_hash(
+        &self,
+        pool: &PgPool,
+        token_hash: &str,
+    ) -> Result<RefreshToken, Error>;
+
+    /// Marks `token_id` as used and stores its replacement in one transaction.
+    /// Returns `None` when the token was already used or revoked, i.e. when a
+    /// concurrent request won the race for it.
+    async fn rotate_refresh_token(
+        &self,
+        pool: &PgPool,
+        token_id: Uuid,
+        replacement: &RefreshTokenCreate,
+    ) -> Result<Option<RefreshToken>, Error>;
+
+    async fn revoke_refresh_token_family(
+        &self,
+        pool: &PgPool,
+        family_id: Uuid,
+    ) -> Result<u64, Error>;
+    async fn revoke_user_refresh_tokens(&self, pool: &PgPool, user_id: Uuid) -> Result<u64, Error>;
+}
@@ -2,7 +2,10 @@ use async_trait::async_trait;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::domain::models::rbac::{Permission, Role};
+use crate::domain::models::rbac::{
+    AssignRoleOutcome, DeleteUserOutcome, Permission, RemoveRoleOutcome, Role, RoleAssignment,
+    UserRoleCreate,
+};
 use crate::infrastructure::error::Error;
 
 #[async_trait]
@@ -43,4 +46,36 @@ pub trait RoleRepository {
         role_id: Uuid,
         permission_id: Uuid,
     ) -> Result<(), Error>;
+
+    // User-Role management
+    /// All assignments of the user, including expired and upcoming ones
+    async fn get_user_role_assignments(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleAssignment>, Error>;
+    /// Refuses to put an end date on the last permanent admin assignment
+    async fn assign_role_to_user(
+        &self,
+        pool: &PgPool,
+        assignment: &UserRoleCreate,
+    ) -> Result<AssignRoleOutcome, Error>;
+    /// Refuses to take the admin role from the last permanent admin
+    async fn remove_role_from_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<RemoveRoleOutcome, Error>;
+    /// Deletes a user, refusing to delete the last permanent admin. Lives
+    /// here because the check and the delete share the admin lock.
+    async fn delete_user_unless_last_admin(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<DeleteUserOutcome, Error>;
+
+    /// Bump the permission version of every user with an assignment that
+    /// started or ended since the previous sweep, returning their ids
+    async fn sweep_role_assignments(&self, pool: &PgPool) -> Result<Vec<Uuid>, Error>;
 }
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::models::token_revocation::{RevokedToken, UserTokenRevocation};
+use crate::infrastructure::error::Error;
+
+#[async_trait]
+pub trait TokenRevocationRepository {
+    async fn revoke_token(
+        &self,
+        pool: &PgPool,
+        jti: &str,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error>;
+    async fn revoke_user_tokens_before(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        revoked_before: DateTime<Utc>,
+    ) -> Result<(), Error>;
+    async fn get_revoked_tokens(&self, pool: &PgPool) -> Result<Vec<RevokedToken>, Error>;
+    async fn get_user_token_revocations(
+        &self,
+        pool: &PgPool,
+    ) -> Result<Vec<UserTokenRevocation>, Error>;
+
+    /// Remove denylist entries for tokens that expired before `expired_before`
+    async fn delete_expired_revoked_tokens(
+        &self,
+        pool: &PgPool,
+        expired_before: DateTime<Utc>,
+    ) -> Result<u64, Error>;
+    /// Remove cutoffs older than `revoked_before`; no token issued that early is still valid
+    async fn delete_user_token_revocations_before(
+        &self,
+        pool: &PgPool,
+        revoked_before: DateTime<Utc>,
+    ) -> Result<u64, Error>;
+}
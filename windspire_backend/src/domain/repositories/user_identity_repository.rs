@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::models::user_identity::{
+    LinkIdentityOutcome, UnlinkIdentityOutcome, UserIdentity, UserIdentityCreate,
+};
+use crate::infrastructure::error::Error;
+
+#[async_trait]
+pub trait UserIdentityRepository {
+    async fn get_identities_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<UserIdentity>, Error>;
+    async fn link_identity(
+        &self,
+        pool: &PgPool,
+        identity: &UserIdentityCreate,
+    ) -> Result<LinkIdentityOutcome, Error>;
+
+    /// Record a sign-in, refreshing the email the provider reported
+    async fn touch_identity(
+        &self,
+        pool: &PgPool,
+        provider_name: &str,
+        provider_subject: &str,
+        email: Option<&str>,
+        email_verified: bool,
+    ) -> Result<(), Error>;
+
+    /// Refuses to unlink the user's last identity
+    async fn unlink_identity(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        identity_id: Uuid,
+    ) -> Result<UnlinkIdentityOutcome, Error>;
+}
@@ -5,10 +5,10 @@ use uuid::Uuid;
 
 use crate::domain::models::rbac::UserWithRoles;
 use crate::domain::models::user::{
-    OAuthUserCreate, User, UserByEmail, UserCreate, UserUpdate, UserWithCountry,
+    AccountState, OAuthUserCreate, User, UserByEmail, UserCreate, UserUpdate, UserWithCountry,
 };
 
-pub(crate) trait UserRepository {
+pub trait UserRepository {
     fn get_user_by_id(
         &self,
         pool: &PgPool,
@@ -34,12 +34,15 @@ pub(crate) trait UserRepository {
     ) -> impl Future<Output = Result<User, Error>>;
 
     // OAuth-related methods
+    /// Only active accounts: an account signed up with an unverified email must
+    /// never capture a later sign-in with that email
     fn get_user_by_email(
         &self,
         pool: &PgPool,
         email: &str,
     ) -> impl Future<Output = Result<UserByEmail, Error>>;
 
+    /// Looks up the user the identity (provider subject) is linked to
     fn get_user_by_provider_id(
         &self,
         pool: &PgPool,
@@ -47,6 +50,8 @@ pub(crate) trait UserRepository {
         provider_name: &str,
     ) -> impl Future<Output = Result<UserByEmail, Error>>;
 
+    /// Creates the user together with its first linked identity, with the
+    /// default role for its account status
     fn create_oauth_user(
         &self,
         pool: &PgPool,
@@ -61,10 +66,31 @@ pub(crate) trait UserRepository {
         provider_name: &str,
     ) -> impl Future<Output = Result<(), Error>>;
 
+    fn get_account_state(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> impl Future<Output = Result<AccountState, Error>>;
+
+    /// Lift a restricted or pending account to active once the user has signed
+    /// in with a verified email. `false` if it already was active.
+    fn activate_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> impl Future<Output = Result<bool, Error>>;
+
     // RBAC-related methods
+    /// Only role assignments that are valid right now count
     fn get_user_with_roles(
         &self,
         pool: &PgPool,
         user_id: Uuid,
     ) -> impl Future<Output = Result<UserWithRoles, Error>>;
+
+    fn get_permission_version(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> impl Future<Output = Result<i64, Error>>;
 }
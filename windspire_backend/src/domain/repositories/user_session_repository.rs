@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::models::user_session::{UserSession, UserSessionCreate};
+use crate::infrastructure::error::Error;
+
+#[async_trait]
+pub trait UserSessionRepository {
+    async fn create_session(
+        &self,
+        pool: &PgPool,
+        session: &UserSessionCreate,
+    ) -> Result<UserSession, Error>;
+    /// Sessions that are neither revoked nor expired at `now`, most recently seen first
+    async fn get_active_sessions_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<UserSession>, Error>;
+    async fn touch_session(
+        &self,
+        pool: &PgPool,
+        session_id: Uuid,
+        last_seen_at: DateTime<Utc>,
+    ) -> Result<(), Error>;
+    /// Record a token refresh, which also moves the session's expiry
+    async fn extend_session(
+        &self,
+        pool: &PgPool,
+        session_id: Uuid,
+        last_seen_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error>;
+
+    /// Revoke one of the user's sessions; `false` if there was no such session
+    /// that was still active
+    async fn revoke_session(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<bool, Error>;
+    async fn revoke_user_sessions(&self, pool: &PgPool, user_id: Uuid) -> Result<u64, Error>;
+    /// Ids of sessions revoked after `revoked_after`; older ones have no valid
+    /// access tokens left
+    async fn get_revoked_session_ids(
+        &self,
+        pool: &PgPool,
+        revoked_after: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, Error>;
+}
@@ -1,5 +1,12 @@
+pub mod sqlx_api_key_repository;
 pub mod sqlx_boat_repository;
 pub mod sqlx_country_repository;
+pub mod sqlx_impersonation_audit_repository;
 pub mod sqlx_permission_repository;
+pub mod sqlx_rate_limit_repository;
+pub mod sqlx_refresh_token_repository;
 pub mod sqlx_role_repository;
+pub mod sqlx_token_revocation_repository;
+pub mod sqlx_user_identity_repository;
 pub mod sqlx_user_repository;
+pub mod sqlx_user_session_repository;
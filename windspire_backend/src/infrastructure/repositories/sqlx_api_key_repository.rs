@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use sqlx::{Error, PgPool};
+use uuid::{NoContext, Timestamp, Uuid};
+
+use crate::domain::{
+    models::api_key::{ApiKey, ApiKeyCreate, ServiceAccount},
+    repositories::api_key_repository::ApiKeyRepository,
+};
+
+pub struct SqlxApiKeyRepository;
+
+#[async_trait]
+impl ApiKeyRepository for SqlxApiKeyRepository {
+    async fn create_api_key(&self, pool: &PgPool, key: &ApiKeyCreate) -> Result<ApiKey, Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO api_keys (id, user_id, name, prefix, key_hash, created_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            key.id,
+            key.user_id,
+            key.name,
+            key.prefix,
+            key.key_hash,
+            key.created_by,
+            key.expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO api_key_permissions (api_key_id, permission_id)
+            SELECT $1, UNNEST($2::uuid[])
+            "#,
+            key.id,
+            &key.permission_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_api_key_by_hash(pool, &key.key_hash).await
+    }
+
+    async fn get_api_key_by_hash(&self, pool: &PgPool, key_hash: &str) -> Result<ApiKey, Error> {
+        let key = sqlx::query_as!(
+            ApiKey,
+            r#"
+            SELECT k.id, k.user_id, k.name, k.prefix, k.key_hash, k.created_by, k.created_at,
+                   k.expires_at, k.last_used_at, k.revoked_at,
+                   COALESCE(ARRAY_AGG(p.name ORDER BY p.name)
+                            FILTER (WHERE p.name IS NOT NULL), '{}') AS "permissions!"
+            FROM api_keys k
+            LEFT JOIN api_key_permissions kp ON kp.api_key_id = k.id
+            LEFT JOIN permissions p ON p.id = kp.permission_id
+            WHERE k.key_hash = $1
+            GROUP BY k.id
+            "#,
+            key_hash
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    async fn get_api_keys_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<ApiKey>, Error> {
+        let keys = sqlx::query_as!(
+            ApiKey,
+            r#"
+            SELECT k.id, k.user_id, k.name, k.prefix, k.key_hash, k.created_by, k.created_at,
+                   k.expires_at, k.last_used_at, k.revoked_at,
+                   COALESCE(ARRAY_AGG(p.name ORDER BY p.name)
+                            FILTER (WHERE p.name IS NOT NULL), '{}') AS "permissions!"
+            FROM api_keys k
+            LEFT JOIN api_key_permissions kp ON kp.api_key_id = k.id
+            LEFT JOIN permissions p ON p.id = kp.permission_id
+            WHERE k.user_id = $1
+            GROUP BY k.id
+            ORDER BY k.created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    async fn revoke_api_key(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        key_id: Uuid,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE api_keys
+            SET revoked_at = COALESCE(revoked_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            "#,
+            key_id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn touch_api_key(&self, pool: &PgPool, key_id: Uuid) -> Result<(), Error> {
+        // Only write once a minute so busy scripts don't update the row on every request
+        sqlx::query!(
+            r#"
+            UPDATE api_keys
+            SET last_used_at = NOW()
+            WHERE id = $1
+              AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+            "#,
+            key_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_service_account(
+        &self,
+        pool: &PgPool,
+        name: &str,
+        country_id: Uuid,
+    ) -> Result<ServiceAccount, Error> {
+        let id = Uuid::new_v7(Timestamp::now(NoContext));
+        // A reserved domain, so the account can never be matched by an identity provider login
+        let email = format!("service-account-{}@windspire.invalid", id);
+
+        let account = sqlx::query_as!(
+            ServiceAccount,
+            r#"
+            INSERT INTO users (id, first_name, last_name, email, country_id, is_service_account,
+                               created_at, updated_at)
+            VALUES ($1, $2, '', $3, $4, TRUE, NOW(), NOW())
+            RETURNING id, first_name AS name, email, created_at
+            "#,
+            id,
+            name,
+            email,
+            country_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    async fn get_service_account(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<ServiceAccount, Error> {
+        let account = sqlx::query_as!(
+            ServiceAccount,
+            r#"
+            SELECT id, first_name AS name, email, created_at
+            FROM users
+            WHERE id = $1 AND is_service_account
+            "#,
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    async fn get_service_accounts(&self, pool: &PgPool) -> Result<Vec<ServiceAccount>, Error> {
+        let accounts = sqlx::query_as!(
+            ServiceAccount,
+            r#"
+            SELECT id, first_name AS name, email, created_at
+            FROM users
+            WHERE is_service_account
+            ORDER BY first_name
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(accounts)
+    }
+
+    async fn is_service_account(&self, pool: &PgPool, user_id: Uuid) -> Result<bool, Error> {
+        let is_service_account = sqlx::query_scalar!(
+            "SELECT is_service_account FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(is_service_account)
+    }
+}
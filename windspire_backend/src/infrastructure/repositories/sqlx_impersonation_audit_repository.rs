@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use sqlx::{Error, PgPool};
+use uuid::Uuid;
+
+use crate::domain::{
+    models::impersonation::{
+        ImpersonationAuditCreate, ImpersonationAuditEntry, ImpersonationAuditFilter,
+    },
+    repositories::impersonation_audit_repository::ImpersonationAuditRepository,
+};
+
+pub struct SqlxImpersonationAuditRepository;
+
+#[async_trait]
+impl ImpersonationAuditRepository for SqlxImpersonationAuditRepository {
+    async fn record(&self, pool: &PgPool, entry: &ImpersonationAuditCreate) -> Result<Uuid, Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO impersonation_audit_log (
+                event, actor_id, actor_email, target_user_id, token_id, method, path,
+                status_code, reason
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+            entry.event.as_str(),
+            entry.actor_id,
+            entry.actor_email,
+            entry.target_user_id,
+            entry.token_id,
+            entry.method,
+            entry.path,
+            entry.status_code,
+            entry.reason
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn set_status_code(
+        &self,
+        pool: &PgPool,
+        id: Uuid,
+        status_code: i32,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE impersonation_audit_log SET status_code = $2 WHERE id = $1",
+            id,
+            status_code
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        pool: &PgPool,
+        filter: &ImpersonationAuditFilter,
+        limit: i64,
+    ) -> Result<Vec<ImpersonationAuditEntry>, Error> {
+        sqlx::query_as!(
+            ImpersonationAuditEntry,
+            r#"
+            SELECT id, event, actor_id, actor_email, target_user_id, token_id, method, path,
+                   status_code, reason, created_at
+            FROM impersonation_audit_log
+            WHERE ($1::uuid IS NULL OR actor_id = $1)
+              AND ($2::uuid IS NULL OR target_user_id = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+            filter.actor_id,
+            filter.target_user_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool};
+
+use crate::domain::repositories::rate_limit_repository::RateLimitRepository;
+
+pub struct SqlxRateLimitRepository;
+
+#[async_trait]
+impl RateLimitRepository for SqlxRateLimitRepository {
+    async fn increment_counter(
+        &self,
+        pool: &PgPool,
+        key: &str,
+        window_start: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<i32, Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO rate_limit_counters (key, window_start, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (key, window_start)
+            DO UPDATE SET count = rate_limit_counters.count + 1
+            RETURNING count
+            "#,
+            key,
+            window_start,
+            expires_at
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn delete_expired_counters(
+        &self,
+        pool: &PgPool,
+        now: DateTime<Utc>,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM rate_limit_counters WHERE expires_at <= $1",
+            now
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use sqlx::{Error, PgPool};
+use uuid::Uuid;
+
+use crate::domain::{
+    models::refresh_token::{RefreshToken, RefreshTokenCreate},
+    repositories::refresh_token_repository::RefreshTokenRepository,
+};
+
+pub struct SqlxRefreshTokenRepository;
+
+#[async_trait]
+impl RefreshTokenRepository for SqlxRefreshTokenRepository {
+    async fn create_refresh_token(
+        &self,
+        pool: &PgPool,
+        token: &RefreshTokenCreate,
+    ) -> Result<RefreshToken, Error> {
+        let created = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, family_id, token_hash, issued_at, expires_at,
+                      used_at, revoked_at, replaced_by
+            "#,
+            token.id,
+            token.user_id,
+            token.family_id,
+            token.token_hash,
+            token.expires_at
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    async fn get_refresh_token_by_hash(
+        &self,
+        pool: &PgPool,
+        token_hash: &str,
+    ) -> Result<RefreshToken, Error> {
+        let token = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            SELECT id, user_id, family_id, token_hash, issued_at, expires_at,
+                   used_at, revoked_at, replaced_by
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            "#,
+            token_hash
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        pool: &PgPool,
+        token_id: Uuid,
+        replacement: &RefreshTokenCreate,
+    ) -> Result<Option<RefreshToken>, Error> {
+        let mut tx = pool.begin().await?;
+
+        // Claim the presented token; only one caller can ever win this update
+        let claimed = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET used_at = NOW()
+            WHERE id = $1 AND used_at IS NULL AND revoked_at IS NULL
+            "#,
+            token_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if claimed.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let created = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, family_id, token_hash, issued_at, expires_at,
+                      used_at, revoked_at, replaced_by
+            "#,
+            replacement.id,
+            replacement.user_id,
+            replacement.family_id,
+            replacement.token_hash,
+            replacement.expires_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET replaced_by = $1 WHERE id = $2",
+            created.id,
+            token_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(created))
+    }
+
+    async fn revoke_refresh_token_family(
+        &self,
+        pool: &PgPool,
+        family_id: Uuid,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE family_id = $1 AND revoked_at IS NULL
+            "#,
+            family_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn revoke_user_refresh_tokens(&self, pool: &PgPool, user_id: Uuid) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1,10 +1,13 @@
 use async_trait::async_trait;
 use chrono::Utc;
-use sqlx::{Error, PgPool};
+use sqlx::{Error, PgPool, Postgres, Transaction};
 use uuid::{NoContext, Timestamp, Uuid};
 
 use crate::domain::{
-    models::rbac::{Permission, Role},
+    models::rbac::{
+        AssignRoleOutcome, DeleteUserOutcome, Permission, RemoveRoleOutcome, Role, RoleAssignment,
+        UserRoleCreate, ROLE_ADMIN,
+    },
     repositories::role_repository::RoleRepository,
 };
 
@@ -180,4 +183,207 @@ impl RoleRepository for SqlxRoleRepository {
 
         Ok(())
     }
+
+    // User-Role management
+    async fn get_user_role_assignments(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<RoleAssignment>, Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT r.id, r.name, r.description, r.created_at,
+                   ur.assigned_at, ur.valid_from, ur.valid_until, ur.assigned_by
+            FROM roles r
+            INNER JOIN user_roles ur ON r.id = ur.role_id
+            WHERE ur.user_id = $1
+            ORDER BY r.name
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RoleAssignment {
+                role: Role {
+                    id: row.id,
+                    name: row.name,
+                    description: row.description,
+                    created_at: row.created_at,
+                },
+                assigned_at: row.assigned_at,
+                valid_from: row.valid_from,
+                valid_until: row.valid_until,
+                assigned_by: row.assigned_by,
+            })
+            .collect())
+    }
+
+    async fn assign_role_to_user(
+        &self,
+        pool: &PgPool,
+        assignment: &UserRoleCreate,
+    ) -> Result<AssignRoleOutcome, Error> {
+        let mut tx = pool.begin().await?;
+
+        if !assignment.is_permanent_at(Utc::now()) {
+            let admins = lock_permanent_admins(&mut tx).await?;
+            if admins == [assignment.user_id] && is_admin_role(&mut tx, assignment.role_id).await? {
+                return Ok(AssignRoleOutcome::LastAdmin);
+            }
+        }
+
+        // xmax is 0 only for freshly inserted rows
+        let inserted = sqlx::query_scalar!(
+            r#"
+            INSERT INTO user_roles (user_id, role_id, assigned_at, valid_from, valid_until, assigned_by)
+            VALUES ($1, $2, NOW(), COALESCE($3, NOW()), $4, $5)
+            ON CONFLICT (user_id, role_id) DO UPDATE
+            SET assigned_at = EXCLUDED.assigned_at,
+                valid_from = EXCLUDED.valid_from,
+                valid_until = EXCLUDED.valid_until,
+                assigned_by = EXCLUDED.assigned_by
+            RETURNING (xmax = 0) AS "inserted!"
+            "#,
+            assignment.user_id,
+            assignment.role_id,
+            assignment.valid_from,
+            assignment.valid_until,
+            assignment.assigned_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(if inserted {
+            AssignRoleOutcome::Assigned
+        } else {
+            AssignRoleOutcome::Updated
+        })
+    }
+
+    async fn remove_role_from_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<RemoveRoleOutcome, Error> {
+        let mut tx = pool.begin().await?;
+
+        let admins = lock_permanent_admins(&mut tx).await?;
+        if admins == [user_id] && is_admin_role(&mut tx, role_id).await? {
+            return Ok(RemoveRoleOutcome::LastAdmin);
+        }
+
+        let result = sqlx::query!(
+            "DELETE FROM user_roles WHERE user_id = $1 AND role_id = $2",
+            user_id,
+            role_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(if result.rows_affected() == 0 {
+            RemoveRoleOutcome::NotAssigned
+        } else {
+            RemoveRoleOutcome::Removed
+        })
+    }
+
+    async fn delete_user_unless_last_admin(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<DeleteUserOutcome, Error> {
+        let mut tx = pool.begin().await?;
+
+        let admins = lock_permanent_admins(&mut tx).await?;
+        if admins == [user_id] {
+            return Ok(DeleteUserOutcome::LastAdmin);
+        }
+
+        let result = sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(if result.rows_affected() == 0 {
+            DeleteUserOutcome::NotFound
+        } else {
+            DeleteUserOutcome::Deleted
+        })
+    }
+
+    async fn sweep_role_assignments(&self, pool: &PgPool) -> Result<Vec<Uuid>, Error> {
+        let mut tx = pool.begin().await?;
+
+        // Locking the watermark makes concurrent sweeps on other instances wait
+        let swept_until =
+            sqlx::query_scalar!("SELECT swept_until FROM role_assignment_sweeps FOR UPDATE")
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let user_ids = sqlx::query_scalar!(
+            r#"
+            UPDATE users
+            SET permission_version = permission_version + 1
+            WHERE id IN (
+                SELECT user_id FROM user_roles
+                WHERE (valid_from > $1 AND valid_from <= NOW())
+                   OR (valid_until > $1 AND valid_until <= NOW())
+            )
+            RETURNING id
+            "#,
+            swept_until
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!("UPDATE role_assignment_sweeps SET swept_until = NOW()")
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user_ids)
+    }
+}
+
+/// Users holding the admin role without an end date, locked so two admins
+/// cannot remove each other concurrently
+async fn lock_permanent_admins(tx: &mut Transaction<'_, Postgres>) -> Result<Vec<Uuid>, Error> {
+    let admins = sqlx::query_scalar!(
+        r#"
+        SELECT ur.user_id
+        FROM user_roles ur
+        INNER JOIN roles r ON r.id = ur.role_id
+        WHERE r.name = $1
+          AND ur.valid_from <= NOW()
+          AND ur.valid_until IS NULL
+        FOR UPDATE OF ur
+        "#,
+        ROLE_ADMIN
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(admins)
+}
+
+async fn is_admin_role(tx: &mut Transaction<'_, Postgres>, role_id: Uuid) -> Result<bool, Error> {
+    let is_admin = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM roles WHERE id = $1 AND name = $2) AS "is_admin!""#,
+        role_id,
+        ROLE_ADMIN
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(is_admin)
 }
@@ -0,0 +1,121 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool};
+use uuid::Uuid;
+
+use crate::domain::{
+    models::token_revocation::{RevokedToken, UserTokenRevocation},
+    repositories::token_revocation_repository::TokenRevocationRepository,
+};
+
+pub struct SqlxTokenRevocationRepository;
+
+#[async_trait]
+impl TokenRevocationRepository for SqlxTokenRevocationRepository {
+    async fn revoke_token(
+        &self,
+        pool: &PgPool,
+        jti: &str,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+            jti,
+            user_id,
+            expires_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_user_tokens_before(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        revoked_before: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_token_revocations (user_id, revoked_before)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET revoked_before = GREATEST(user_token_revocations.revoked_before, EXCLUDED.revoked_before)
+            "#,
+            user_id,
+            revoked_before
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_revoked_tokens(&self, pool: &PgPool) -> Result<Vec<RevokedToken>, Error> {
+        let tokens = sqlx::query_as!(
+            RevokedToken,
+            r#"
+            SELECT jti, user_id, expires_at, revoked_at
+            FROM revoked_tokens
+            WHERE expires_at > NOW()
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    async fn get_user_token_revocations(
+        &self,
+        pool: &PgPool,
+    ) -> Result<Vec<UserTokenRevocation>, Error> {
+        let revocations = sqlx::query_as!(
+            UserTokenRevocation,
+            r#"
+            SELECT user_id, revoked_before
+            FROM user_token_revocations
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(revocations)
+    }
+
+    async fn delete_expired_revoked_tokens(
+        &self,
+        pool: &PgPool,
+        expired_before: DateTime<Utc>,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM revoked_tokens WHERE expires_at < $1",
+            expired_before
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_user_token_revocations_before(
+        &self,
+        pool: &PgPool,
+        revoked_before: DateTime<Utc>,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM user_token_revocations WHERE revoked_before < $1",
+            revoked_before
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use sqlx::{Error, PgPool};
+use uuid::Uuid;
+
+use crate::domain::{
+    models::user_identity::{
+        LinkIdentityOutcome, UnlinkIdentityOutcome, UserIdentity, UserIdentityCreate,
+    },
+    repositories::user_identity_repository::UserIdentityRepository,
+};
+
+pub struct SqlxUserIdentityRepository;
+
+#[async_trait]
+impl UserIdentityRepository for SqlxUserIdentityRepository {
+    async fn get_identities_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<UserIdentity>, Error> {
+        let identities = sqlx::query_as!(
+            UserIdentity,
+            r#"
+            SELECT id, user_id, provider_name, provider_subject, email, email_verified,
+                   created_at, last_used_at
+            FROM user_identities
+            WHERE user_id = $1
+            ORDER BY created_at
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(identities)
+    }
+
+    async fn link_identity(
+        &self,
+        pool: &PgPool,
+        identity: &UserIdentityCreate,
+    ) -> Result<LinkIdentityOutcome, Error> {
+        let linked = sqlx::query_as!(
+            UserIdentity,
+            r#"
+            INSERT INTO user_identities (user_id, provider_name, provider_subject, email, email_verified, last_used_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            ON CONFLICT (provider_name, provider_subject) DO NOTHING
+            RETURNING id, user_id, provider_name, provider_subject, email, email_verified,
+                      created_at, last_used_at
+            "#,
+            identity.user_id,
+            identity.provider_name,
+            identity.provider_subject,
+            identity.email,
+            identity.email_verified
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(linked) = linked {
+            return Ok(LinkIdentityOutcome::Linked(linked));
+        }
+
+        let existing = sqlx::query_as!(
+            UserIdentity,
+            r#"
+            SELECT id, user_id, provider_name, provider_subject, email, email_verified,
+                   created_at, last_used_at
+            FROM user_identities
+            WHERE provider_name = $1 AND provider_subject = $2
+            "#,
+            identity.provider_name,
+            identity.provider_subject
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if existing.user_id == identity.user_id {
+            Ok(LinkIdentityOutcome::AlreadyLinked(existing))
+        } else {
+            Ok(LinkIdentityOutcome::LinkedToOtherUser)
+        }
+    }
+
+    async fn touch_identity(
+        &self,
+        pool: &PgPool,
+        provider_name: &str,
+        provider_subject: &str,
+        email: Option<&str>,
+        email_verified: bool,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE user_identities
+            SET email = $3, email_verified = $4, last_used_at = NOW()
+            WHERE provider_name = $1 AND provider_subject = $2
+            "#,
+            provider_name,
+            provider_subject,
+            email,
+            email_verified
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn unlink_identity(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        identity_id: Uuid,
+    ) -> Result<UnlinkIdentityOutcome, Error> {
+        let mut tx = pool.begin().await?;
+
+        // Lock the user's identities so two concurrent unlinks cannot remove the last two
+        let identity_ids = sqlx::query_scalar!(
+            "SELECT id FROM user_identities WHERE user_id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !identity_ids.contains(&identity_id) {
+            return Ok(UnlinkIdentityOutcome::NotFound);
+        }
+        if identity_ids.len() == 1 {
+            return Ok(UnlinkIdentityOutcome::LastIdentity);
+        }
+
+        let unlinked = sqlx::query!(
+            r#"
+            DELETE FROM user_identities
+            WHERE id = $1 AND user_id = $2
+            RETURNING provider_name, provider_subject
+            "#,
+            identity_id,
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Don't leave the unlinked identity recorded as the most recent sign-in
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET provider_id = NULL, provider_name = NULL, updated_at = NOW()
+            WHERE id = $1 AND provider_name = $2 AND provider_id = $3
+            "#,
+            user_id,
+            unlinked.provider_name,
+            unlinked.provider_subject
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(UnlinkIdentityOutcome::Unlinked)
+    }
+}
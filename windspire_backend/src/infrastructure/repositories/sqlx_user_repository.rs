@@ -4,8 +4,11 @@ use sqlx::{Error, PgPool};
 use uuid::{NoContext, Timestamp, Uuid};
 
 use crate::domain::{
-    models::rbac::{Permission, Role, UserWithRoles},
-    models::user::{OAuthUserCreate, User, UserByEmail, UserCreate, UserUpdate, UserWithCountry},
+    models::rbac::{Permission, Role, UserWithRoles, ROLE_RESTRICTED, ROLE_USER},
+    models::user::{
+        AccountState, AccountStatus, OAuthUserCreate, User, UserByEmail, UserCreate, UserUpdate,
+        UserWithCountry,
+    },
     repositories::user_repository::UserRepository,
 };
 
@@ -168,7 +171,7 @@ impl UserRepository for SqlxUserRepository {
             r#"
             SELECT id, email, first_name, last_name, provider_id, provider_name, country_id
             FROM users
-            WHERE email = $1
+            WHERE email = $1 AND account_status = 'active'
             "#,
             email
         )
@@ -187,9 +190,11 @@ impl UserRepository for SqlxUserRepository {
         let user = sqlx::query_as!(
             UserByEmail,
             r#"
-            SELECT id, email, first_name, last_name, provider_id, provider_name, country_id
-            FROM users
-            WHERE provider_id = $1 AND provider_name = $2
+            SELECT u.id, u.email, u.first_name, u.last_name, u.provider_id, u.provider_name,
+                   u.country_id
+            FROM users u
+            JOIN user_identities ui ON ui.user_id = u.id
+            WHERE ui.provider_subject = $1 AND ui.provider_name = $2
             "#,
             provider_id,
             provider_name
@@ -208,15 +213,16 @@ impl UserRepository for SqlxUserRepository {
         let ts = Timestamp::now(NoContext);
         let id = Uuid::new_v7(ts);
         let now = Utc::now();
+        let mut tx = pool.begin().await?;
 
         let created_user = sqlx::query_as!(
             User,
             r#"
             INSERT INTO users (
                 id, first_name, last_name, email, phone, country_id,
-                provider_id, provider_name, avatar_url, created_at, updated_at
+                provider_id, provider_name, avatar_url, created_at, updated_at, account_status
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING id, first_name, last_name, email, phone, country_id,
                       provider_id, provider_name, avatar_url, created_at, updated_at
             "#,
@@ -230,24 +236,49 @@ impl UserRepository for SqlxUserRepository {
             Some(user.provider_name.clone()),
             user.avatar_url,
             Some(now),
-            Some(now)
+            Some(now),
+            user.account_status.as_str()
         )
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
 
-        // Assign default user role
-        let default_role_id = sqlx::query!("SELECT id FROM roles WHERE name = 'user' LIMIT 1")
-            .fetch_one(pool)
-            .await?;
-
         sqlx::query!(
-            "INSERT INTO user_roles (user_id, role_id) VALUES ($1, $2)",
+            r#"
+            INSERT INTO user_identities (user_id, provider_name, provider_subject, email, email_verified, last_used_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
             created_user.id,
-            default_role_id.id
+            user.provider_name,
+            user.provider_id,
+            user.provider_email,
+            user.email_verified,
+            now
         )
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
+        // Assign the default role; pending accounts get none
+        let default_role = match user.account_status {
+            AccountStatus::Active => Some(ROLE_USER),
+            AccountStatus::Restricted => Some(ROLE_RESTRICTED),
+            AccountStatus::Pending => None,
+        };
+        if let Some(default_role) = default_role {
+            let default_role_id =
+                sqlx::query!("SELECT id FROM roles WHERE name = $1 LIMIT 1", default_role)
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+            sqlx::query!(
+                "INSERT INTO user_roles (user_id, role_id) VALUES ($1, $2)",
+                created_user.id,
+                default_role_id.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
         Ok(created_user)
     }
 
@@ -277,6 +308,79 @@ impl UserRepository for SqlxUserRepository {
         Ok(())
     }
 
+    async fn get_account_state(&self, pool: &PgPool, user_id: Uuid) -> Result<AccountState, Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT u.account_status,
+                   EXISTS (
+                       SELECT 1 FROM user_identities ui
+                       WHERE ui.user_id = u.id AND ui.email_verified
+                         AND LOWER(ui.email) = LOWER(u.email)
+                   ) AS "email_verified!"
+            FROM users u
+            WHERE u.id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(AccountState {
+            status: row
+                .account_status
+                .parse()
+                .map_err(|e: String| Error::Decode(e.into()))?,
+            email_verified: row.email_verified,
+        })
+    }
+
+    async fn activate_user(&self, pool: &PgPool, user_id: Uuid) -> Result<bool, Error> {
+        let mut tx = pool.begin().await?;
+
+        let activated = sqlx::query!(
+            r#"
+            UPDATE users
+            SET account_status = 'active', updated_at = NOW()
+            WHERE id = $1 AND account_status <> 'active'
+            "#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+        if !activated {
+            return Ok(false);
+        }
+
+        // Swap the read-only role for the default one
+        sqlx::query!(
+            r#"
+            DELETE FROM user_roles
+            WHERE user_id = $1 AND role_id = (SELECT id FROM roles WHERE name = $2)
+            "#,
+            user_id,
+            ROLE_RESTRICTED
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO user_roles (user_id, role_id)
+            SELECT $1, id FROM roles WHERE name = $2
+            ON CONFLICT (user_id, role_id) DO NOTHING
+            "#,
+            user_id,
+            ROLE_USER
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
     // RBAC-related methods
     async fn get_user_with_roles(
         &self,
@@ -286,7 +390,7 @@ impl UserRepository for SqlxUserRepository {
         // Get user basic info
         let user = sqlx::query!(
             r#"
-            SELECT id, email, first_name, last_name
+            SELECT id, email, first_name, last_name, permission_version
             FROM users
             WHERE id = $1
             "#,
@@ -295,7 +399,7 @@ impl UserRepository for SqlxUserRepository {
         .fetch_one(pool)
         .await?;
 
-        // Get user roles
+        // Get user roles, skipping assignments outside their validity window
         let roles = sqlx::query_as!(
             Role,
             r#"
@@ -303,6 +407,8 @@ impl UserRepository for SqlxUserRepository {
             FROM roles r
             INNER JOIN user_roles ur ON r.id = ur.role_id
             WHERE ur.user_id = $1
+              AND ur.valid_from <= NOW()
+              AND (ur.valid_until IS NULL OR ur.valid_until > NOW())
             "#,
             user_id
         )
@@ -318,6 +424,8 @@ impl UserRepository for SqlxUserRepository {
             INNER JOIN role_permissions rp ON p.id = rp.permission_id
             INNER JOIN user_roles ur ON rp.role_id = ur.role_id
             WHERE ur.user_id = $1
+              AND ur.valid_from <= NOW()
+              AND (ur.valid_until IS NULL OR ur.valid_until > NOW())
             "#,
             user_id
         )
@@ -329,8 +437,20 @@ impl UserRepository for SqlxUserRepository {
             email: user.email,
             first_name: user.first_name,
             last_name: user.last_name,
+            permission_version: user.permission_version,
             roles,
             permissions,
         })
     }
+
+    async fn get_permission_version(&self, pool: &PgPool, user_id: Uuid) -> Result<i64, Error> {
+        let version = sqlx::query_scalar!(
+            "SELECT permission_version FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(version)
+    }
 }
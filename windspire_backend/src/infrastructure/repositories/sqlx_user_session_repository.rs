@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool};
+use uuid::Uuid;
+
+use crate::domain::{
+    models::user_session::{UserSession, UserSessionCreate},
+    repositories::user_session_repository::UserSessionRepository,
+};
+
+pub struct SqlxUserSessionRepository;
+
+#[async_trait]
+impl UserSessionRepository for SqlxUserSessionRepository {
+    async fn create_session(
+        &self,
+        pool: &PgPool,
+        session: &UserSessionCreate,
+    ) -> Result<UserSession, Error> {
+        let created = sqlx::query_as!(
+            UserSession,
+            r#"
+            INSERT INTO user_sessions (id, user_id, user_agent, ip_address, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, user_agent, ip_address, created_at, last_seen_at,
+                      expires_at, revoked_at
+            "#,
+            session.id,
+            session.user_id,
+            session.user_agent,
+            session.ip_address,
+            session.expires_at
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    async fn get_active_sessions_for_user(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<UserSession>, Error> {
+        let sessions = sqlx::query_as!(
+            UserSession,
+            r#"
+            SELECT id, user_id, user_agent, ip_address, created_at, last_seen_at,
+                   expires_at, revoked_at
+            FROM user_sessions
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > $2
+            ORDER BY last_seen_at DESC
+            "#,
+            user_id,
+            now
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn touch_session(
+        &self,
+        pool: &PgPool,
+        session_id: Uuid,
+        last_seen_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE user_sessions
+            SET last_seen_at = GREATEST(last_seen_at, $2)
+            WHERE id = $1
+            "#,
+            session_id,
+            last_seen_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn extend_session(
+        &self,
+        pool: &PgPool,
+        session_id: Uuid,
+        last_seen_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE user_sessions
+            SET last_seen_at = GREATEST(last_seen_at, $2), expires_at = $3
+            WHERE id = $1 AND revoked_at IS NULL
+            "#,
+            session_id,
+            last_seen_at,
+            expires_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_session(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_sessions
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+            session_id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_user_sessions(&self, pool: &PgPool, user_id: Uuid) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_sessions
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_revoked_session_ids(
+        &self,
+        pool: &PgPool,
+        revoked_after: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM user_sessions
+            WHERE revoked_at > $1
+            "#,
+            revoked_after
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ids)
+    }
+}
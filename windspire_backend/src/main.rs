@@ -1,11 +1,25 @@
 #![allow(dead_code)]
 
 use application::approuter;
-use application::config::AppConfig;
-use application::services::{firebase_service::FirebaseService, jwt_service::JwtService};
+use application::config::{AppConfig, AuthMode};
+use application::middleware::rbac_middleware;
+use application::services::{
+    api_key_service::ApiKeyService,
+    firebase_service::{FirebaseService, FIREBASE_PROVIDER_NAME},
+    identity_provider::IdentityProviders,
+    jwt_service::JwtService,
+    oidc_provider::OidcProvider,
+    permission_version_service::{PermissionVersionService, VERSION_CACHE_SECONDS},
+    rate_limit_service::RateLimitService,
+    refresh_token_service::RefreshTokenService,
+    session_service::{SessionService, TOUCH_INTERVAL_SECONDS},
+    test_issuer::TestIssuer,
+    token_revocation_service::TokenRevocationService,
+};
 use application::state::AppState;
 use dotenvy::dotenv;
 use sqlx::postgres::PgPoolOptions;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpListener;
@@ -47,14 +61,106 @@ async fn main() {
         .expect("Failed to run migrations...");
     println!("Database migrations completed successfully!");
 
+    // Every permission a route can require must exist, or nobody but admins could pass it
+    let missing_permissions = rbac_middleware::missing_permissions(&db_pool)
+        .await
+        .expect("Failed to load permissions");
+    assert!(
+        missing_permissions.is_empty(),
+        "Permissions required by routes are missing from the database: {}",
+        missing_permissions
+            .iter()
+            .map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
     // Create JWT service
-    let jwt_service = Arc::new(JwtService::new(config.jwt.clone()));
+    let jwt_service =
+        Arc::new(JwtService::new(config.jwt.clone()).expect("Failed to load JWT signing keys"));
+
+    // Create Firebase service, verifying ID tokens the way AUTH_MODE asks for
+    let firebase_service = FirebaseService::new(config.firebase.project_id.clone())
+        .with_certificates_url(config.firebase.certificates_url.clone());
+    let mut test_issuer = None;
+    let firebase_service = match config.auth_mode {
+        AuthMode::Production => firebase_service,
+        AuthMode::FirebaseEmulator => firebase_service.with_emulator(),
+        AuthMode::TestIssuer => {
+            let issuer = Arc::new(TestIssuer::new(config.firebase.project_id.clone()));
+            test_issuer = Some(issuer.clone());
+            firebase_service.with_test_issuer(issuer)
+        }
+    };
+    let firebase_service = Arc::new(firebase_service);
+    if config.auth_mode == AuthMode::Production {
+        firebase_service.spawn_certificate_refresh();
+    } else {
+        tracing::warn!(
+            "AUTH_MODE={}: accepting ID tokens that are not signed by Firebase. Never use this in production!",
+            config.auth_mode.as_str()
+        );
+    }
 
-    // Create Firebase service
-    let firebase_service = Arc::new(FirebaseService::new(config.firebase.project_id.clone()));
+    // Register identity providers: Firebase plus any configured OIDC providers
+    let mut identity_providers = IdentityProviders::new().register(firebase_service);
+    for oidc_config in &config.oidc_providers {
+        assert_ne!(
+            oidc_config.name, FIREBASE_PROVIDER_NAME,
+            "OIDC provider name is reserved"
+        );
+        println!("Registering OIDC provider: {}", oidc_config.name);
+        identity_providers = identity_providers.register(Arc::new(OidcProvider::new(oidc_config)));
+    }
+    let identity_providers = Arc::new(identity_providers);
+
+    // Create refresh token service
+    let refresh_token_service = Arc::new(RefreshTokenService::new(
+        config.jwt.refresh_token_expiration_days,
+    ));
+
+    // Create session service recording where users are logged in
+    let session_service = Arc::new(SessionService::new(TOUCH_INTERVAL_SECONDS));
+
+    // Create token revocation service and keep its cache in sync with the database
+    let token_revocation_service =
+        Arc::new(TokenRevocationService::new(config.jwt.expiration_hours));
+    token_revocation_service
+        .reload(&db_pool)
+        .await
+        .expect("Failed to load token revocations");
+    token_revocation_service.spawn_reload_task(db_pool.clone());
+
+    // Create permission version service so role changes apply to already issued tokens
+    let permission_version_service = Arc::new(PermissionVersionService::new(VERSION_CACHE_SECONDS));
+    permission_version_service.spawn_assignment_sweep(db_pool.clone());
+
+    // Create API key service for scripts and service accounts
+    let api_key_service = Arc::new(ApiKeyService::new());
+
+    // Create rate limit service, counting in memory or in Postgres as RATE_LIMIT_STORE asks for
+    let rate_limit_service = Arc::new(RateLimitService::from_config(
+        config.rate_limit.clone(),
+        &db_pool,
+    ));
+    if config.rate_limit.enabled {
+        rate_limit_service.spawn_prune_task();
+    }
 
     // Create application state
-    let app_state = AppState::new(db_pool, jwt_service, firebase_service, config.clone());
+    let app_state = AppState::new(
+        db_pool,
+        jwt_service,
+        identity_providers,
+        refresh_token_service,
+        session_service,
+        token_revocation_service,
+        permission_version_service,
+        api_key_service,
+        rate_limit_service,
+        test_issuer,
+        config.clone(),
+    );
 
     // Determine port: Check PORT env var (Azure Container Apps standard), or use config default
     let port = std::env::var("PORT").unwrap_or_else(|_| {
@@ -77,7 +183,11 @@ async fn main() {
 
     let app = approuter::create_router(app_state);
 
-    axum::serve(listener, app)
-        .await
-        .expect("Error serving application");
+    // Peer addresses are needed to rate limit by client IP
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .expect("Error serving application");
 }
@@ -0,0 +1,70 @@
+use reqwest::Method;
+use windspire_backend::application::handlers::auth_handlers::{IdTokenAuthRequest, LogoutRequest};
+use windspire_backend::application::http_response::Message;
+
+use crate::client::{data, Tokens, WindspireClient};
+use crate::error::ClientError;
+use crate::models::{AuthTokenData, CurrentUser};
+
+impl WindspireClient {
+    /// Signs in with a Firebase ID token, signing up on the first login
+    pub async fn sign_in_with_firebase(
+        &self,
+        id_token: &str,
+    ) -> Result<AuthTokenData, ClientError> {
+        self.sign_in("/auth/firebase", id_token).await
+    }
+
+    /// Signs in with an ID token of a configured identity provider
+    pub async fn sign_in_with_provider(
+        &self,
+        provider: &str,
+        id_token: &str,
+    ) -> Result<AuthTokenData, ClientError> {
+        self.sign_in(&format!("/auth/providers/{}", provider), id_token)
+            .await
+    }
+
+    async fn sign_in(&self, path: &str, id_token: &str) -> Result<AuthTokenData, ClientError> {
+        let request = IdTokenAuthRequest {
+            id_token: id_token.to_string(),
+            display_name: None,
+        };
+        let response = self
+            .http()
+            .post(self.url(path))
+            .json(&request)
+            .send()
+            .await?;
+        let auth: AuthTokenData = data(response).await?;
+
+        self.set_tokens(Tokens {
+            access_token: auth.token.clone(),
+            refresh_token: auth.refresh_token.clone(),
+        })
+        .await;
+        Ok(auth)
+    }
+
+    /// Ends the session on the server and forgets the tokens
+    pub async fn logout(&self) -> Result<(), ClientError> {
+        let request = LogoutRequest {
+            refresh_token: self.tokens().await.map(|tokens| tokens.refresh_token),
+        };
+        let result = self.post::<_, Message>("/auth/logout", &request).await;
+        self.clear_credentials().await;
+        result.map(drop)
+    }
+
+    /// The signed-in user, with their roles and permissions
+    pub async fn me(&self) -> Result<CurrentUser, ClientError> {
+        self.get("/auth/me").await
+    }
+
+    /// Ends every session of the signed-in user, on all devices
+    pub async fn logout_all(&self) -> Result<(), ClientError> {
+        let result = self.execute(Method::POST, "/auth/logout/all").await;
+        self.clear_credentials().await;
+        result
+    }
+}
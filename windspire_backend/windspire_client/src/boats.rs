@@ -0,0 +1,81 @@
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::client::WindspireClient;
+use crate::error::ClientError;
+use crate::models::{
+    Boat, BoatCreate, BoatUpdate, BoatWithOwners, PaginatedResult, UserWithCountry,
+};
+
+impl WindspireClient {
+    /// A page of all boats; `page` starts at 1 and `limit` is at most 100
+    pub async fn boats(&self, page: u32, limit: u32) -> Result<PaginatedResult<Boat>, ClientError> {
+        self.get(&format!("/boats?page={}&limit={}", page, limit))
+            .await
+    }
+
+    /// Like [`WindspireClient::boats`], with the owners of every boat
+    pub async fn boats_with_owners(
+        &self,
+        page: u32,
+        limit: u32,
+    ) -> Result<PaginatedResult<BoatWithOwners>, ClientError> {
+        self.get(&format!(
+            "/boats?page={}&limit={}&include=owners",
+            page, limit
+        ))
+        .await
+    }
+
+    /// Boats owned by the signed-in user
+    pub async fn my_boats(&self) -> Result<Vec<Boat>, ClientError> {
+        self.get("/boats/my").await
+    }
+
+    /// Registers a boat owned by the signed-in user (`boats:create`)
+    pub async fn create_my_boat(&self, boat: &BoatCreate) -> Result<Boat, ClientError> {
+        self.post("/boats/my", boat).await
+    }
+
+    /// Registers a boat without an owner (`boats:write`)
+    pub async fn create_boat(&self, boat: &BoatCreate) -> Result<Boat, ClientError> {
+        self.post("/boats", boat).await
+    }
+
+    /// Updates a boat (`boats:write`, or `boats:write_own` for its owners)
+    pub async fn update_boat(&self, boat_id: Uuid, boat: &BoatUpdate) -> Result<Boat, ClientError> {
+        self.put(&format!("/boats/{}", boat_id), boat).await
+    }
+
+    pub async fn delete_boat(&self, boat_id: Uuid) -> Result<(), ClientError> {
+        self.execute(Method::DELETE, &format!("/boats/{}", boat_id))
+            .await
+    }
+
+    pub async fn boat_owners(&self, boat_id: Uuid) -> Result<Vec<UserWithCountry>, ClientError> {
+        self.get_bare(&format!("/boats/{}/owners", boat_id)).await
+    }
+
+    /// Boats owned by a user
+    pub async fn user_boats(&self, user_id: Uuid) -> Result<Vec<Boat>, ClientError> {
+        self.get_bare(&format!("/users/{}/boats", user_id)).await
+    }
+
+    /// Adds an owner; allowed for owners of the boat and with `boats:write`
+    pub async fn add_boat_owner(&self, boat_id: Uuid, user_id: Uuid) -> Result<(), ClientError> {
+        self.execute(
+            Method::POST,
+            &format!("/boats/{}/owners/{}", boat_id, user_id),
+        )
+        .await
+    }
+
+    /// Removes an owner; fails with a conflict for the last owner
+    pub async fn remove_boat_owner(&self, boat_id: Uuid, user_id: Uuid) -> Result<(), ClientError> {
+        self.execute(
+            Method::DELETE,
+            &format!("/boats/{}/owners/{}", boat_id, user_id),
+        )
+        .await
+    }
+}
@@ -0,0 +1,271 @@
+use std::sync::Arc;
+
+use reqwest::{header, Method, RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::Mutex;
+use windspire_backend::application::error::PROBLEM_JSON;
+use windspire_backend::application::handlers::auth_handlers::{
+    RefreshTokenRequest, TokenRefreshResponse,
+};
+use windspire_backend::application::http_response::ApiResponse;
+
+use crate::error::ClientError;
+use crate::models::ProblemDetails;
+
+/// The tokens of a signed-in client. Keep them to resume a session later
+/// with [`WindspireClient::set_tokens`]; the refresh token changes on every
+/// refresh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone)]
+enum Credentials {
+    None,
+    Session(Tokens),
+    /// API keys do not expire, so there is nothing to refresh
+    ApiKey(String),
+}
+
+impl Credentials {
+    fn bearer(&self) -> Option<&str> {
+        match self {
+            Credentials::None => None,
+            Credentials::Session(tokens) => Some(&tokens.access_token),
+            Credentials::ApiKey(key) => Some(key),
+        }
+    }
+}
+
+/// Client for one Windspire deployment. Cheap to clone; clones share the
+/// credentials, so a refresh made by one is seen by all.
+#[derive(Debug, Clone)]
+pub struct WindspireClient {
+    http: reqwest::Client,
+    /// Including the `/api/v1` prefix, without a trailing slash
+    base_url: String,
+    credentials: Arc<Mutex<Credentials>>,
+}
+
+impl WindspireClient {
+    /// A client for the API at `base_url`, e.g. `https://windspire.example/api/v1`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Like [`WindspireClient::new`], sending requests with `http` (timeouts,
+    /// proxies, ...)
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            credentials: Arc::new(Mutex::new(Credentials::None)),
+        }
+    }
+
+    /// Authenticates with an API key (`wsk_...`) instead of a login
+    pub async fn set_api_key(&self, api_key: impl Into<String>) {
+        *self.credentials.lock().await = Credentials::ApiKey(api_key.into());
+    }
+
+    /// Resumes a session from tokens kept earlier
+    pub async fn set_tokens(&self, tokens: Tokens) {
+        *self.credentials.lock().await = Credentials::Session(tokens);
+    }
+
+    /// The current tokens, if signed in with a login
+    pub async fn tokens(&self) -> Option<Tokens> {
+        match &*self.credentials.lock().await {
+            Credentials::Session(tokens) => Some(tokens.clone()),
+            _ => None,
+        }
+    }
+
+    /// Forgets the credentials without telling the server
+    pub async fn clear_credentials(&self) {
+        *self.credentials.lock().await = Credentials::None;
+    }
+
+    /// Exchanges the refresh token for new tokens. Calls refresh by
+    /// themselves when needed, so this is only useful to refresh ahead of time.
+    pub async fn refresh(&self) -> Result<(), ClientError> {
+        let mut credentials = self.credentials.lock().await;
+        self.refresh_tokens(&mut credentials).await
+    }
+
+    /// Exchanges the refresh token, holding the credentials lock so
+    /// concurrent calls that all saw an expired token refresh only once
+    async fn refresh_tokens(&self, credentials: &mut Credentials) -> Result<(), ClientError> {
+        let Credentials::Session(tokens) = &*credentials else {
+            return Err(ClientError::NotSignedIn);
+        };
+
+        let request = RefreshTokenRequest {
+            refresh_token: tokens.refresh_token.clone(),
+        };
+        let response = self
+            .http
+            .post(self.url("/auth/refresh"))
+            .json(&request)
+            .send()
+            .await?;
+
+        match data::<TokenRefreshResponse>(response).await {
+            Ok(refreshed) => {
+                *credentials = Credentials::Session(Tokens {
+                    access_token: refreshed.access_token,
+                    refresh_token: refreshed.refresh_token,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                // The refresh token is spent or revoked: the session is over
+                if e.status() == Some(StatusCode::UNAUTHORIZED) {
+                    *credentials = Credentials::None;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub(crate) fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        data(self.send(Method::GET, path, None::<&()>).await?).await
+    }
+
+    pub(crate) async fn post<B, T>(&self, path: &str, body: &B) -> Result<T, ClientError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        data(self.send(Method::POST, path, Some(body)).await?).await
+    }
+
+    pub(crate) async fn put<B, T>(&self, path: &str, body: &B) -> Result<T, ClientError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        data(self.send(Method::PUT, path, Some(body)).await?).await
+    }
+
+    /// For endpoints that send their data without the envelope
+    pub(crate) async fn get_bare<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        body(self.send(Method::GET, path, None::<&()>).await?).await
+    }
+
+    /// For endpoints that only confirm the action
+    pub(crate) async fn execute(&self, method: Method, path: &str) -> Result<(), ClientError> {
+        self.send(method, path, None::<&()>).await.map(drop)
+    }
+
+    /// Sends an authenticated request and checks its status. When the access
+    /// token expired, refreshes it and sends the request once more.
+    pub(crate) async fn send<B>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Response, ClientError>
+    where
+        B: Serialize + ?Sized,
+    {
+        let credentials = self.credentials.lock().await.clone();
+        let response = self
+            .request(method.clone(), path, body, credentials.bearer())
+            .send()
+            .await?;
+
+        let error = match check(response).await {
+            Err(e) if e.is_token_expired() && matches!(credentials, Credentials::Session(_)) => e,
+            result => return result,
+        };
+
+        match self.refresh_after(&credentials).await? {
+            Some(bearer) => {
+                let response = self
+                    .request(method, path, body, Some(&bearer))
+                    .send()
+                    .await?;
+                check(response).await
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Refreshes the tokens unless another call already replaced `stale`,
+    /// and returns the access token to retry with
+    async fn refresh_after(&self, stale: &Credentials) -> Result<Option<String>, ClientError> {
+        let mut credentials = self.credentials.lock().await;
+        if credentials.bearer() == stale.bearer() {
+            self.refresh_tokens(&mut credentials).await?;
+        }
+        Ok(credentials.bearer().map(str::to_string))
+    }
+
+    fn request<B>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+        bearer: Option<&str>,
+    ) -> RequestBuilder
+    where
+        B: Serialize + ?Sized,
+    {
+        let mut request = self.http.request(method, self.url(path));
+        if let Some(bearer) = bearer {
+            request = request.bearer_auth(bearer);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        request
+    }
+}
+
+/// Passes successful responses through and turns the others into errors
+pub(crate) async fn check(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let is_problem = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with(PROBLEM_JSON));
+    let body = response.text().await?;
+    if is_problem {
+        if let Ok(problem) = serde_json::from_str::<ProblemDetails>(&body) {
+            return Err(ClientError::Api(Box::new(problem)));
+        }
+    }
+    Err(ClientError::Status { status, body })
+}
+
+/// Decodes a response body as it is, for the few endpoints that send their
+/// data without the `{"success": true, "data": ...}` envelope
+pub(crate) async fn body<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    let status = response.status();
+    let bytes = response.bytes().await?;
+    serde_json::from_slice(&bytes).map_err(|source| ClientError::Decode { status, source })
+}
+
+/// Decodes the `data` of an enveloped response, checking the status first
+pub(crate) async fn data<T: DeserializeOwned>(response: Response) -> Result<T, ClientError> {
+    let response = check(response).await?;
+    body::<ApiResponse<T>>(response)
+        .await
+        .map(|envelope| envelope.data)
+}
@@ -0,0 +1,42 @@
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::client::WindspireClient;
+use crate::error::ClientError;
+use crate::models::{Country, CountryCreate, CountryUpdate};
+
+impl WindspireClient {
+    pub async fn countries(&self) -> Result<Vec<Country>, ClientError> {
+        self.get("/countries").await
+    }
+
+    pub async fn country(&self, country_id: Uuid) -> Result<Country, ClientError> {
+        self.get(&format!("/countries/{}", country_id)).await
+    }
+
+    /// A country by its ISO 3166 alpha-2 or alpha-3 code
+    pub async fn country_by_code(&self, code: &str) -> Result<Country, ClientError> {
+        self.get(&format!("/countries/code/{}", code)).await
+    }
+
+    /// Creates a country (`countries:write`)
+    pub async fn create_country(&self, country: &CountryCreate) -> Result<Country, ClientError> {
+        self.post("/countries", country).await
+    }
+
+    /// Updates a country (`countries:write`)
+    pub async fn update_country(
+        &self,
+        country_id: Uuid,
+        country: &CountryUpdate,
+    ) -> Result<Country, ClientError> {
+        self.put(&format!("/countries/{}", country_id), country)
+            .await
+    }
+
+    /// Deletes a country (`countries:delete`)
+    pub async fn delete_country(&self, country_id: Uuid) -> Result<(), ClientError> {
+        self.execute(Method::DELETE, &format!("/countries/{}", country_id))
+            .await
+    }
+}
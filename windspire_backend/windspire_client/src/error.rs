@@ -0,0 +1,166 @@
+use reqwest::StatusCode;
+
+use crate::models::ProblemDetails;
+
+/// Why a call failed
+#[derive(Debug)]
+pub enum ClientError {
+    /// The server answered with a problem document
+    Api(Box<ProblemDetails>),
+    /// The server answered with an error status but without a problem
+    /// document, e.g. a 405 or a proxy error page
+    Status { status: StatusCode, body: String },
+    /// The response body is not what the endpoint documents
+    Decode {
+        status: StatusCode,
+        source: serde_json::Error,
+    },
+    /// The request could not be sent or the response not read
+    Transport(reqwest::Error),
+    /// The call needs credentials and the client has none
+    NotSignedIn,
+}
+
+impl ClientError {
+    /// The problem document, when the server sent one
+    pub fn problem(&self) -> Option<&ProblemDetails> {
+        match self {
+            ClientError::Api(problem) => Some(problem),
+            _ => None,
+        }
+    }
+
+    /// The error code of the problem document, when the server sent one
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.problem()
+            .map(|problem| ErrorCode::parse(&problem.code))
+    }
+
+    /// The HTTP status the server answered with, if it answered
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api(problem) => StatusCode::from_u16(problem.status).ok(),
+            ClientError::Status { status, .. } | ClientError::Decode { status, .. } => {
+                Some(*status)
+            }
+            ClientError::Transport(e) => e.status(),
+            ClientError::NotSignedIn => None,
+        }
+    }
+
+    pub(crate) fn is_token_expired(&self) -> bool {
+        self.code() == Some(ErrorCode::TokenExpired)
+    }
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Api(problem) => write!(
+                f,
+                "{} ({}, correlation id {})",
+                problem.detail, problem.code, problem.correlation_id
+            ),
+            ClientError::Status { status, .. } => write!(f, "Request failed with {}", status),
+            ClientError::Decode { status, source } => {
+                write!(f, "Unexpected {} response body: {}", status, source)
+            }
+            ClientError::Transport(e) => write!(f, "Request failed: {}", e),
+            ClientError::NotSignedIn => write!(f, "Not signed in"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Decode { source, .. } => Some(source),
+            ClientError::Transport(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Transport(err)
+    }
+}
+
+/// The `code` of a problem document. Codes are stable across releases;
+/// ones this client does not know yet are kept as [`ErrorCode::Other`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorCode {
+    BadRequest,
+    ValidationFailed,
+    Unauthorized,
+    /// The access token expired; the client refreshes it by itself
+    TokenExpired,
+    Forbidden,
+    NotFound,
+    Conflict,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    RateLimited,
+    DatabaseError,
+    InternalError,
+    Other(String),
+}
+
+impl ErrorCode {
+    pub fn parse(code: &str) -> Self {
+        match code {
+            "bad_request" => ErrorCode::BadRequest,
+            "validation_failed" => ErrorCode::ValidationFailed,
+            "unauthorized" => ErrorCode::Unauthorized,
+            "token_expired" => ErrorCode::TokenExpired,
+            "forbidden" => ErrorCode::Forbidden,
+            "not_found" => ErrorCode::NotFound,
+            "conflict" => ErrorCode::Conflict,
+            "payload_too_large" => ErrorCode::PayloadTooLarge,
+            "unsupported_media_type" => ErrorCode::UnsupportedMediaType,
+            "rate_limited" => ErrorCode::RateLimited,
+            "database_error" => ErrorCode::DatabaseError,
+            "internal_error" => ErrorCode::InternalError,
+            other => ErrorCode::Other(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windspire_backend::application::error::AppError;
+
+    #[test]
+    fn test_every_server_code_is_known() {
+        let errors = [
+            AppError::BadRequest(String::new()),
+            AppError::Validation(Vec::new()),
+            AppError::Unauthorized(String::new()),
+            AppError::TokenExpired,
+            AppError::Forbidden(String::new()),
+            AppError::NotFound(String::new()),
+            AppError::Conflict(String::new()),
+            AppError::PayloadTooLarge,
+            AppError::UnsupportedMediaType,
+            AppError::TooManyRequests {
+                retry_after_seconds: 1,
+            },
+            AppError::Database(sqlx::Error::Protocol(String::new())),
+            AppError::Internal(String::new()),
+        ];
+
+        for error in errors {
+            assert!(
+                !matches!(ErrorCode::parse(error.code()), ErrorCode::Other(_)),
+                "Unknown server error code {}",
+                error.code()
+            );
+        }
+        assert_eq!(
+            ErrorCode::parse("something_new"),
+            ErrorCode::Other("something_new".to_string())
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Typed async client for the Windspire API.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), windspire_client::ClientError> {
+//! use windspire_client::WindspireClient;
+//!
+//! let client = WindspireClient::new("https://windspire.example/api/v1");
+//! client.sign_in_with_firebase("<Firebase ID token>").await?;
+//! let boats = client.my_boats().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A signed-in client refreshes its access token by itself when the server
+//! answers `token_expired`, and retries the request once. Errors carry the
+//! server's problem document, see [`ClientError`].
+
+mod auth;
+mod boats;
+mod client;
+mod countries;
+mod error;
+mod users;
+
+pub use client::{Tokens, WindspireClient};
+pub use error::{ClientError, ErrorCode};
+
+/// The request and response types, shared with the server
+pub mod models {
+    pub use windspire_backend::application::error::{FieldError, ProblemDetails};
+    pub use windspire_backend::application::handlers::auth_handlers::{
+        AuthTokenData, CurrentUser, UserInfo,
+    };
+    pub use windspire_backend::domain::interface::boat_repository::PaginatedResult;
+    pub use windspire_backend::domain::models::auth::Actor;
+    pub use windspire_backend::domain::models::boat::{Boat, BoatCreate, BoatUpdate};
+    pub use windspire_backend::domain::models::boat_owner::{BoatWithOwners, UserProfile};
+    pub use windspire_backend::domain::models::country::{Country, CountryCreate, CountryUpdate};
+    pub use windspire_backend::domain::models::user::{
+        AccountStatus, User, UserCreate, UserUpdate, UserWithCountry,
+    };
+}
@@ -0,0 +1,39 @@
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::client::WindspireClient;
+use crate::error::ClientError;
+use crate::models::{User, UserCreate, UserProfile, UserUpdate, UserWithCountry};
+
+impl WindspireClient {
+    pub async fn users(&self) -> Result<Vec<UserWithCountry>, ClientError> {
+        self.get("/users").await
+    }
+
+    /// A user (`users:read`, or `users:read_own` for the signed-in user)
+    pub async fn user(&self, user_id: Uuid) -> Result<User, ClientError> {
+        self.get(&format!("/users/{}", user_id)).await
+    }
+
+    /// A user together with the boats they own
+    pub async fn user_profile(&self, user_id: Uuid) -> Result<UserProfile, ClientError> {
+        self.get(&format!("/users/{}/profile", user_id)).await
+    }
+
+    /// Creates a user (`users:write`)
+    pub async fn create_user(&self, user: &UserCreate) -> Result<User, ClientError> {
+        self.post("/users", user).await
+    }
+
+    /// Updates a user (`users:write`, or `users:write_own` for the signed-in
+    /// user, who may not change their email address)
+    pub async fn update_user(&self, user_id: Uuid, user: &UserUpdate) -> Result<User, ClientError> {
+        self.put(&format!("/users/{}", user_id), user).await
+    }
+
+    /// Deletes a user (`users:delete`)
+    pub async fn delete_user(&self, user_id: Uuid) -> Result<(), ClientError> {
+        self.execute(Method::DELETE, &format!("/users/{}", user_id))
+            .await
+    }
+}
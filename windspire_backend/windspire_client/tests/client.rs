@@ -0,0 +1,546 @@
+//! Runs the client against the real router, served in-process on a loopback
+//! port. Needs a Postgres database in `DATABASE_URL`; migrations are applied.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+use windspire_backend::application::approuter::create_router;
+use windspire_backend::application::config::{
+    ApiVersionsConfig, AppConfig, AuthMode, CorsConfig, FirebaseConfig, JwtConfig, RateLimitConfig,
+    RateLimitRule, RateLimitStoreKind, UnverifiedEmailPolicy,
+};
+use windspire_backend::application::services::{
+    api_key_service::ApiKeyService,
+    firebase_service::FirebaseService,
+    identity_provider::IdentityProviders,
+    jwt_service::JwtService,
+    permission_version_service::PermissionVersionService,
+    rate_limit_service::RateLimitService,
+    refresh_token_service::RefreshTokenService,
+    session_service::SessionService,
+    test_issuer::{TestIdTokenRequest, TestIssuer},
+    token_revocation_service::TokenRevocationService,
+};
+use windspire_backend::application::state::AppState;
+use windspire_backend::domain::models::auth::AuthUser;
+use windspire_client::models::{
+    AccountStatus, BoatCreate, BoatUpdate, CountryCreate, CountryUpdate, UserUpdate,
+};
+use windspire_client::{ClientError, ErrorCode, Tokens, WindspireClient};
+
+const PROJECT_ID: &str = "windspire-test";
+
+struct TestServer {
+    base_url: String,
+    pool: PgPool,
+    config: AppConfig,
+    test_issuer: Arc<TestIssuer>,
+}
+
+impl TestServer {
+    async fn start() -> Self {
+        let database_url =
+            std::env::var("DATABASE_URL").expect("DATABASE_URL must point to a test database");
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect(&database_url)
+            .await
+            .expect("Failed to connect to the database");
+        sqlx::migrate!("../migrations")
+            .run(&pool)
+            .await
+            .expect("Failed to run migrations");
+
+        let config = test_config(database_url);
+        let test_issuer = Arc::new(TestIssuer::new(PROJECT_ID.to_string()));
+        let firebase_service =
+            FirebaseService::new(PROJECT_ID.to_string()).with_test_issuer(test_issuer.clone());
+        let token_revocation_service =
+            Arc::new(TokenRevocationService::new(config.jwt.expiration_hours));
+        token_revocation_service.reload(&pool).await.unwrap();
+
+        let app_state = AppState::new(
+            pool.clone(),
+            Arc::new(JwtService::new(config.jwt.clone()).unwrap()),
+            Arc::new(IdentityProviders::new().register(Arc::new(firebase_service))),
+            Arc::new(RefreshTokenService::new(
+                config.jwt.refresh_token_expiration_days,
+            )),
+            Arc::new(SessionService::new(60)),
+            token_revocation_service,
+            Arc::new(PermissionVersionService::new(0)),
+            Arc::new(ApiKeyService::new()),
+            Arc::new(RateLimitService::from_config(
+                config.rate_limit.clone(),
+                &pool,
+            )),
+            Some(test_issuer.clone()),
+            config.clone(),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = create_router(app_state);
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        Self {
+            base_url: format!("http://{}/api/v1", address),
+            pool,
+            config,
+            test_issuer,
+        }
+    }
+
+    fn client(&self) -> WindspireClient {
+        WindspireClient::new(&self.base_url)
+    }
+
+    /// A client signed in as a new user, granted `roles` on top of the default role
+    async fn signed_in_client(&self, roles: &[&str]) -> (WindspireClient, Uuid) {
+        let uid = Uuid::new_v4().to_string();
+        let client = self.client();
+        let auth = client
+            .sign_in_with_firebase(&self.id_token(&uid))
+            .await
+            .unwrap();
+        let user_id: Uuid = auth.user.id.parse().unwrap();
+
+        if !roles.is_empty() {
+            for role in roles {
+                sqlx::query(
+                    "INSERT INTO user_roles (user_id, role_id) SELECT $1, id FROM roles WHERE name = $2",
+                )
+                .bind(user_id)
+                .bind(role)
+                .execute(&self.pool)
+                .await
+                .unwrap();
+            }
+            // Sign in again for a token carrying the new permissions
+            client
+                .sign_in_with_firebase(&self.id_token(&uid))
+                .await
+                .unwrap();
+        }
+        (client, user_id)
+    }
+
+    fn id_token(&self, uid: &str) -> String {
+        self.id_token_with_email(uid, &format!("{}@client-test.windspire.example", uid), true)
+    }
+
+    fn id_token_with_email(&self, uid: &str, email: &str, email_verified: bool) -> String {
+        self.test_issuer
+            .issue_id_token(&TestIdTokenRequest {
+                uid: uid.to_string(),
+                email: Some(email.to_string()),
+                name: Some("Client Test".to_string()),
+                email_verified: Some(email_verified),
+            })
+            .unwrap()
+    }
+
+    /// An access token for `user_id` that expired an hour ago
+    fn expired_access_token(&self, user_id: Uuid) -> String {
+        let mut jwt = self.config.jwt.clone();
+        jwt.expiration_hours = -1;
+        let user = AuthUser {
+            id: user_id,
+            email: "expired@client-test.windspire.example".to_string(),
+            first_name: "Client".to_string(),
+            last_name: "Test".to_string(),
+            provider_id: String::new(),
+            provider_name: String::new(),
+            avatar_url: None,
+            roles: Vec::new(),
+            permissions: Vec::new(),
+            permission_version: 0,
+        };
+        JwtService::new(jwt).unwrap().generate_token(&user).unwrap()
+    }
+}
+
+/// Countries are seeded by the migrations
+async fn norway(client: &WindspireClient) -> Uuid {
+    client.country_by_code("NO").await.unwrap().id
+}
+
+fn test_config(database_url: String) -> AppConfig {
+    let unlimited = RateLimitRule::new(u32::MAX, 1);
+    AppConfig {
+        database_url,
+        server_address: "127.0.0.1:0".to_string(),
+        auth_mode: AuthMode::TestIssuer,
+        unverified_email_policy: UnverifiedEmailPolicy::Restricted,
+        firebase: FirebaseConfig {
+            project_id: PROJECT_ID.to_string(),
+            certificates_url: String::new(),
+        },
+        oidc_providers: Vec::new(),
+        jwt: JwtConfig {
+            secret: "client-test-secret".to_string(),
+            expiration_hours: 1,
+            impersonation_expiration_minutes: 15,
+            issuer: "windspire".to_string(),
+            audience: "windspire-api".to_string(),
+            refresh_token_expiration_days: 1,
+            algorithm: "HS256".to_string(),
+            signing_key_id: "default".to_string(),
+            signing_key_file: None,
+            retired_keys: Vec::new(),
+            retired_key_grace_hours: 1,
+        },
+        cors: CorsConfig {
+            allowed_origins: Vec::new(),
+            allowed_methods: Vec::new(),
+            allowed_headers: Vec::new(),
+        },
+        rate_limit: RateLimitConfig {
+            enabled: false,
+            store: RateLimitStoreKind::Memory,
+            trust_forwarded_for: false,
+            login: unlimited,
+            refresh: unlimited,
+            public: unlimited,
+            api: unlimited,
+        },
+        api_versions: ApiVersionsConfig::default(),
+    }
+}
+
+fn boat(name: &str, country_id: Uuid) -> BoatCreate {
+    BoatCreate {
+        name: name.to_string(),
+        brand: Some("Beneteau".to_string()),
+        model: None,
+        sail_number: None,
+        country_id,
+    }
+}
+
+#[tokio::test]
+async fn test_boats_and_owners() {
+    let server = TestServer::start().await;
+    let (owner, owner_id) = server.signed_in_client(&[]).await;
+    let country_id = norway(&owner).await;
+    let (crew, crew_id) = server.signed_in_client(&[]).await;
+
+    let me = owner.me().await.unwrap();
+    assert_eq!(me.id, owner_id);
+
+    let created = owner
+        .create_my_boat(&boat("Aurora", country_id))
+        .await
+        .unwrap();
+    assert_eq!(created.name, "Aurora");
+    let my_boats = owner.my_boats().await.unwrap();
+    assert!(my_boats.iter().any(|b| b.id == created.id));
+
+    owner.add_boat_owner(created.id, crew_id).await.unwrap();
+    let owners = crew.boat_owners(created.id).await.unwrap();
+    assert_eq!(owners.len(), 2);
+    let crew_boats = crew.user_boats(crew_id).await.unwrap();
+    assert_eq!(crew_boats.len(), 1);
+    let profile = owner.user_profile(crew_id).await.unwrap();
+    assert_eq!(profile.boat_count, 1);
+
+    owner.remove_boat_owner(created.id, crew_id).await.unwrap();
+    let last_owner = owner.remove_boat_owner(created.id, owner_id).await;
+    assert_eq!(last_owner.unwrap_err().code(), Some(ErrorCode::Conflict));
+
+    let page = owner.boats(1, 100).await.unwrap();
+    assert_eq!(page.page, 1);
+    assert!(page.total >= 1);
+    let with_owners = owner.boats_with_owners(1, 1).await.unwrap();
+    assert_eq!(with_owners.data.len(), 1);
+}
+
+#[tokio::test]
+async fn test_admin_operations() {
+    let server = TestServer::start().await;
+    let (admin, _) = server.signed_in_client(&["admin"]).await;
+    let (user, user_id) = server.signed_in_client(&[]).await;
+
+    // User-assigned codes, never seeded; the country is deleted again below
+    let letters: String = Uuid::new_v4().as_bytes()[..2]
+        .iter()
+        .map(|byte| char::from(b'A' + byte % 26))
+        .collect();
+    let country = admin
+        .create_country(&CountryCreate {
+            iso_name: format!("Client test {}", letters),
+            iso_alpha_2: "XX".to_string(),
+            iso_alpha_3: format!("X{}", letters),
+        })
+        .await
+        .unwrap();
+    let renamed = admin
+        .update_country(
+            country.id,
+            &CountryUpdate {
+                iso_name: format!("Client test {} renamed", letters),
+                iso_alpha_2: "XX".to_string(),
+                iso_alpha_3: format!("X{}", letters),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        admin.country(country.id).await.unwrap().iso_name,
+        renamed.iso_name
+    );
+    admin.delete_country(country.id).await.unwrap();
+
+    let boat = admin
+        .create_boat(&boat("Borealis", norway(&admin).await))
+        .await
+        .unwrap();
+    let updated = admin
+        .update_boat(
+            boat.id,
+            &BoatUpdate {
+                name: "Borealis II".to_string(),
+                brand: None,
+                model: None,
+                sail_number: None,
+                country_id: boat.country_id,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.name, "Borealis II");
+    admin.delete_boat(boat.id).await.unwrap();
+
+    assert_eq!(admin.user(user_id).await.unwrap().id, user_id);
+    assert!(admin.users().await.unwrap().iter().any(|u| u.id == user_id));
+
+    let forbidden = user.delete_user(user_id).await.unwrap_err();
+    assert_eq!(forbidden.code(), Some(ErrorCode::Forbidden));
+    admin.delete_user(user_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_own_permissions_cover_only_own_resources() {
+    let server = TestServer::start().await;
+    let (owner, owner_id) = server.signed_in_client(&[]).await;
+    let (other, other_id) = server.signed_in_client(&[]).await;
+    let country_id = norway(&owner).await;
+
+    // users:read_own and users:write_own
+    let me = owner.user(owner_id).await.unwrap();
+    let forbidden = owner.user(other_id).await.unwrap_err();
+    assert_eq!(forbidden.code(), Some(ErrorCode::Forbidden));
+
+    let mut update = UserUpdate {
+        first_name: "Renamed".to_string(),
+        last_name: me.last_name.clone(),
+        email: me.email.clone(),
+        phone: None,
+        country_id,
+    };
+    let renamed = owner.update_user(owner_id, &update).await.unwrap();
+    assert_eq!(renamed.first_name, "Renamed");
+    let forbidden = other.update_user(owner_id, &update).await.unwrap_err();
+    assert_eq!(forbidden.code(), Some(ErrorCode::Forbidden));
+
+    update.email = format!("{}@client-test.windspire.example", Uuid::new_v4());
+    let new_email = owner.update_user(owner_id, &update).await.unwrap_err();
+    assert_eq!(new_email.code(), Some(ErrorCode::Forbidden));
+
+    // boats:write_own
+    let created = owner
+        .create_my_boat(&boat("Cassiopeia", country_id))
+        .await
+        .unwrap();
+    let rename = BoatUpdate {
+        name: "Cassiopeia II".to_string(),
+        brand: None,
+        model: None,
+        sail_number: None,
+        country_id,
+    };
+    let updated = owner.update_boat(created.id, &rename).await.unwrap();
+    assert_eq!(updated.name, "Cassiopeia II");
+    let forbidden = other.update_boat(created.id, &rename).await.unwrap_err();
+    assert_eq!(forbidden.code(), Some(ErrorCode::Forbidden));
+}
+
+#[tokio::test]
+async fn test_only_the_account_email_activates_a_restricted_account() {
+    let server = TestServer::start().await;
+    let uid = Uuid::new_v4().to_string();
+    let claimed = format!("{}@client-test.windspire.example", uid);
+    let own = format!("own-{}@client-test.windspire.example", uid);
+
+    let client = server.client();
+    client
+        .sign_in_with_firebase(&server.id_token_with_email(&uid, &claimed, false))
+        .await
+        .unwrap();
+    assert_eq!(
+        client.me().await.unwrap().account_status,
+        AccountStatus::Restricted
+    );
+
+    // Linking an identity with a verified address of its own is refused
+    let link = reqwest::Client::new()
+        .post(format!("{}/auth/identities", server.base_url))
+        .bearer_auth(client.tokens().await.unwrap().access_token)
+        .json(&serde_json::json!({
+            "provider": "firebase",
+            "id_token": server.id_token_with_email(&Uuid::new_v4().to_string(), &own, true),
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(link.status(), reqwest::StatusCode::FORBIDDEN);
+
+    // A verified address other than the account's does not activate it
+    client
+        .sign_in_with_firebase(&server.id_token_with_email(&uid, &own, true))
+        .await
+        .unwrap();
+    assert_eq!(
+        client.me().await.unwrap().account_status,
+        AccountStatus::Restricted
+    );
+
+    client
+        .sign_in_with_firebase(&server.id_token_with_email(&uid, &claimed, true))
+        .await
+        .unwrap();
+    assert_eq!(
+        client.me().await.unwrap().account_status,
+        AccountStatus::Active
+    );
+}
+
+#[tokio::test]
+async fn test_owner_changes_are_refused_while_impersonating() {
+    let server = TestServer::start().await;
+    let (admin, admin_id) = server.signed_in_client(&["admin"]).await;
+    let (owner, owner_id) = server.signed_in_client(&[]).await;
+    let country_id = norway(&owner).await;
+    let created = owner
+        .create_my_boat(&boat("Dorado", country_id))
+        .await
+        .unwrap();
+
+    let response: serde_json::Value = reqwest::Client::new()
+        .post(format!(
+            "{}/admin/users/{}/impersonate",
+            server.base_url, owner_id
+        ))
+        .bearer_auth(admin.tokens().await.unwrap().access_token)
+        .json(&serde_json::json!({ "reason": "Client test" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let impersonating = server.client();
+    impersonating
+        .set_api_key(response["data"]["token"].as_str().unwrap())
+        .await;
+
+    let add = impersonating
+        .add_boat_owner(created.id, admin_id)
+        .await
+        .unwrap_err();
+    assert_eq!(add.code(), Some(ErrorCode::Forbidden));
+    let remove = impersonating
+        .remove_boat_owner(created.id, owner_id)
+        .await
+        .unwrap_err();
+    assert_eq!(remove.code(), Some(ErrorCode::Forbidden));
+    assert_eq!(owner.boat_owners(created.id).await.unwrap().len(), 1);
+
+    // Editing the boat only does what the owner could do themselves
+    let rename = BoatUpdate {
+        name: "Dorado II".to_string(),
+        brand: None,
+        model: None,
+        sail_number: None,
+        country_id,
+    };
+    impersonating
+        .update_boat(created.id, &rename)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_expired_access_token_is_refreshed() {
+    let server = TestServer::start().await;
+    let (client, user_id) = server.signed_in_client(&[]).await;
+    let tokens = client.tokens().await.unwrap();
+
+    let expired = Tokens {
+        access_token: server.expired_access_token(user_id),
+        refresh_token: tokens.refresh_token.clone(),
+    };
+    client.set_tokens(expired.clone()).await;
+
+    // Concurrent calls share a single refresh; the refresh token is single use
+    let (me, boats) = tokio::join!(client.me(), client.my_boats());
+    assert_eq!(me.unwrap().id, user_id);
+    boats.unwrap();
+
+    let refreshed = client.tokens().await.unwrap();
+    assert_ne!(refreshed.access_token, expired.access_token);
+    assert_ne!(refreshed.refresh_token, expired.refresh_token);
+}
+
+#[tokio::test]
+async fn test_failed_refresh_ends_the_session() {
+    let server = TestServer::start().await;
+    let (client, user_id) = server.signed_in_client(&[]).await;
+    client
+        .set_tokens(Tokens {
+            access_token: server.expired_access_token(user_id),
+            refresh_token: "not-a-refresh-token".to_string(),
+        })
+        .await;
+
+    let error = client.me().await.unwrap_err();
+    assert_eq!(error.code(), Some(ErrorCode::Unauthorized));
+    assert_eq!(client.tokens().await, None);
+    assert!(matches!(client.me().await, Err(ClientError::Api(_))));
+}
+
+#[tokio::test]
+async fn test_errors_carry_the_problem_document() {
+    let server = TestServer::start().await;
+    let (client, _) = server.signed_in_client(&[]).await;
+
+    let missing = client.country(Uuid::new_v4()).await.unwrap_err();
+    assert_eq!(missing.code(), Some(ErrorCode::NotFound));
+    assert_eq!(missing.status().map(|s| s.as_u16()), Some(404));
+    assert!(!missing.problem().unwrap().correlation_id.is_empty());
+
+    let invalid = client
+        .create_my_boat(&boat("A", norway(&client).await))
+        .await
+        .unwrap_err();
+    assert_eq!(invalid.code(), Some(ErrorCode::ValidationFailed));
+    let errors = &invalid.problem().unwrap().errors;
+    assert_eq!(errors[0].field.as_deref(), Some("name"));
+
+    client.logout().await.unwrap();
+    assert!(matches!(
+        client.me().await,
+        Err(e) if e.code() == Some(ErrorCode::Unauthorized)
+    ));
+}
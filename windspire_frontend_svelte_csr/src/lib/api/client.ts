@@ -28,7 +28,7 @@ class ApiClient {
 					if (errorText) {
 						try {
 							const errorData = JSON.parse(errorText);
-							errorMessage = errorData.message || errorData.error || errorMessage;
+							errorMessage = errorData.detail || errorData.message || errorData.error || errorMessage;
 						} catch {
 							// If not JSON, use the text directly
 							errorMessage = errorText || errorMessage;
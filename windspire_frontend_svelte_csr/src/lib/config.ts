@@ -1,7 +1,7 @@
 // Environment configuration for different deployment environments
 export const config = {
 	// API base URL - will be replaced by Vite at build time
-	API_BASE_URL: import.meta.env.VITE_API_BASE_URL || 'http://localhost:8080/api',
+	API_BASE_URL: import.meta.env.VITE_API_BASE_URL || 'http://localhost:8080/api/v1',
 
 	// Firebase configuration - will be replaced by Vite at build time
 	FIREBASE_CONFIG: {